@@ -0,0 +1,383 @@
+mod noise;
+pub use noise::Noise;
+
+mod pulse;
+pub use pulse::Pulse;
+
+mod resampler;
+pub use resampler::{HighPass, Resampler, Sample};
+
+mod scope;
+pub use scope::{Scope, SCOPE_LENGTH};
+
+mod units;
+pub use units::{Envelope, Length, Sweep};
+
+mod wave;
+pub use wave::{Wave, WAVE_RAM_SIZE};
+
+pub const NR10_ADDR: u16 = 0xff10;
+pub const NR14_ADDR: u16 = 0xff14;
+pub const NR20_ADDR: u16 = 0xff15;
+pub const NR24_ADDR: u16 = 0xff19;
+pub const NR30_ADDR: u16 = 0xff1a;
+pub const NR34_ADDR: u16 = 0xff1e;
+pub const NR40_ADDR: u16 = 0xff1f;
+pub const NR44_ADDR: u16 = 0xff23;
+pub const NR50_ADDR: u16 = 0xff24;
+pub const NR51_ADDR: u16 = 0xff25;
+pub const NR52_ADDR: u16 = 0xff26;
+pub const WAVE_RAM_ADDR: u16 = 0xff30;
+pub const WAVE_RAM_ADDR_END: u16 = 0xff3f;
+
+/// The bits of `NR10`-`NR52` that always read as 1, indexed from `NR10`.
+/// Unused registers read as 0xff.
+const READ_MASKS: [u8; 23] = [
+    0x80, 0x3f, 0x00, 0xff, 0xbf, // NR10-NR14
+    0xff, 0x3f, 0x00, 0xff, 0xbf, // NR20-NR24
+    0x7f, 0xff, 0x9f, 0xff, 0xbf, // NR30-NR34
+    0xff, 0xff, 0x00, 0x00, 0xbf, // NR40-NR44
+    0x00, 0x00, 0x70, // NR50-NR52
+];
+
+/// The number of channels.
+pub const CHANNELS: usize = 4;
+
+/// The number of T-cycles per second.
+pub const CLOCK_RATE: u32 = 4_194_304;
+/// The rate at which the output of the APU is sampled (one sample per M-cycle).
+pub const APU_CLOCK_RATE: u32 = 1_048_576;
+/// The number of T-cycles between two samples of the APU output.
+const CYCLES_PER_SAMPLE: u32 = 4;
+/// The output sample rate used until another one is set.
+pub const DEFAULT_SAMPLE_RATE: u32 = 48_000;
+
+/// The audio processing unit, with its four channels.
+#[derive(Debug, Clone)]
+pub struct Apu {
+    pub ch1: Pulse,
+    pub ch2: Pulse,
+    pub ch3: Wave,
+    pub ch4: Noise,
+    /// The master volume and VIN panning (`NR50`).
+    pub nr50: u8,
+    /// The panning of each channel (`NR51`): bits 4-7 left, bits 0-3 right.
+    pub nr51: u8,
+    /// Whether the APU is powered on (`NR52` bit 7).
+    pub powered: bool,
+    /// The next step of the frame sequencer (0-7).
+    pub frame_step: u8,
+    /// The T-cycles elapsed since the output was last sampled.
+    sample_cycles: u32,
+    resampler: Resampler,
+    high_pass: HighPass,
+    /// The resamplers of the individual channel outputs, when they are captured.
+    channel_resamplers: Option<Vec<Resampler>>,
+    /// The channels left out of the mix, for debugging.
+    pub muted: [bool; CHANNELS],
+    /// The recent outputs of each channel, for debugging.
+    pub scope: Scope,
+}
+
+impl Default for Apu {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Apu {
+    /// Creates a new `Apu`, powered off.
+    pub fn new() -> Self {
+        Self {
+            ch1: Pulse::new(true),
+            ch2: Pulse::new(false),
+            ch3: Wave::new(),
+            ch4: Noise::new(),
+            nr50: 0,
+            nr51: 0,
+            powered: false,
+            frame_step: 0,
+            sample_cycles: 0,
+            resampler: Resampler::new(APU_CLOCK_RATE, DEFAULT_SAMPLE_RATE),
+            high_pass: HighPass::new(DEFAULT_SAMPLE_RATE, false),
+            channel_resamplers: None,
+            muted: [false; CHANNELS],
+            scope: Scope::default(),
+        }
+    }
+
+    /// Sets the rate of the samples returned by `take_samples`, and the
+    /// model whose output capacitor is imitated.
+    pub fn set_sample_rate(&mut self, sample_rate: u32, cgb: bool) {
+        self.resampler = Resampler::new(APU_CLOCK_RATE, sample_rate);
+        self.high_pass = HighPass::new(sample_rate, cgb);
+        if self.channel_resamplers.is_some() {
+            self.set_channel_capture(true);
+        }
+    }
+
+    /// Returns the rate of the samples returned by `take_samples`.
+    pub fn sample_rate(&self) -> u32 {
+        self.resampler.sample_rate()
+    }
+
+    /// Starts or stops resampling the output of each channel on its own.
+    pub fn set_channel_capture(&mut self, enabled: bool) {
+        self.channel_resamplers = enabled.then(|| {
+            let sample_rate = self.resampler.sample_rate();
+            (0..CHANNELS)
+                .map(|_| Resampler::new(APU_CLOCK_RATE, sample_rate))
+                .collect()
+        });
+    }
+
+    /// Returns the samples of each channel's DAC produced since the last call,
+    /// before panning and filtering, if they are captured.
+    pub fn take_channel_samples(&mut self) -> Option<Vec<Vec<f32>>> {
+        let resamplers = self.channel_resamplers.as_mut()?;
+        let samples = resamplers
+            .iter_mut()
+            .map(|resampler| {
+                let samples = resampler.take_samples();
+                samples.into_iter().map(|(sample, _)| sample).collect()
+            })
+            .collect();
+        Some(samples)
+    }
+
+    /// Returns the stereo samples produced since the last call, at the output sample rate.
+    pub fn take_samples(&mut self) -> Vec<Sample> {
+        let mut samples = self.resampler.take_samples();
+        for sample in &mut samples {
+            *sample = self.high_pass.filter(*sample);
+        }
+        samples
+    }
+
+    /// Returns the value of the given sound register or wave RAM byte.
+    pub fn read_register(&self, addr: u16) -> u8 {
+        let value = match addr {
+            NR10_ADDR..=NR14_ADDR => self.ch1.read(addr - NR10_ADDR),
+            NR20_ADDR..=NR24_ADDR => self.ch2.read(addr - NR20_ADDR),
+            NR30_ADDR..=NR34_ADDR => self.ch3.read(addr - NR30_ADDR),
+            NR40_ADDR..=NR44_ADDR => self.ch4.read(addr - NR40_ADDR),
+            NR50_ADDR => self.nr50,
+            NR51_ADDR => self.nr51,
+            NR52_ADDR => (self.powered as u8) << 7 | self.channels_enabled(),
+            WAVE_RAM_ADDR..=WAVE_RAM_ADDR_END => {
+                return self.ch3.ram[(addr - WAVE_RAM_ADDR) as usize]
+            }
+            _ => 0xff,
+        };
+        value | READ_MASKS.get((addr - NR10_ADDR) as usize).unwrap_or(&0xff)
+    }
+
+    /// Writes the given sound register or wave RAM byte.
+    /// While powered off, only `NR52` and wave RAM can be written.
+    pub fn write_register(&mut self, addr: u16, value: u8) {
+        if !self.powered && !matches!(addr, NR52_ADDR | WAVE_RAM_ADDR..=WAVE_RAM_ADDR_END) {
+            return;
+        }
+
+        // In the first half of a length period, enabling a length counter clocks it
+        let extra_length_clock = self.frame_step % 2 == 1;
+        match addr {
+            NR10_ADDR..=NR14_ADDR => self.ch1.write(addr - NR10_ADDR, value, extra_length_clock),
+            NR20_ADDR..=NR24_ADDR => self.ch2.write(addr - NR20_ADDR, value, extra_length_clock),
+            NR30_ADDR..=NR34_ADDR => self.ch3.write(addr - NR30_ADDR, value, extra_length_clock),
+            NR40_ADDR..=NR44_ADDR => self.ch4.write(addr - NR40_ADDR, value, extra_length_clock),
+            NR50_ADDR => self.nr50 = value,
+            NR51_ADDR => self.nr51 = value,
+            NR52_ADDR => self.set_power(value & 0b1000_0000 != 0),
+            WAVE_RAM_ADDR..=WAVE_RAM_ADDR_END => {
+                self.ch3.ram[(addr - WAVE_RAM_ADDR) as usize] = value
+            }
+            _ => {}
+        }
+    }
+
+    /// Powers the APU on or off. Powering off clears every register but wave RAM.
+    fn set_power(&mut self, on: bool) {
+        if on && !self.powered {
+            self.frame_step = 0;
+        } else if !on && self.powered {
+            let ram = self.ch3.ram;
+            *self = Self {
+                sample_cycles: self.sample_cycles,
+                resampler: self.resampler.clone(),
+                high_pass: self.high_pass.clone(),
+                channel_resamplers: self.channel_resamplers.take(),
+                muted: self.muted,
+                scope: self.scope.clone(),
+                ..Self::new()
+            };
+            self.ch3.ram = ram;
+        }
+        self.powered = on;
+    }
+
+    /// Returns the status bits of `NR52`: which channels are playing.
+    fn channels_enabled(&self) -> u8 {
+        (self.ch1.enabled as u8)
+            | (self.ch2.enabled as u8) << 1
+            | (self.ch3.enabled as u8) << 2
+            | (self.ch4.enabled as u8) << 3
+    }
+
+    /// Advances the channels by the given number of cycles, sampling the output as it goes.
+    pub fn tick(&mut self, cycles: u32) {
+        for _ in 0..cycles {
+            if self.powered {
+                self.ch1.step();
+                self.ch2.step();
+                self.ch3.step();
+                self.ch4.step();
+            }
+
+            self.sample_cycles += 1;
+            if self.sample_cycles == CYCLES_PER_SAMPLE {
+                self.sample_cycles = 0;
+                let outputs = self.channel_outputs();
+                self.resampler.push(self.mix(outputs));
+                self.scope.push(outputs);
+                if let Some(resamplers) = &mut self.channel_resamplers {
+                    for (resampler, output) in resamplers.iter_mut().zip(outputs) {
+                        resampler.push((output, output));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Advances the 512 Hz frame sequencer, which is clocked by `DIV`.
+    pub fn clock_frame_sequencer(&mut self) {
+        if !self.powered {
+            return;
+        }
+
+        if self.frame_step.is_multiple_of(2) {
+            self.ch1.clock_length();
+            self.ch2.clock_length();
+            self.ch3.clock_length();
+            self.ch4.clock_length();
+        }
+        if self.frame_step == 2 || self.frame_step == 6 {
+            self.ch1.clock_sweep();
+        }
+        if self.frame_step == 7 {
+            self.ch1.envelope.clock();
+            self.ch2.envelope.clock();
+            self.ch4.envelope.clock();
+        }
+
+        self.frame_step = (self.frame_step + 1) % 8;
+    }
+
+    /// Returns the analog output of each channel's DAC, between -1.0 and 1.0.
+    /// A channel whose DAC is off outputs 0.0.
+    pub fn channel_outputs(&self) -> [f32; CHANNELS] {
+        let dac = |enabled: bool, digital: u8| {
+            if enabled {
+                1.0 - digital as f32 / 7.5
+            } else {
+                0.0
+            }
+        };
+
+        [
+            dac(self.ch1.envelope.dac_enabled(), self.ch1.output()),
+            dac(self.ch2.envelope.dac_enabled(), self.ch2.output()),
+            dac(self.ch3.dac_enabled, self.ch3.output()),
+            dac(self.ch4.envelope.dac_enabled(), self.ch4.output()),
+        ]
+    }
+
+    /// Returns the left and right outputs, mixed according to `NR51` and
+    /// scaled by the master volume of `NR50`, between -1.0 and 1.0.
+    pub fn output(&self) -> (f32, f32) {
+        self.mix(self.channel_outputs())
+    }
+
+    /// Mixes the given channel outputs into the left and right outputs,
+    /// leaving out the muted channels.
+    fn mix(&self, channels: [f32; CHANNELS]) -> (f32, f32) {
+        let mix = |shift: u8| {
+            let sum: f32 = (0..CHANNELS)
+                .filter(|channel| !self.muted[*channel])
+                .filter(|channel| self.nr51 & (1 << (channel + shift as usize)) != 0)
+                .map(|channel| channels[channel])
+                .sum();
+            sum / CHANNELS as f32
+        };
+        let volume = |shift: u8| (((self.nr50 >> shift) & 0b111) + 1) as f32 / 8.0;
+
+        (mix(4) * volume(4), mix(0) * volume(0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn powered_apu() -> Apu {
+        let mut apu = Apu::new();
+        apu.write_register(NR52_ADDR, 0x80);
+        apu
+    }
+
+    #[test]
+    fn test_register_read_masks() {
+        let mut apu = powered_apu();
+        apu.write_register(NR10_ADDR + 1, 0b1011_1111);
+        assert_eq!(apu.read_register(NR10_ADDR + 1), 0b1011_1111);
+        assert_eq!(apu.read_register(NR10_ADDR + 3), 0xff);
+        assert_eq!(apu.read_register(NR52_ADDR), 0xf0);
+        assert_eq!(apu.read_register(0xff27), 0xff);
+    }
+
+    #[test]
+    fn test_power_off_clears_registers() {
+        let mut apu = powered_apu();
+        apu.write_register(NR51_ADDR, 0xff);
+        apu.write_register(WAVE_RAM_ADDR, 0x12);
+        apu.write_register(NR52_ADDR, 0x00);
+
+        assert_eq!(apu.read_register(NR51_ADDR), 0x00);
+        apu.write_register(NR51_ADDR, 0xff);
+        assert_eq!(apu.read_register(NR51_ADDR), 0x00);
+        assert_eq!(apu.read_register(WAVE_RAM_ADDR), 0x12);
+    }
+
+    #[test]
+    fn test_length_counter_stops_channel() {
+        let mut apu = powered_apu();
+        apu.write_register(NR20_ADDR + 2, 0xf0);
+        apu.write_register(NR20_ADDR + 1, 62);
+        apu.write_register(NR24_ADDR, 0b1100_0000);
+        assert_eq!(apu.read_register(NR52_ADDR) & 0b10, 0b10);
+
+        for _ in 0..4 {
+            apu.clock_frame_sequencer();
+        }
+        assert_eq!(apu.read_register(NR52_ADDR) & 0b10, 0);
+    }
+
+    #[test]
+    fn test_pulse_output_and_panning() {
+        let mut apu = powered_apu();
+        apu.write_register(NR50_ADDR, 0x77);
+        apu.write_register(NR51_ADDR, 0b0001_0000);
+        apu.write_register(NR10_ADDR + 1, 0b1000_0000);
+        apu.write_register(NR10_ADDR + 2, 0xf0);
+        apu.write_register(NR14_ADDR, 0x87);
+
+        // Step through a whole period of the 50% duty cycle
+        let mut outputs = Vec::new();
+        for _ in 0..8 {
+            apu.tick((2048 - 0x700) * 4);
+            outputs.push(apu.output());
+        }
+        assert!(outputs.iter().all(|(_, right)| *right == 0.0));
+        assert_eq!(outputs.iter().filter(|(left, _)| *left < 0.0).count(), 4);
+    }
+}
@@ -0,0 +1,155 @@
+use super::units::{Envelope, Length};
+use super::CLOCK_RATE;
+
+/// The base periods selected by `NR43` bits 0-2.
+const DIVISORS: [u32; 8] = [8, 16, 32, 48, 64, 80, 96, 112];
+
+/// The noise channel (channel 4), driven by a linear feedback shift register.
+#[derive(Debug, Clone)]
+pub struct Noise {
+    /// Whether the channel is playing.
+    pub enabled: bool,
+    /// The clock shift (`NR43` bits 4-7).
+    pub shift: u8,
+    /// Whether the LFSR is shortened to 7 bits (`NR43` bit 3).
+    pub short: bool,
+    /// The divisor code (`NR43` bits 0-2).
+    pub divisor: u8,
+    timer: u32,
+    /// The 15-bit linear feedback shift register.
+    lfsr: u16,
+    pub length: Length,
+    pub envelope: Envelope,
+}
+
+impl Default for Noise {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Noise {
+    /// Creates a new noise channel.
+    pub fn new() -> Self {
+        Self {
+            enabled: false,
+            shift: 0,
+            short: false,
+            divisor: 0,
+            timer: 0,
+            lfsr: 0x7fff,
+            length: Length::new(64),
+            envelope: Envelope::default(),
+        }
+    }
+
+    /// Returns the value of the given register (0-4 for `NR40`-`NR44`), before masking.
+    pub fn read(&self, register: u16) -> u8 {
+        match register {
+            2 => self.envelope.read(),
+            3 => self.shift << 4 | (self.short as u8) << 3 | self.divisor,
+            4 => (self.length.enabled as u8) << 6,
+            _ => 0xff,
+        }
+    }
+
+    /// Writes the given register (0-4 for `NR40`-`NR44`).
+    pub fn write(&mut self, register: u16, value: u8, extra_length_clock: bool) {
+        match register {
+            1 => self.length.load((value & 0b0011_1111) as u16),
+            2 => {
+                self.envelope.write(value);
+                if !self.envelope.dac_enabled() {
+                    self.enabled = false;
+                }
+            }
+            3 => {
+                self.shift = value >> 4;
+                self.short = value & 0b1000 != 0;
+                self.divisor = value & 0b111;
+            }
+            4 => {
+                let trigger = value & 0b1000_0000 != 0;
+                let enable_length = value & 0b0100_0000 != 0;
+
+                if self
+                    .length
+                    .write_control(enable_length, trigger, extra_length_clock)
+                {
+                    self.enabled = false;
+                }
+                if trigger {
+                    self.enabled = self.envelope.dac_enabled();
+                    self.timer = self.period();
+                    self.lfsr = 0x7fff;
+                    self.envelope.trigger();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Returns the number of cycles between shifts of the LFSR.
+    fn period(&self) -> u32 {
+        DIVISORS[self.divisor as usize] << self.shift
+    }
+
+    /// Returns the number of times per second the LFSR is shifted.
+    pub fn frequency_hz(&self) -> f64 {
+        CLOCK_RATE as f64 / self.period() as f64
+    }
+
+    /// Advances the channel by a single cycle.
+    pub fn step(&mut self) {
+        self.timer = self.timer.saturating_sub(1);
+        if self.timer > 0 {
+            return;
+        }
+        self.timer = self.period();
+
+        let feedback = (self.lfsr ^ (self.lfsr >> 1)) & 1;
+        self.lfsr = (self.lfsr >> 1) | feedback << 14;
+        if self.short {
+            self.lfsr = (self.lfsr & !(1 << 6)) | feedback << 6;
+        }
+    }
+
+    /// Clocks the length counter.
+    pub fn clock_length(&mut self) {
+        if self.length.clock() {
+            self.enabled = false;
+        }
+    }
+
+    /// Returns the digital output of the channel (0-15).
+    pub fn output(&self) -> u8 {
+        if self.enabled && self.lfsr & 1 == 0 {
+            self.envelope.volume
+        } else {
+            0
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_short_lfsr_repeats() {
+        let mut noise = Noise::new();
+        noise.write(3, 0b1000, false);
+        noise.write(2, 0xf0, false);
+        noise.write(4, 0x80, false);
+
+        let mut states = Vec::new();
+        for _ in 0..254 {
+            for _ in 0..noise.period() {
+                noise.step();
+            }
+            states.push(noise.lfsr & 0x7f);
+        }
+        // The 7-bit LFSR has a period of 127 shifts
+        assert_eq!(states[..127], states[127..]);
+    }
+}
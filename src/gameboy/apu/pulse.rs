@@ -0,0 +1,148 @@
+use super::units::{Envelope, Length, Sweep};
+use super::CLOCK_RATE;
+
+/// The waveforms of the four duty cycles: 12.5%, 25%, 50% and 75%.
+const DUTY_PATTERNS: [u8; 4] = [0b0000_0001, 0b1000_0001, 0b1000_0111, 0b0111_1110];
+
+/// A pulse channel: channel 1 (with a frequency sweep) or channel 2.
+#[derive(Debug, Clone)]
+pub struct Pulse {
+    /// Whether the channel is playing.
+    pub enabled: bool,
+    /// The duty cycle (`NRx1` bits 6-7).
+    pub duty: u8,
+    /// The position within the duty cycle waveform.
+    duty_step: u8,
+    /// The 11-bit frequency (`NRx3` and `NRx4` bits 0-2).
+    pub frequency: u16,
+    timer: u16,
+    pub length: Length,
+    pub envelope: Envelope,
+    /// The frequency sweep, on channel 1 only.
+    pub sweep: Option<Sweep>,
+}
+
+impl Pulse {
+    /// Creates a new pulse channel, with a frequency sweep if requested.
+    pub fn new(sweep: bool) -> Self {
+        Self {
+            enabled: false,
+            duty: 0,
+            duty_step: 0,
+            frequency: 0,
+            timer: 0,
+            length: Length::new(64),
+            envelope: Envelope::default(),
+            sweep: sweep.then(Sweep::default),
+        }
+    }
+
+    /// Returns the value of the given register (0-4 for `NRx0`-`NRx4`), before masking.
+    pub fn read(&self, register: u16) -> u8 {
+        match register {
+            0 => self.sweep.as_ref().map_or(0xff, Sweep::read),
+            1 => self.duty << 6,
+            2 => self.envelope.read(),
+            4 => (self.length.enabled as u8) << 6,
+            _ => 0xff,
+        }
+    }
+
+    /// Writes the given register (0-4 for `NRx0`-`NRx4`).
+    pub fn write(&mut self, register: u16, value: u8, extra_length_clock: bool) {
+        match register {
+            0 => {
+                if let Some(sweep) = &mut self.sweep {
+                    if sweep.write(value) {
+                        self.enabled = false;
+                    }
+                }
+            }
+            1 => {
+                self.duty = value >> 6;
+                self.length.load((value & 0b0011_1111) as u16);
+            }
+            2 => {
+                self.envelope.write(value);
+                if !self.envelope.dac_enabled() {
+                    self.enabled = false;
+                }
+            }
+            3 => self.frequency = (self.frequency & 0x700) | value as u16,
+            4 => {
+                self.frequency = (self.frequency & 0xff) | ((value & 0b111) as u16) << 8;
+                let trigger = value & 0b1000_0000 != 0;
+                let enable_length = value & 0b0100_0000 != 0;
+
+                if self
+                    .length
+                    .write_control(enable_length, trigger, extra_length_clock)
+                {
+                    self.enabled = false;
+                }
+                if trigger {
+                    self.trigger();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Restarts the channel.
+    fn trigger(&mut self) {
+        self.enabled = self.envelope.dac_enabled();
+        self.timer = self.period();
+        self.envelope.trigger();
+
+        if let Some(sweep) = &mut self.sweep {
+            if sweep.trigger(self.frequency) {
+                self.enabled = false;
+            }
+        }
+    }
+
+    /// Returns the number of cycles between steps of the duty cycle.
+    fn period(&self) -> u16 {
+        (2048 - self.frequency) * 4
+    }
+
+    /// Returns the frequency of the duty cycle in Hz.
+    pub fn frequency_hz(&self) -> f64 {
+        CLOCK_RATE as f64 / (self.period() as f64 * 8.0)
+    }
+
+    /// Advances the channel by a single cycle.
+    pub fn step(&mut self) {
+        self.timer = self.timer.saturating_sub(1);
+        if self.timer == 0 {
+            self.timer = self.period();
+            self.duty_step = (self.duty_step + 1) % 8;
+        }
+    }
+
+    /// Clocks the length counter.
+    pub fn clock_length(&mut self) {
+        if self.length.clock() {
+            self.enabled = false;
+        }
+    }
+
+    /// Clocks the frequency sweep, if any.
+    pub fn clock_sweep(&mut self) {
+        if let Some(sweep) = &mut self.sweep {
+            if sweep.clock(&mut self.frequency) {
+                self.enabled = false;
+            }
+        }
+    }
+
+    /// Returns the digital output of the channel (0-15).
+    pub fn output(&self) -> u8 {
+        let high = DUTY_PATTERNS[self.duty as usize] >> (7 - self.duty_step) & 1 != 0;
+        if self.enabled && high {
+            self.envelope.volume
+        } else {
+            0
+        }
+    }
+}
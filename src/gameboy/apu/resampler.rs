@@ -0,0 +1,185 @@
+use std::f64::consts::PI;
+
+/// The number of output samples each amplitude change is spread over.
+const KERNEL_TAPS: usize = 16;
+/// The number of sub-sample positions the kernel is precomputed for.
+const KERNEL_PHASES: usize = 64;
+/// The cutoff of the low-pass kernel, as a fraction of the output Nyquist frequency.
+const CUTOFF: f64 = 0.9;
+
+/// A stereo sample: left, then right.
+pub type Sample = (f32, f32);
+
+/// Converts the APU output to a lower sample rate without aliasing.
+///
+/// Rather than sampling the output, every change in amplitude is added to the
+/// output as a band-limited step (BLEP): a windowed-sinc impulse that is later
+/// integrated. Outputs are delayed by half the kernel width.
+#[derive(Debug, Clone)]
+pub struct Resampler {
+    /// The number of output samples per second.
+    sample_rate: u32,
+    /// The number of output samples per input clock.
+    ratio: f64,
+    /// The position of the next input clock in the output, relative to `deltas[0]`.
+    time: f64,
+    /// The band-limited amplitude changes not yet integrated into output samples.
+    deltas: Vec<[f32; 2]>,
+    /// The last amplitude given as input.
+    last: [f32; 2],
+    /// The running sum of `deltas`, i.e. the current output amplitude.
+    integrator: [f32; 2],
+    /// The kernel taps for each sub-sample phase, each summing to 1.
+    kernel: Vec<[f32; KERNEL_TAPS]>,
+}
+
+impl Resampler {
+    /// Creates a new `Resampler` from `clock_rate` input samples per second to
+    /// `sample_rate` output samples per second.
+    pub fn new(clock_rate: u32, sample_rate: u32) -> Self {
+        Self {
+            sample_rate,
+            ratio: sample_rate as f64 / clock_rate as f64,
+            time: 0.0,
+            deltas: vec![[0.0; 2]; KERNEL_TAPS],
+            last: [0.0; 2],
+            integrator: [0.0; 2],
+            kernel: (0..KERNEL_PHASES).map(kernel_phase).collect(),
+        }
+    }
+
+    /// Returns the number of output samples per second.
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// Adds the amplitude of the next input clock.
+    pub fn push(&mut self, (left, right): Sample) {
+        let delta = [left - self.last[0], right - self.last[1]];
+        if delta != [0.0; 2] {
+            self.last = [left, right];
+            self.add_delta(delta);
+        }
+        self.time += self.ratio;
+    }
+
+    /// Spreads an amplitude change at the current time over the next output samples.
+    fn add_delta(&mut self, delta: [f32; 2]) {
+        let index = self.time as usize;
+        let phase = ((self.time - index as f64) * KERNEL_PHASES as f64) as usize;
+
+        if self.deltas.len() < index + KERNEL_TAPS {
+            self.deltas.resize(index + KERNEL_TAPS, [0.0; 2]);
+        }
+
+        for (tap, weight) in self.kernel[phase].iter().enumerate() {
+            self.deltas[index + tap][0] += delta[0] * weight;
+            self.deltas[index + tap][1] += delta[1] * weight;
+        }
+    }
+
+    /// Returns the output samples that no further input can change.
+    pub fn take_samples(&mut self) -> Vec<Sample> {
+        let available = self.time as usize;
+        if self.deltas.len() < available + KERNEL_TAPS {
+            self.deltas.resize(available + KERNEL_TAPS, [0.0; 2]);
+        }
+
+        let samples = self
+            .deltas
+            .drain(..available)
+            .map(|delta| {
+                self.integrator[0] += delta[0];
+                self.integrator[1] += delta[1];
+                (self.integrator[0], self.integrator[1])
+            })
+            .collect();
+
+        self.time -= available as f64;
+        samples
+    }
+}
+
+/// Returns the windowed-sinc kernel for the given sub-sample phase,
+/// normalized so that a step always ends at its full height.
+fn kernel_phase(phase: usize) -> [f32; KERNEL_TAPS] {
+    let offset = phase as f64 / KERNEL_PHASES as f64;
+    let half = KERNEL_TAPS as f64 / 2.0;
+
+    let mut taps = [0.0; KERNEL_TAPS];
+    for (tap, weight) in taps.iter_mut().enumerate() {
+        let x = tap as f64 - offset - half + 1.0;
+        let sinc = if x == 0.0 {
+            1.0
+        } else {
+            (PI * CUTOFF * x).sin() / (PI * CUTOFF * x)
+        };
+        // Blackman window over the width of the kernel
+        let n = (x + half) / KERNEL_TAPS as f64;
+        let window = 0.42 - 0.5 * (2.0 * PI * n).cos() + 0.08 * (4.0 * PI * n).cos();
+        *weight = sinc * window;
+    }
+
+    let sum: f64 = taps.iter().sum();
+    taps.map(|weight| (weight / sum) as f32)
+}
+
+/// The capacitor on the audio output, which removes the DC offset of the DACs.
+#[derive(Debug, Clone)]
+pub struct HighPass {
+    /// The fraction of its charge the capacitor keeps each output sample.
+    charge_factor: f32,
+    capacitor: [f32; 2],
+}
+
+impl HighPass {
+    /// Creates the filter of the given model for the given output sample rate.
+    /// The capacitor of the CGB discharges faster than that of the DMG.
+    pub fn new(sample_rate: u32, cgb: bool) -> Self {
+        let base: f64 = if cgb { 0.998943 } else { 0.999958 };
+        Self {
+            charge_factor: base.powf(4_194_304.0 / sample_rate as f64) as f32,
+            capacitor: [0.0; 2],
+        }
+    }
+
+    /// Filters a single sample.
+    pub fn filter(&mut self, (left, right): Sample) -> Sample {
+        let out = [left - self.capacitor[0], right - self.capacitor[1]];
+        self.capacitor[0] = left - out[0] * self.charge_factor;
+        self.capacitor[1] = right - out[1] * self.charge_factor;
+        (out[0], out[1])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_step_settles() {
+        let mut resampler = Resampler::new(1_048_576, 48_000);
+        for _ in 0..1_048_576 / 100 {
+            resampler.push((0.5, -0.25));
+        }
+
+        let samples = resampler.take_samples();
+        assert!((479..=481).contains(&samples.len()));
+        let (left, right) = samples.last().unwrap();
+        assert!((left - 0.5).abs() < 1e-4);
+        assert!((right + 0.25).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_high_pass_removes_dc() {
+        let mut high_pass = HighPass::new(48_000, false);
+        let first = high_pass.filter((1.0, 1.0));
+        assert_eq!(first, (1.0, 1.0));
+
+        let mut last = first;
+        for _ in 0..48_000 {
+            last = high_pass.filter((1.0, 1.0));
+        }
+        assert!(last.0.abs() < 0.01);
+    }
+}
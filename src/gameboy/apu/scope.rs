@@ -0,0 +1,49 @@
+use super::CHANNELS;
+
+/// The number of snapshots kept by the oscilloscope.
+pub const SCOPE_LENGTH: usize = 512;
+/// The number of APU samples between two oscilloscope snapshots (about 16 kHz).
+const SCOPE_INTERVAL: u32 = 64;
+
+/// Keeps a short history of the channel outputs, for debugging views.
+#[derive(Debug, Clone)]
+pub struct Scope {
+    snapshots: Vec<[f32; CHANNELS]>,
+    /// The index of the oldest snapshot.
+    next: usize,
+    /// The APU samples since the last snapshot.
+    samples: u32,
+}
+
+impl Default for Scope {
+    fn default() -> Self {
+        Self {
+            snapshots: vec![[0.0; CHANNELS]; SCOPE_LENGTH],
+            next: 0,
+            samples: 0,
+        }
+    }
+}
+
+impl Scope {
+    /// Records the channel outputs, if a snapshot is due.
+    pub fn push(&mut self, outputs: [f32; CHANNELS]) {
+        self.samples += 1;
+        if self.samples < SCOPE_INTERVAL {
+            return;
+        }
+        self.samples = 0;
+
+        self.snapshots[self.next] = outputs;
+        self.next = (self.next + 1) % SCOPE_LENGTH;
+    }
+
+    /// Returns the history of the given channel, oldest first.
+    pub fn channel(&self, channel: usize) -> impl Iterator<Item = f32> + '_ {
+        let (newest, oldest) = self.snapshots.split_at(self.next);
+        oldest
+            .iter()
+            .chain(newest)
+            .map(move |outputs| outputs[channel])
+    }
+}
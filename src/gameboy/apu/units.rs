@@ -0,0 +1,252 @@
+/// The length counter of a channel, which turns it off once it expires.
+#[derive(Debug, Clone)]
+pub struct Length {
+    /// The number of frame sequencer clocks left before the channel turns off.
+    pub counter: u16,
+    /// Whether the counter is running (`NRx4` bit 6).
+    pub enabled: bool,
+    /// The length the counter starts from (64, or 256 for the wave channel).
+    max: u16,
+}
+
+impl Length {
+    /// Creates a new `Length` counter for lengths of up to `max`.
+    pub fn new(max: u16) -> Self {
+        Self {
+            counter: 0,
+            enabled: false,
+            max,
+        }
+    }
+
+    /// Loads the length written to `NRx1`.
+    pub fn load(&mut self, value: u16) {
+        self.counter = self.max - value;
+    }
+
+    /// Clocks the counter. Returns `true` if the channel must be turned off.
+    pub fn clock(&mut self) -> bool {
+        if self.enabled && self.counter > 0 {
+            self.counter -= 1;
+            self.counter == 0
+        } else {
+            false
+        }
+    }
+
+    /// Handles the length enable and trigger bits of a write to `NRx4`.
+    /// `extra_clock` tells whether the next frame sequencer step does not clock
+    /// the length counters, in which case enabling the counter clocks it once.
+    /// Returns `true` if the channel must be turned off.
+    pub fn write_control(&mut self, enable: bool, trigger: bool, extra_clock: bool) -> bool {
+        let was_enabled = self.enabled;
+        self.enabled = enable;
+
+        let mut expired = false;
+        if extra_clock && enable && !was_enabled && self.counter > 0 {
+            self.counter -= 1;
+            expired = self.counter == 0 && !trigger;
+        }
+
+        if trigger && self.counter == 0 {
+            self.counter = self.max;
+            if enable && extra_clock {
+                self.counter -= 1;
+            }
+        }
+        expired
+    }
+}
+
+/// The volume envelope of a pulse or noise channel (`NRx2`).
+#[derive(Debug, Default, Clone)]
+pub struct Envelope {
+    /// The volume the channel starts at when triggered.
+    initial: u8,
+    /// Whether the volume goes up rather than down.
+    increase: bool,
+    /// The number of frame sequencer envelope clocks between volume changes (0 stops it).
+    period: u8,
+    timer: u8,
+    /// The current volume (0-15).
+    pub volume: u8,
+}
+
+impl Envelope {
+    /// Returns the value of the `NRx2` register.
+    pub fn read(&self) -> u8 {
+        self.initial << 4 | (self.increase as u8) << 3 | self.period
+    }
+
+    /// Writes the `NRx2` register.
+    pub fn write(&mut self, value: u8) {
+        self.initial = value >> 4;
+        self.increase = value & 0b1000 != 0;
+        self.period = value & 0b111;
+    }
+
+    /// Returns whether the DAC of the channel is on: it is turned off by
+    /// writing zeros to the upper five bits of `NRx2`.
+    pub fn dac_enabled(&self) -> bool {
+        self.initial != 0 || self.increase
+    }
+
+    /// Restarts the envelope when the channel is triggered.
+    pub fn trigger(&mut self) {
+        self.volume = self.initial;
+        self.timer = period_or_8(self.period);
+    }
+
+    /// Clocks the envelope, once every 8 frame sequencer steps.
+    pub fn clock(&mut self) {
+        if self.period == 0 {
+            return;
+        }
+
+        self.timer = self.timer.saturating_sub(1);
+        if self.timer == 0 {
+            self.timer = self.period;
+            if self.increase && self.volume < 15 {
+                self.volume += 1;
+            } else if !self.increase && self.volume > 0 {
+                self.volume -= 1;
+            }
+        }
+    }
+}
+
+/// The frequency sweep of channel 1 (`NR10`).
+#[derive(Debug, Default, Clone)]
+pub struct Sweep {
+    period: u8,
+    negate: bool,
+    shift: u8,
+    timer: u8,
+    enabled: bool,
+    /// The copy of the frequency the sweep works on.
+    shadow: u16,
+    /// Whether a subtraction has been calculated since the last trigger.
+    negated: bool,
+}
+
+impl Sweep {
+    /// Returns the value of the `NR10` register.
+    pub fn read(&self) -> u8 {
+        0b1000_0000 | self.period << 4 | (self.negate as u8) << 3 | self.shift
+    }
+
+    /// Writes the `NR10` register.
+    /// Returns `true` if the channel must be turned off: leaving subtraction
+    /// mode after it has been used disables the channel.
+    pub fn write(&mut self, value: u8) -> bool {
+        self.period = (value >> 4) & 0b111;
+        self.negate = value & 0b1000 != 0;
+        self.shift = value & 0b111;
+        !self.negate && self.negated
+    }
+
+    /// Restarts the sweep from the given frequency when the channel is triggered.
+    /// Returns `true` if the channel must be turned off.
+    pub fn trigger(&mut self, frequency: u16) -> bool {
+        self.shadow = frequency;
+        self.timer = period_or_8(self.period);
+        self.enabled = self.period != 0 || self.shift != 0;
+        self.negated = false;
+
+        self.shift != 0 && self.calculate() > 2047
+    }
+
+    /// Calculates the next frequency.
+    fn calculate(&mut self) -> u16 {
+        let delta = self.shadow >> self.shift;
+        if self.negate {
+            self.negated = true;
+            self.shadow - delta
+        } else {
+            self.shadow + delta
+        }
+    }
+
+    /// Clocks the sweep, updating the given frequency.
+    /// Returns `true` if the channel must be turned off.
+    pub fn clock(&mut self, frequency: &mut u16) -> bool {
+        self.timer = self.timer.saturating_sub(1);
+        if self.timer > 0 {
+            return false;
+        }
+        self.timer = period_or_8(self.period);
+
+        if !self.enabled || self.period == 0 {
+            return false;
+        }
+
+        let new = self.calculate();
+        if new > 2047 {
+            return true;
+        }
+        if self.shift != 0 {
+            self.shadow = new;
+            *frequency = new;
+            // The new frequency is checked for overflow once more
+            return self.calculate() > 2047;
+        }
+        false
+    }
+}
+
+/// Returns the given timer period, with 0 standing for 8.
+fn period_or_8(period: u8) -> u8 {
+    if period == 0 {
+        8
+    } else {
+        period
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_length_extra_clock() {
+        let mut length = Length::new(64);
+        length.load(62);
+        assert!(!length.write_control(true, false, false));
+        assert_eq!(length.counter, 2);
+
+        // Enabling the counter when the next step doesn't clock it clocks it once
+        length.enabled = false;
+        assert!(!length.write_control(true, false, true));
+        assert_eq!(length.counter, 1);
+        length.enabled = false;
+        assert!(length.write_control(true, false, true));
+    }
+
+    #[test]
+    fn test_envelope() {
+        let mut envelope = Envelope::default();
+        envelope.write(0xf1);
+        envelope.trigger();
+        envelope.clock();
+        assert_eq!(envelope.volume, 14);
+
+        envelope.write(0x00);
+        assert!(!envelope.dac_enabled());
+    }
+
+    #[test]
+    fn test_sweep_overflow() {
+        let mut sweep = Sweep::default();
+        sweep.write(0b0001_0001);
+        assert!(!sweep.trigger(0x100));
+        let mut frequency = 0x100;
+        assert!(!sweep.clock(&mut frequency));
+        assert_eq!(frequency, 0x180);
+
+        // 0x400 + 0x200 is fine, but the second check of 0x600 + 0x300 overflows
+        assert!(!sweep.trigger(0x400));
+        let mut frequency = 0x400;
+        assert!(sweep.clock(&mut frequency));
+        assert_eq!(frequency, 0x600);
+    }
+}
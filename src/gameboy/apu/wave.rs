@@ -0,0 +1,130 @@
+use super::units::Length;
+use super::CLOCK_RATE;
+
+/// The size of wave RAM: 32 4-bit samples.
+pub const WAVE_RAM_SIZE: usize = 16;
+
+/// The wave channel (channel 3), playing the samples in wave RAM.
+#[derive(Debug, Clone)]
+pub struct Wave {
+    /// Whether the channel is playing.
+    pub enabled: bool,
+    /// Whether the DAC is on (`NR30` bit 7).
+    pub dac_enabled: bool,
+    /// The output level (`NR32` bits 5-6): mute, 100%, 50% or 25%.
+    pub volume: u8,
+    /// The 11-bit frequency (`NR33` and `NR34` bits 0-2).
+    pub frequency: u16,
+    timer: u16,
+    /// The index of the sample being played (0-31).
+    position: u8,
+    pub length: Length,
+    /// The 32 4-bit samples, high nibble first.
+    pub ram: [u8; WAVE_RAM_SIZE],
+}
+
+impl Default for Wave {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Wave {
+    /// Creates a new wave channel.
+    pub fn new() -> Self {
+        Self {
+            enabled: false,
+            dac_enabled: false,
+            volume: 0,
+            frequency: 0,
+            timer: 0,
+            position: 0,
+            length: Length::new(256),
+            ram: [0; WAVE_RAM_SIZE],
+        }
+    }
+
+    /// Returns the value of the given register (0-4 for `NR30`-`NR34`), before masking.
+    pub fn read(&self, register: u16) -> u8 {
+        match register {
+            0 => (self.dac_enabled as u8) << 7,
+            2 => self.volume << 5,
+            4 => (self.length.enabled as u8) << 6,
+            _ => 0xff,
+        }
+    }
+
+    /// Writes the given register (0-4 for `NR30`-`NR34`).
+    pub fn write(&mut self, register: u16, value: u8, extra_length_clock: bool) {
+        match register {
+            0 => {
+                self.dac_enabled = value & 0b1000_0000 != 0;
+                if !self.dac_enabled {
+                    self.enabled = false;
+                }
+            }
+            1 => self.length.load(value as u16),
+            2 => self.volume = (value >> 5) & 0b11,
+            3 => self.frequency = (self.frequency & 0x700) | value as u16,
+            4 => {
+                self.frequency = (self.frequency & 0xff) | ((value & 0b111) as u16) << 8;
+                let trigger = value & 0b1000_0000 != 0;
+                let enable_length = value & 0b0100_0000 != 0;
+
+                if self
+                    .length
+                    .write_control(enable_length, trigger, extra_length_clock)
+                {
+                    self.enabled = false;
+                }
+                if trigger {
+                    self.enabled = self.dac_enabled;
+                    self.timer = self.period();
+                    self.position = 0;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Returns the number of cycles between samples.
+    fn period(&self) -> u16 {
+        (2048 - self.frequency) * 2
+    }
+
+    /// Returns the frequency of the waveform in Hz.
+    pub fn frequency_hz(&self) -> f64 {
+        CLOCK_RATE as f64 / (self.period() as f64 * 32.0)
+    }
+
+    /// Advances the channel by a single cycle.
+    pub fn step(&mut self) {
+        self.timer = self.timer.saturating_sub(1);
+        if self.timer == 0 {
+            self.timer = self.period();
+            self.position = (self.position + 1) % 32;
+        }
+    }
+
+    /// Clocks the length counter.
+    pub fn clock_length(&mut self) {
+        if self.length.clock() {
+            self.enabled = false;
+        }
+    }
+
+    /// Returns the digital output of the channel (0-15).
+    pub fn output(&self) -> u8 {
+        if !self.enabled || self.volume == 0 {
+            return 0;
+        }
+
+        let byte = self.ram[self.position as usize / 2];
+        let sample = if self.position.is_multiple_of(2) {
+            byte >> 4
+        } else {
+            byte & 0x0f
+        };
+        sample >> (self.volume - 1)
+    }
+}
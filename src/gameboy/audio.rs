@@ -0,0 +1,100 @@
+use std::collections::VecDeque;
+use std::fmt::Debug;
+use std::sync::{Arc, Mutex};
+
+pub use super::apu::{Sample, DEFAULT_SAMPLE_RATE};
+
+/// A destination for the audio output of the `GameBoy`.
+pub trait AudioSink: Debug + Send {
+    /// Receives the next stereo samples, at the sample rate of the `GameBoy`.
+    fn push_samples(&mut self, samples: &[Sample]);
+
+    /// Returns whether the sink also wants the output of each channel on its own.
+    fn wants_channels(&self) -> bool {
+        false
+    }
+
+    /// Receives the next mono samples of the given channel (0-3), before panning
+    /// and filtering. Only called if `wants_channels` returns `true`.
+    fn push_channel_samples(&mut self, _channel: usize, _samples: &[f32]) {}
+
+    /// Called once no more samples will be pushed, e.g. to complete a file.
+    fn finish(&mut self) {}
+}
+
+/// A sink that discards every sample.
+#[derive(Debug, Default, Clone)]
+pub struct NullSink;
+
+impl AudioSink for NullSink {
+    fn push_samples(&mut self, _samples: &[Sample]) {}
+}
+
+/// A sink that keeps the latest samples in a bounded queue, to be read from
+/// another thread (e.g. an audio callback) through a clone of the sink.
+#[derive(Debug, Clone)]
+pub struct RingBufferSink {
+    buffer: Arc<Mutex<VecDeque<Sample>>>,
+    capacity: usize,
+}
+
+impl RingBufferSink {
+    /// Creates a new `RingBufferSink` holding up to `capacity` samples.
+    /// Once full, the oldest samples are dropped.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            buffer: Arc::new(Mutex::new(VecDeque::with_capacity(capacity))),
+            capacity,
+        }
+    }
+
+    /// Returns the number of samples waiting to be read.
+    pub fn len(&self) -> usize {
+        self.buffer.lock().unwrap().len()
+    }
+
+    /// Returns whether no samples are waiting to be read.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Moves the oldest samples into `out`, returning how many were read.
+    pub fn pop_samples(&self, out: &mut [Sample]) -> usize {
+        let mut buffer = self.buffer.lock().unwrap();
+        let count = out.len().min(buffer.len());
+        for (slot, sample) in out.iter_mut().zip(buffer.drain(..count)) {
+            *slot = sample;
+        }
+        count
+    }
+}
+
+impl AudioSink for RingBufferSink {
+    fn push_samples(&mut self, samples: &[Sample]) {
+        let mut buffer = self.buffer.lock().unwrap();
+        for sample in samples {
+            if buffer.len() == self.capacity {
+                buffer.pop_front();
+            }
+            buffer.push_back(*sample);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ring_buffer_drops_oldest() {
+        let reader = RingBufferSink::new(3);
+        let mut sink = reader.clone();
+        sink.push_samples(&[(1.0, 1.0), (2.0, 2.0), (3.0, 3.0), (4.0, 4.0)]);
+        assert_eq!(reader.len(), 3);
+
+        let mut out = [(0.0, 0.0); 2];
+        assert_eq!(reader.pop_samples(&mut out), 2);
+        assert_eq!(out, [(2.0, 2.0), (3.0, 3.0)]);
+        assert_eq!(reader.len(), 1);
+    }
+}
@@ -0,0 +1,69 @@
+pub const TITLE_ADDR: usize = 0x0134;
+pub const TITLE_ADDR_END: usize = 0x0142;
+pub const CGB_FLAG_ADDR: usize = 0x0143;
+pub const SGB_FLAG_ADDR: usize = 0x0146;
+pub const CARTRIDGE_TYPE_ADDR: usize = 0x0147;
+
+/// The cartridge header, found at 0x0100-0x014f of every ROM.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Header {
+    pub title: String,
+    pub cgb_flag: u8,
+    pub sgb_flag: u8,
+    pub cartridge_type: u8,
+}
+
+impl Header {
+    /// Parses the header of the given ROM. Missing bytes are read as zero.
+    pub fn parse(rom: &[u8]) -> Self {
+        let byte = |addr: usize| rom.get(addr).copied().unwrap_or(0);
+
+        let title = (TITLE_ADDR..=TITLE_ADDR_END)
+            .map(byte)
+            .take_while(|byte| *byte != 0)
+            .filter(|byte| byte.is_ascii_graphic() || *byte == b' ')
+            .map(|byte| byte as char)
+            .collect();
+
+        Self {
+            title,
+            cgb_flag: byte(CGB_FLAG_ADDR),
+            sgb_flag: byte(SGB_FLAG_ADDR),
+            cartridge_type: byte(CARTRIDGE_TYPE_ADDR),
+        }
+    }
+
+    /// Returns whether the game supports the CGB features.
+    pub fn supports_cgb(&self) -> bool {
+        self.cgb_flag & 0x80 != 0
+    }
+
+    /// Returns whether the game only runs on a CGB.
+    pub fn cgb_only(&self) -> bool {
+        self.cgb_flag == 0xc0
+    }
+
+    /// Returns whether the game supports the SGB features.
+    pub fn supports_sgb(&self) -> bool {
+        self.sgb_flag == 0x03
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_header() {
+        let mut rom = vec![0; 0x150];
+        rom[TITLE_ADDR..TITLE_ADDR + 6].copy_from_slice(b"TETRIS");
+        rom[CGB_FLAG_ADDR] = 0x80;
+        rom[SGB_FLAG_ADDR] = 0x03;
+
+        let header = Header::parse(&rom);
+        assert_eq!(header.title, "TETRIS");
+        assert!(header.supports_cgb());
+        assert!(!header.cgb_only());
+        assert!(header.supports_sgb());
+    }
+}
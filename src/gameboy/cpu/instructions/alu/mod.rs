@@ -1,5 +1,3 @@
 pub mod x8;
-pub use x8::*;
 
 pub mod x16;
-pub use x16::*;
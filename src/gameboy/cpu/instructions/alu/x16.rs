@@ -1,8 +1,9 @@
-use crate::gameboy::cpu::instructions::util::{add16, increment16};
+use crate::gameboy::cpu::instructions::util::{add16, add_signed, decrement16, increment16};
 use crate::gameboy::cpu::Cpu;
 
 /// INC DE
 pub fn op_13(cpu: &mut Cpu) {
+    cpu.mem.corrupt_oam(cpu.regs.de());
     cpu.regs.set_de(increment16(cpu.regs.de()));
 }
 
@@ -15,5 +16,71 @@ pub fn op_19(cpu: &mut Cpu) {
 
 /// INC HL
 pub fn op_23(cpu: &mut Cpu) {
+    cpu.mem.corrupt_oam(cpu.regs.hl());
     cpu.regs.set_hl(increment16(cpu.regs.hl()));
 }
+
+/// INC BC
+pub fn op_03(cpu: &mut Cpu) {
+    cpu.mem.corrupt_oam(cpu.regs.bc());
+    cpu.regs.set_bc(increment16(cpu.regs.bc()));
+}
+
+/// ADD HL, BC
+pub fn op_09(cpu: &mut Cpu) {
+    let (new_hl, new_flags) = add16(cpu.regs.hl(), cpu.regs.bc(), cpu.regs.f);
+    cpu.regs.set_hl(new_hl);
+    cpu.regs.f = new_flags;
+}
+
+/// DEC BC
+pub fn op_0b(cpu: &mut Cpu) {
+    cpu.mem.corrupt_oam(cpu.regs.bc());
+    cpu.regs.set_bc(decrement16(cpu.regs.bc()));
+}
+
+/// DEC DE
+pub fn op_1b(cpu: &mut Cpu) {
+    cpu.mem.corrupt_oam(cpu.regs.de());
+    cpu.regs.set_de(decrement16(cpu.regs.de()));
+}
+
+/// ADD HL, HL
+pub fn op_29(cpu: &mut Cpu) {
+    let (new_hl, new_flags) = add16(cpu.regs.hl(), cpu.regs.hl(), cpu.regs.f);
+    cpu.regs.set_hl(new_hl);
+    cpu.regs.f = new_flags;
+}
+
+/// DEC HL
+pub fn op_2b(cpu: &mut Cpu) {
+    cpu.mem.corrupt_oam(cpu.regs.hl());
+    cpu.regs.set_hl(decrement16(cpu.regs.hl()));
+}
+
+/// INC SP
+pub fn op_33(cpu: &mut Cpu) {
+    cpu.mem.corrupt_oam(cpu.sp);
+    cpu.sp = increment16(cpu.sp);
+}
+
+/// ADD HL, SP
+pub fn op_39(cpu: &mut Cpu) {
+    let (new_hl, new_flags) = add16(cpu.regs.hl(), cpu.sp, cpu.regs.f);
+    cpu.regs.set_hl(new_hl);
+    cpu.regs.f = new_flags;
+}
+
+/// DEC SP
+pub fn op_3b(cpu: &mut Cpu) {
+    cpu.mem.corrupt_oam(cpu.sp);
+    cpu.sp = decrement16(cpu.sp);
+}
+
+/// ADD SP, i8
+pub fn op_e8(cpu: &mut Cpu) {
+    let offset = cpu.next_byte();
+    let (new_sp, new_flags) = add_signed(cpu.sp, offset);
+    cpu.sp = new_sp;
+    cpu.regs.f = new_flags;
+}
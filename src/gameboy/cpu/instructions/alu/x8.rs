@@ -1,5 +1,5 @@
 use crate::gameboy::cpu::instructions::util::{
-    add8, compare, decrement8, increment8, sbc, sub, xor,
+    adc, add8, and, compare, decimal_adjust, decrement8, increment8, or, sbc, sub, xor,
 };
 use crate::gameboy::cpu::Cpu;
 
@@ -76,7 +76,7 @@ pub fn op_3d(cpu: &mut Cpu) {
 /// ADD A, (HL)
 pub fn op_86(cpu: &mut Cpu) {
     let addr = cpu.regs.hl();
-    let byte = cpu.mem.read().unwrap().read_byte(addr);
+    let byte = cpu.read_byte(addr);
     let (new_a, new_flags) = add8(cpu.regs.a, byte);
     cpu.regs.a = new_a;
     cpu.regs.f = new_flags;
@@ -106,7 +106,7 @@ pub fn op_af(cpu: &mut Cpu) {
 /// CP A, (HL)
 pub fn op_be(cpu: &mut Cpu) {
     let addr = cpu.regs.hl();
-    let byte = cpu.mem.read().unwrap().read_byte(addr);
+    let byte = cpu.read_byte(addr);
     cpu.regs.f = compare(cpu.regs.a, byte, cpu.regs.f);
 }
 
@@ -114,3 +114,544 @@ pub fn op_be(cpu: &mut Cpu) {
 pub fn op_fe(cpu: &mut Cpu) {
     cpu.regs.f = compare(cpu.regs.a, cpu.next_byte(), cpu.regs.f);
 }
+
+/// INC D
+pub fn op_14(cpu: &mut Cpu) {
+    let (new_d, new_flags) = increment8(cpu.regs.d, cpu.regs.f);
+    cpu.regs.d = new_d;
+    cpu.regs.f = new_flags;
+}
+
+/// DEC H
+pub fn op_25(cpu: &mut Cpu) {
+    let (new_h, new_flags) = decrement8(cpu.regs.h, cpu.regs.f);
+    cpu.regs.h = new_h;
+    cpu.regs.f = new_flags;
+}
+
+/// DAA
+pub fn op_27(cpu: &mut Cpu) {
+    let (new_a, new_flags) = decimal_adjust(cpu.regs.a, cpu.regs.f);
+    cpu.regs.a = new_a;
+    cpu.regs.f = new_flags;
+}
+
+/// INC L
+pub fn op_2c(cpu: &mut Cpu) {
+    let (new_l, new_flags) = increment8(cpu.regs.l, cpu.regs.f);
+    cpu.regs.l = new_l;
+    cpu.regs.f = new_flags;
+}
+
+/// DEC L
+pub fn op_2d(cpu: &mut Cpu) {
+    let (new_l, new_flags) = decrement8(cpu.regs.l, cpu.regs.f);
+    cpu.regs.l = new_l;
+    cpu.regs.f = new_flags;
+}
+
+/// CPL
+pub fn op_2f(cpu: &mut Cpu) {
+    cpu.regs.a = !cpu.regs.a;
+    cpu.set_flag_n(true);
+    cpu.set_flag_h(true);
+}
+
+/// INC (HL)
+pub fn op_34(cpu: &mut Cpu) {
+    let addr = cpu.regs.hl();
+    let byte = cpu.read_byte(addr);
+    let (new_byte, new_flags) = increment8(byte, cpu.regs.f);
+    cpu.write_byte(addr, new_byte);
+    cpu.regs.f = new_flags;
+}
+
+/// DEC (HL)
+pub fn op_35(cpu: &mut Cpu) {
+    let addr = cpu.regs.hl();
+    let byte = cpu.read_byte(addr);
+    let (new_byte, new_flags) = decrement8(byte, cpu.regs.f);
+    cpu.write_byte(addr, new_byte);
+    cpu.regs.f = new_flags;
+}
+
+/// SCF
+pub fn op_37(cpu: &mut Cpu) {
+    cpu.set_flag_n(false);
+    cpu.set_flag_h(false);
+    cpu.set_flag_c(true);
+}
+
+/// CCF
+pub fn op_3f(cpu: &mut Cpu) {
+    cpu.set_flag_n(false);
+    cpu.set_flag_h(false);
+    cpu.set_flag_c(cpu.get_flag_c() == 0);
+}
+
+/// ADD A, B
+pub fn op_80(cpu: &mut Cpu) {
+    let (new_a, new_flags) = add8(cpu.regs.a, cpu.regs.b);
+    cpu.regs.a = new_a;
+    cpu.regs.f = new_flags;
+}
+
+/// ADD A, C
+pub fn op_81(cpu: &mut Cpu) {
+    let (new_a, new_flags) = add8(cpu.regs.a, cpu.regs.c);
+    cpu.regs.a = new_a;
+    cpu.regs.f = new_flags;
+}
+
+/// ADD A, D
+pub fn op_82(cpu: &mut Cpu) {
+    let (new_a, new_flags) = add8(cpu.regs.a, cpu.regs.d);
+    cpu.regs.a = new_a;
+    cpu.regs.f = new_flags;
+}
+
+/// ADD A, E
+pub fn op_83(cpu: &mut Cpu) {
+    let (new_a, new_flags) = add8(cpu.regs.a, cpu.regs.e);
+    cpu.regs.a = new_a;
+    cpu.regs.f = new_flags;
+}
+
+/// ADD A, H
+pub fn op_84(cpu: &mut Cpu) {
+    let (new_a, new_flags) = add8(cpu.regs.a, cpu.regs.h);
+    cpu.regs.a = new_a;
+    cpu.regs.f = new_flags;
+}
+
+/// ADD A, L
+pub fn op_85(cpu: &mut Cpu) {
+    let (new_a, new_flags) = add8(cpu.regs.a, cpu.regs.l);
+    cpu.regs.a = new_a;
+    cpu.regs.f = new_flags;
+}
+
+/// ADD A, A
+pub fn op_87(cpu: &mut Cpu) {
+    let (new_a, new_flags) = add8(cpu.regs.a, cpu.regs.a);
+    cpu.regs.a = new_a;
+    cpu.regs.f = new_flags;
+}
+
+/// ADC A, B
+pub fn op_88(cpu: &mut Cpu) {
+    let (new_a, new_flags) = adc(cpu.regs.a, cpu.regs.b, cpu.regs.f);
+    cpu.regs.a = new_a;
+    cpu.regs.f = new_flags;
+}
+
+/// ADC A, C
+pub fn op_89(cpu: &mut Cpu) {
+    let (new_a, new_flags) = adc(cpu.regs.a, cpu.regs.c, cpu.regs.f);
+    cpu.regs.a = new_a;
+    cpu.regs.f = new_flags;
+}
+
+/// ADC A, D
+pub fn op_8a(cpu: &mut Cpu) {
+    let (new_a, new_flags) = adc(cpu.regs.a, cpu.regs.d, cpu.regs.f);
+    cpu.regs.a = new_a;
+    cpu.regs.f = new_flags;
+}
+
+/// ADC A, E
+pub fn op_8b(cpu: &mut Cpu) {
+    let (new_a, new_flags) = adc(cpu.regs.a, cpu.regs.e, cpu.regs.f);
+    cpu.regs.a = new_a;
+    cpu.regs.f = new_flags;
+}
+
+/// ADC A, H
+pub fn op_8c(cpu: &mut Cpu) {
+    let (new_a, new_flags) = adc(cpu.regs.a, cpu.regs.h, cpu.regs.f);
+    cpu.regs.a = new_a;
+    cpu.regs.f = new_flags;
+}
+
+/// ADC A, L
+pub fn op_8d(cpu: &mut Cpu) {
+    let (new_a, new_flags) = adc(cpu.regs.a, cpu.regs.l, cpu.regs.f);
+    cpu.regs.a = new_a;
+    cpu.regs.f = new_flags;
+}
+
+/// ADC A, (HL)
+pub fn op_8e(cpu: &mut Cpu) {
+    let addr = cpu.regs.hl();
+    let byte = cpu.read_byte(addr);
+    let (new_a, new_flags) = adc(cpu.regs.a, byte, cpu.regs.f);
+    cpu.regs.a = new_a;
+    cpu.regs.f = new_flags;
+}
+
+/// ADC A, A
+pub fn op_8f(cpu: &mut Cpu) {
+    let (new_a, new_flags) = adc(cpu.regs.a, cpu.regs.a, cpu.regs.f);
+    cpu.regs.a = new_a;
+    cpu.regs.f = new_flags;
+}
+
+/// SUB A, C
+pub fn op_91(cpu: &mut Cpu) {
+    let (new_a, new_flags) = sub(cpu.regs.a, cpu.regs.c, cpu.regs.f);
+    cpu.regs.a = new_a;
+    cpu.regs.f = new_flags;
+}
+
+/// SUB A, D
+pub fn op_92(cpu: &mut Cpu) {
+    let (new_a, new_flags) = sub(cpu.regs.a, cpu.regs.d, cpu.regs.f);
+    cpu.regs.a = new_a;
+    cpu.regs.f = new_flags;
+}
+
+/// SUB A, E
+pub fn op_93(cpu: &mut Cpu) {
+    let (new_a, new_flags) = sub(cpu.regs.a, cpu.regs.e, cpu.regs.f);
+    cpu.regs.a = new_a;
+    cpu.regs.f = new_flags;
+}
+
+/// SUB A, H
+pub fn op_94(cpu: &mut Cpu) {
+    let (new_a, new_flags) = sub(cpu.regs.a, cpu.regs.h, cpu.regs.f);
+    cpu.regs.a = new_a;
+    cpu.regs.f = new_flags;
+}
+
+/// SUB A, L
+pub fn op_95(cpu: &mut Cpu) {
+    let (new_a, new_flags) = sub(cpu.regs.a, cpu.regs.l, cpu.regs.f);
+    cpu.regs.a = new_a;
+    cpu.regs.f = new_flags;
+}
+
+/// SUB A, (HL)
+pub fn op_96(cpu: &mut Cpu) {
+    let addr = cpu.regs.hl();
+    let byte = cpu.read_byte(addr);
+    let (new_a, new_flags) = sub(cpu.regs.a, byte, cpu.regs.f);
+    cpu.regs.a = new_a;
+    cpu.regs.f = new_flags;
+}
+
+/// SUB A, A
+pub fn op_97(cpu: &mut Cpu) {
+    let (new_a, new_flags) = sub(cpu.regs.a, cpu.regs.a, cpu.regs.f);
+    cpu.regs.a = new_a;
+    cpu.regs.f = new_flags;
+}
+
+/// SBC A, B
+pub fn op_98(cpu: &mut Cpu) {
+    let (new_a, new_flags) = sbc(cpu.regs.a, cpu.regs.b, cpu.regs.f);
+    cpu.regs.a = new_a;
+    cpu.regs.f = new_flags;
+}
+
+/// SBC A, C
+pub fn op_99(cpu: &mut Cpu) {
+    let (new_a, new_flags) = sbc(cpu.regs.a, cpu.regs.c, cpu.regs.f);
+    cpu.regs.a = new_a;
+    cpu.regs.f = new_flags;
+}
+
+/// SBC A, D
+pub fn op_9a(cpu: &mut Cpu) {
+    let (new_a, new_flags) = sbc(cpu.regs.a, cpu.regs.d, cpu.regs.f);
+    cpu.regs.a = new_a;
+    cpu.regs.f = new_flags;
+}
+
+/// SBC A, E
+pub fn op_9b(cpu: &mut Cpu) {
+    let (new_a, new_flags) = sbc(cpu.regs.a, cpu.regs.e, cpu.regs.f);
+    cpu.regs.a = new_a;
+    cpu.regs.f = new_flags;
+}
+
+/// SBC A, H
+pub fn op_9c(cpu: &mut Cpu) {
+    let (new_a, new_flags) = sbc(cpu.regs.a, cpu.regs.h, cpu.regs.f);
+    cpu.regs.a = new_a;
+    cpu.regs.f = new_flags;
+}
+
+/// SBC A, L
+pub fn op_9d(cpu: &mut Cpu) {
+    let (new_a, new_flags) = sbc(cpu.regs.a, cpu.regs.l, cpu.regs.f);
+    cpu.regs.a = new_a;
+    cpu.regs.f = new_flags;
+}
+
+/// SBC A, (HL)
+pub fn op_9e(cpu: &mut Cpu) {
+    let addr = cpu.regs.hl();
+    let byte = cpu.read_byte(addr);
+    let (new_a, new_flags) = sbc(cpu.regs.a, byte, cpu.regs.f);
+    cpu.regs.a = new_a;
+    cpu.regs.f = new_flags;
+}
+
+/// AND A, B
+pub fn op_a0(cpu: &mut Cpu) {
+    let (new_a, new_flags) = and(cpu.regs.a, cpu.regs.b);
+    cpu.regs.a = new_a;
+    cpu.regs.f = new_flags;
+}
+
+/// AND A, C
+pub fn op_a1(cpu: &mut Cpu) {
+    let (new_a, new_flags) = and(cpu.regs.a, cpu.regs.c);
+    cpu.regs.a = new_a;
+    cpu.regs.f = new_flags;
+}
+
+/// AND A, D
+pub fn op_a2(cpu: &mut Cpu) {
+    let (new_a, new_flags) = and(cpu.regs.a, cpu.regs.d);
+    cpu.regs.a = new_a;
+    cpu.regs.f = new_flags;
+}
+
+/// AND A, E
+pub fn op_a3(cpu: &mut Cpu) {
+    let (new_a, new_flags) = and(cpu.regs.a, cpu.regs.e);
+    cpu.regs.a = new_a;
+    cpu.regs.f = new_flags;
+}
+
+/// AND A, H
+pub fn op_a4(cpu: &mut Cpu) {
+    let (new_a, new_flags) = and(cpu.regs.a, cpu.regs.h);
+    cpu.regs.a = new_a;
+    cpu.regs.f = new_flags;
+}
+
+/// AND A, L
+pub fn op_a5(cpu: &mut Cpu) {
+    let (new_a, new_flags) = and(cpu.regs.a, cpu.regs.l);
+    cpu.regs.a = new_a;
+    cpu.regs.f = new_flags;
+}
+
+/// AND A, (HL)
+pub fn op_a6(cpu: &mut Cpu) {
+    let addr = cpu.regs.hl();
+    let byte = cpu.read_byte(addr);
+    let (new_a, new_flags) = and(cpu.regs.a, byte);
+    cpu.regs.a = new_a;
+    cpu.regs.f = new_flags;
+}
+
+/// AND A, A
+pub fn op_a7(cpu: &mut Cpu) {
+    let (new_a, new_flags) = and(cpu.regs.a, cpu.regs.a);
+    cpu.regs.a = new_a;
+    cpu.regs.f = new_flags;
+}
+
+/// XOR A, B
+pub fn op_a8(cpu: &mut Cpu) {
+    let (new_a, new_flags) = xor(cpu.regs.a, cpu.regs.b, cpu.regs.f);
+    cpu.regs.a = new_a;
+    cpu.regs.f = new_flags;
+}
+
+/// XOR A, C
+pub fn op_a9(cpu: &mut Cpu) {
+    let (new_a, new_flags) = xor(cpu.regs.a, cpu.regs.c, cpu.regs.f);
+    cpu.regs.a = new_a;
+    cpu.regs.f = new_flags;
+}
+
+/// XOR A, D
+pub fn op_aa(cpu: &mut Cpu) {
+    let (new_a, new_flags) = xor(cpu.regs.a, cpu.regs.d, cpu.regs.f);
+    cpu.regs.a = new_a;
+    cpu.regs.f = new_flags;
+}
+
+/// XOR A, E
+pub fn op_ab(cpu: &mut Cpu) {
+    let (new_a, new_flags) = xor(cpu.regs.a, cpu.regs.e, cpu.regs.f);
+    cpu.regs.a = new_a;
+    cpu.regs.f = new_flags;
+}
+
+/// XOR A, H
+pub fn op_ac(cpu: &mut Cpu) {
+    let (new_a, new_flags) = xor(cpu.regs.a, cpu.regs.h, cpu.regs.f);
+    cpu.regs.a = new_a;
+    cpu.regs.f = new_flags;
+}
+
+/// XOR A, L
+pub fn op_ad(cpu: &mut Cpu) {
+    let (new_a, new_flags) = xor(cpu.regs.a, cpu.regs.l, cpu.regs.f);
+    cpu.regs.a = new_a;
+    cpu.regs.f = new_flags;
+}
+
+/// XOR A, (HL)
+pub fn op_ae(cpu: &mut Cpu) {
+    let addr = cpu.regs.hl();
+    let byte = cpu.read_byte(addr);
+    let (new_a, new_flags) = xor(cpu.regs.a, byte, cpu.regs.f);
+    cpu.regs.a = new_a;
+    cpu.regs.f = new_flags;
+}
+
+/// OR A, B
+pub fn op_b0(cpu: &mut Cpu) {
+    let (new_a, new_flags) = or(cpu.regs.a, cpu.regs.b);
+    cpu.regs.a = new_a;
+    cpu.regs.f = new_flags;
+}
+
+/// OR A, C
+pub fn op_b1(cpu: &mut Cpu) {
+    let (new_a, new_flags) = or(cpu.regs.a, cpu.regs.c);
+    cpu.regs.a = new_a;
+    cpu.regs.f = new_flags;
+}
+
+/// OR A, D
+pub fn op_b2(cpu: &mut Cpu) {
+    let (new_a, new_flags) = or(cpu.regs.a, cpu.regs.d);
+    cpu.regs.a = new_a;
+    cpu.regs.f = new_flags;
+}
+
+/// OR A, E
+pub fn op_b3(cpu: &mut Cpu) {
+    let (new_a, new_flags) = or(cpu.regs.a, cpu.regs.e);
+    cpu.regs.a = new_a;
+    cpu.regs.f = new_flags;
+}
+
+/// OR A, H
+pub fn op_b4(cpu: &mut Cpu) {
+    let (new_a, new_flags) = or(cpu.regs.a, cpu.regs.h);
+    cpu.regs.a = new_a;
+    cpu.regs.f = new_flags;
+}
+
+/// OR A, L
+pub fn op_b5(cpu: &mut Cpu) {
+    let (new_a, new_flags) = or(cpu.regs.a, cpu.regs.l);
+    cpu.regs.a = new_a;
+    cpu.regs.f = new_flags;
+}
+
+/// OR A, (HL)
+pub fn op_b6(cpu: &mut Cpu) {
+    let addr = cpu.regs.hl();
+    let byte = cpu.read_byte(addr);
+    let (new_a, new_flags) = or(cpu.regs.a, byte);
+    cpu.regs.a = new_a;
+    cpu.regs.f = new_flags;
+}
+
+/// OR A, A
+pub fn op_b7(cpu: &mut Cpu) {
+    let (new_a, new_flags) = or(cpu.regs.a, cpu.regs.a);
+    cpu.regs.a = new_a;
+    cpu.regs.f = new_flags;
+}
+
+/// CP A, B
+pub fn op_b8(cpu: &mut Cpu) {
+    cpu.regs.f = compare(cpu.regs.a, cpu.regs.b, cpu.regs.f);
+}
+
+/// CP A, C
+pub fn op_b9(cpu: &mut Cpu) {
+    cpu.regs.f = compare(cpu.regs.a, cpu.regs.c, cpu.regs.f);
+}
+
+/// CP A, D
+pub fn op_ba(cpu: &mut Cpu) {
+    cpu.regs.f = compare(cpu.regs.a, cpu.regs.d, cpu.regs.f);
+}
+
+/// CP A, E
+pub fn op_bb(cpu: &mut Cpu) {
+    cpu.regs.f = compare(cpu.regs.a, cpu.regs.e, cpu.regs.f);
+}
+
+/// CP A, H
+pub fn op_bc(cpu: &mut Cpu) {
+    cpu.regs.f = compare(cpu.regs.a, cpu.regs.h, cpu.regs.f);
+}
+
+/// CP A, L
+pub fn op_bd(cpu: &mut Cpu) {
+    cpu.regs.f = compare(cpu.regs.a, cpu.regs.l, cpu.regs.f);
+}
+
+/// CP A, A
+pub fn op_bf(cpu: &mut Cpu) {
+    cpu.regs.f = compare(cpu.regs.a, cpu.regs.a, cpu.regs.f);
+}
+
+/// ADD A, u8
+pub fn op_c6(cpu: &mut Cpu) {
+    let byte = cpu.next_byte();
+    let (new_a, new_flags) = add8(cpu.regs.a, byte);
+    cpu.regs.a = new_a;
+    cpu.regs.f = new_flags;
+}
+
+/// ADC A, u8
+pub fn op_ce(cpu: &mut Cpu) {
+    let byte = cpu.next_byte();
+    let (new_a, new_flags) = adc(cpu.regs.a, byte, cpu.regs.f);
+    cpu.regs.a = new_a;
+    cpu.regs.f = new_flags;
+}
+
+/// SUB A, u8
+pub fn op_d6(cpu: &mut Cpu) {
+    let byte = cpu.next_byte();
+    let (new_a, new_flags) = sub(cpu.regs.a, byte, cpu.regs.f);
+    cpu.regs.a = new_a;
+    cpu.regs.f = new_flags;
+}
+
+/// SBC A, u8
+pub fn op_de(cpu: &mut Cpu) {
+    let byte = cpu.next_byte();
+    let (new_a, new_flags) = sbc(cpu.regs.a, byte, cpu.regs.f);
+    cpu.regs.a = new_a;
+    cpu.regs.f = new_flags;
+}
+
+/// AND A, u8
+pub fn op_e6(cpu: &mut Cpu) {
+    let byte = cpu.next_byte();
+    let (new_a, new_flags) = and(cpu.regs.a, byte);
+    cpu.regs.a = new_a;
+    cpu.regs.f = new_flags;
+}
+
+/// XOR A, u8
+pub fn op_ee(cpu: &mut Cpu) {
+    let byte = cpu.next_byte();
+    let (new_a, new_flags) = xor(cpu.regs.a, byte, cpu.regs.f);
+    cpu.regs.a = new_a;
+    cpu.regs.f = new_flags;
+}
+
+/// OR A, u8
+pub fn op_f6(cpu: &mut Cpu) {
+    let byte = cpu.next_byte();
+    let (new_a, new_flags) = or(cpu.regs.a, byte);
+    cpu.regs.a = new_a;
+    cpu.regs.f = new_flags;
+}
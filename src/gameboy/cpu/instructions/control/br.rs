@@ -11,6 +11,7 @@ pub fn op_20(cpu: &mut Cpu) {
     let offset = cpu.next_byte() as i8;
     if cpu.get_flag_z() == 0 {
         cpu.pc = cpu.pc.wrapping_add(offset as u16);
+        cpu.cycles += 4;
     }
 }
 
@@ -19,6 +20,7 @@ pub fn op_28(cpu: &mut Cpu) {
     let offset = cpu.next_byte() as i8;
     if cpu.get_flag_z() != 0 {
         cpu.pc = cpu.pc.wrapping_add(offset as u16);
+        cpu.cycles += 4;
     }
 }
 
@@ -30,6 +32,217 @@ pub fn op_c9(cpu: &mut Cpu) {
 /// CALL u16
 pub fn op_cd(cpu: &mut Cpu) {
     let word = cpu.next_word();
+    cpu.internal_cycle();
     cpu.push_stack(cpu.pc);
     cpu.pc = word;
 }
+
+/// JR NC, i8
+pub fn op_30(cpu: &mut Cpu) {
+    let offset = cpu.next_byte() as i8;
+    if cpu.get_flag_c() == 0 {
+        cpu.pc = cpu.pc.wrapping_add(offset as u16);
+        cpu.cycles += 4;
+    }
+}
+
+/// JR C, i8
+pub fn op_38(cpu: &mut Cpu) {
+    let offset = cpu.next_byte() as i8;
+    if cpu.get_flag_c() != 0 {
+        cpu.pc = cpu.pc.wrapping_add(offset as u16);
+        cpu.cycles += 4;
+    }
+}
+
+/// RET NZ
+pub fn op_c0(cpu: &mut Cpu) {
+    // Checking the condition takes an M-cycle of its own
+    cpu.internal_cycle();
+    if cpu.get_flag_z() == 0 {
+        cpu.pc = cpu.pop_stack();
+        cpu.cycles += 12;
+    }
+}
+
+/// JP NZ, u16
+pub fn op_c2(cpu: &mut Cpu) {
+    let word = cpu.next_word();
+    if cpu.get_flag_z() == 0 {
+        cpu.pc = word;
+        cpu.cycles += 4;
+    }
+}
+
+/// JP u16
+pub fn op_c3(cpu: &mut Cpu) {
+    cpu.pc = cpu.next_word();
+}
+
+/// CALL NZ, u16
+pub fn op_c4(cpu: &mut Cpu) {
+    let word = cpu.next_word();
+    if cpu.get_flag_z() == 0 {
+        cpu.internal_cycle();
+        cpu.push_stack(cpu.pc);
+        cpu.pc = word;
+        cpu.cycles += 12;
+    }
+}
+
+/// RST 00h
+pub fn op_c7(cpu: &mut Cpu) {
+    cpu.internal_cycle();
+    cpu.push_stack(cpu.pc);
+    cpu.pc = 0x0000;
+}
+
+/// RET Z
+pub fn op_c8(cpu: &mut Cpu) {
+    // Checking the condition takes an M-cycle of its own
+    cpu.internal_cycle();
+    if cpu.get_flag_z() != 0 {
+        cpu.pc = cpu.pop_stack();
+        cpu.cycles += 12;
+    }
+}
+
+/// JP Z, u16
+pub fn op_ca(cpu: &mut Cpu) {
+    let word = cpu.next_word();
+    if cpu.get_flag_z() != 0 {
+        cpu.pc = word;
+        cpu.cycles += 4;
+    }
+}
+
+/// CALL Z, u16
+pub fn op_cc(cpu: &mut Cpu) {
+    let word = cpu.next_word();
+    if cpu.get_flag_z() != 0 {
+        cpu.internal_cycle();
+        cpu.push_stack(cpu.pc);
+        cpu.pc = word;
+        cpu.cycles += 12;
+    }
+}
+
+/// RST 08h
+pub fn op_cf(cpu: &mut Cpu) {
+    cpu.internal_cycle();
+    cpu.push_stack(cpu.pc);
+    cpu.pc = 0x0008;
+}
+
+/// RET NC
+pub fn op_d0(cpu: &mut Cpu) {
+    // Checking the condition takes an M-cycle of its own
+    cpu.internal_cycle();
+    if cpu.get_flag_c() == 0 {
+        cpu.pc = cpu.pop_stack();
+        cpu.cycles += 12;
+    }
+}
+
+/// JP NC, u16
+pub fn op_d2(cpu: &mut Cpu) {
+    let word = cpu.next_word();
+    if cpu.get_flag_c() == 0 {
+        cpu.pc = word;
+        cpu.cycles += 4;
+    }
+}
+
+/// CALL NC, u16
+pub fn op_d4(cpu: &mut Cpu) {
+    let word = cpu.next_word();
+    if cpu.get_flag_c() == 0 {
+        cpu.internal_cycle();
+        cpu.push_stack(cpu.pc);
+        cpu.pc = word;
+        cpu.cycles += 12;
+    }
+}
+
+/// RST 10h
+pub fn op_d7(cpu: &mut Cpu) {
+    cpu.internal_cycle();
+    cpu.push_stack(cpu.pc);
+    cpu.pc = 0x0010;
+}
+
+/// RET C
+pub fn op_d8(cpu: &mut Cpu) {
+    // Checking the condition takes an M-cycle of its own
+    cpu.internal_cycle();
+    if cpu.get_flag_c() != 0 {
+        cpu.pc = cpu.pop_stack();
+        cpu.cycles += 12;
+    }
+}
+
+/// RETI
+pub fn op_d9(cpu: &mut Cpu) {
+    cpu.pc = cpu.pop_stack();
+    cpu.ime = true;
+}
+
+/// JP C, u16
+pub fn op_da(cpu: &mut Cpu) {
+    let word = cpu.next_word();
+    if cpu.get_flag_c() != 0 {
+        cpu.pc = word;
+        cpu.cycles += 4;
+    }
+}
+
+/// CALL C, u16
+pub fn op_dc(cpu: &mut Cpu) {
+    let word = cpu.next_word();
+    if cpu.get_flag_c() != 0 {
+        cpu.internal_cycle();
+        cpu.push_stack(cpu.pc);
+        cpu.pc = word;
+        cpu.cycles += 12;
+    }
+}
+
+/// RST 18h
+pub fn op_df(cpu: &mut Cpu) {
+    cpu.internal_cycle();
+    cpu.push_stack(cpu.pc);
+    cpu.pc = 0x0018;
+}
+
+/// RST 20h
+pub fn op_e7(cpu: &mut Cpu) {
+    cpu.internal_cycle();
+    cpu.push_stack(cpu.pc);
+    cpu.pc = 0x0020;
+}
+
+/// JP HL
+pub fn op_e9(cpu: &mut Cpu) {
+    cpu.pc = cpu.regs.hl();
+}
+
+/// RST 28h
+pub fn op_ef(cpu: &mut Cpu) {
+    cpu.internal_cycle();
+    cpu.push_stack(cpu.pc);
+    cpu.pc = 0x0028;
+}
+
+/// RST 30h
+pub fn op_f7(cpu: &mut Cpu) {
+    cpu.internal_cycle();
+    cpu.push_stack(cpu.pc);
+    cpu.pc = 0x0030;
+}
+
+/// RST 38h
+pub fn op_ff(cpu: &mut Cpu) {
+    cpu.internal_cycle();
+    cpu.push_stack(cpu.pc);
+    cpu.pc = 0x0038;
+}
@@ -4,3 +4,37 @@ use crate::gameboy::cpu::Cpu;
 pub fn op_00(_cpu: &mut Cpu) {
     // NOP
 }
+
+/// STOP
+pub fn op_10(cpu: &mut Cpu) {
+    // STOP is followed by a padding byte
+    cpu.next_byte();
+
+    // On the CGB, STOP performs a pending speed switch, which stalls the CPU
+    if !cpu.mem.switch_speed() {
+        cpu.stopped = true;
+    }
+}
+
+/// HALT
+pub fn op_76(cpu: &mut Cpu) {
+    // With interrupts disabled and one already pending, HALT doesn't halt
+    // and the next opcode is read twice
+    if !cpu.ime && cpu.mem.pending_interrupts() != 0 {
+        cpu.halt_bug = true;
+    } else {
+        cpu.halt = true;
+    }
+}
+
+/// DI
+pub fn op_f3(cpu: &mut Cpu) {
+    cpu.ime = false;
+    cpu.ime_pending = false;
+}
+
+/// EI
+pub fn op_fb(cpu: &mut Cpu) {
+    // Interrupts are enabled after the next instruction
+    cpu.ime_pending = true;
+}
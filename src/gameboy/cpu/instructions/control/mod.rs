@@ -1,5 +1,3 @@
 pub mod br;
-pub use br::*;
 
 pub mod misc;
-pub use misc::*;
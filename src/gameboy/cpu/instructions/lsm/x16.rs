@@ -1,3 +1,4 @@
+use crate::gameboy::cpu::instructions::util::add_signed;
 use crate::gameboy::cpu::Cpu;
 
 /// LD DE, u16
@@ -25,5 +26,70 @@ pub fn op_c1(cpu: &mut Cpu) {
 
 /// PUSH BC
 pub fn op_c5(cpu: &mut Cpu) {
+    cpu.internal_cycle();
     cpu.push_stack(cpu.regs.bc());
 }
+
+/// LD BC, u16
+pub fn op_01(cpu: &mut Cpu) {
+    let word = cpu.next_word();
+    cpu.regs.set_bc(word);
+}
+
+/// LD (u16), SP
+pub fn op_08(cpu: &mut Cpu) {
+    let addr = cpu.next_word();
+    let sp = cpu.sp;
+    cpu.write_byte(addr, sp as u8);
+    cpu.write_byte(addr.wrapping_add(1), (sp >> 8) as u8);
+}
+
+/// POP DE
+pub fn op_d1(cpu: &mut Cpu) {
+    let word = cpu.pop_stack();
+    cpu.regs.set_de(word);
+}
+
+/// PUSH DE
+pub fn op_d5(cpu: &mut Cpu) {
+    cpu.internal_cycle();
+    cpu.push_stack(cpu.regs.de());
+}
+
+/// POP HL
+pub fn op_e1(cpu: &mut Cpu) {
+    let word = cpu.pop_stack();
+    cpu.regs.set_hl(word);
+}
+
+/// PUSH HL
+pub fn op_e5(cpu: &mut Cpu) {
+    cpu.internal_cycle();
+    cpu.push_stack(cpu.regs.hl());
+}
+
+/// POP AF
+pub fn op_f1(cpu: &mut Cpu) {
+    // The low nibble of F always reads 0
+    let word = cpu.pop_stack();
+    cpu.regs.set_af(word & 0xfff0);
+}
+
+/// PUSH AF
+pub fn op_f5(cpu: &mut Cpu) {
+    cpu.internal_cycle();
+    cpu.push_stack(cpu.regs.af());
+}
+
+/// LD HL, SP+i8
+pub fn op_f8(cpu: &mut Cpu) {
+    let offset = cpu.next_byte();
+    let (new_hl, new_flags) = add_signed(cpu.sp, offset);
+    cpu.regs.set_hl(new_hl);
+    cpu.regs.f = new_flags;
+}
+
+/// LD SP, HL
+pub fn op_f9(cpu: &mut Cpu) {
+    cpu.sp = cpu.regs.hl();
+}
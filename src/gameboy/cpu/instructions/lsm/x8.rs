@@ -13,7 +13,7 @@ pub fn op_16(cpu: &mut Cpu) {
 /// LD A, (DE)
 pub fn op_1a(cpu: &mut Cpu) {
     let addr = cpu.regs.de();
-    cpu.regs.a = cpu.mem.read().unwrap().read_byte(addr);
+    cpu.regs.a = cpu.read_byte(addr);
 }
 
 /// LD E, u8
@@ -25,7 +25,8 @@ pub fn op_1e(cpu: &mut Cpu) {
 pub fn op_22(cpu: &mut Cpu) {
     let addr = cpu.regs.hl();
     let a = cpu.regs.a;
-    cpu.mem.write().unwrap().write_byte(addr, a);
+    cpu.write_byte(addr, a);
+    cpu.mem.corrupt_oam(addr);
     cpu.regs.set_hl(addr.wrapping_add(1));
 }
 
@@ -33,7 +34,8 @@ pub fn op_22(cpu: &mut Cpu) {
 pub fn op_32(cpu: &mut Cpu) {
     let addr = cpu.regs.hl();
     let a = cpu.regs.a;
-    cpu.mem.write().unwrap().write_byte(addr, a);
+    cpu.write_byte(addr, a);
+    cpu.mem.corrupt_oam(addr);
     cpu.regs.set_hl(addr.wrapping_sub(1));
 }
 
@@ -56,7 +58,7 @@ pub fn op_67(cpu: &mut Cpu) {
 pub fn op_77(cpu: &mut Cpu) {
     let addr = cpu.regs.hl();
     let a = cpu.regs.a;
-    cpu.mem.write().unwrap().write_byte(addr, a);
+    cpu.write_byte(addr, a);
 }
 
 /// LD A, B
@@ -83,21 +85,21 @@ pub fn op_7d(cpu: &mut Cpu) {
 pub fn op_e0(cpu: &mut Cpu) {
     let addr = 0xff00 + cpu.next_byte() as u16;
     let a = cpu.regs.a;
-    cpu.mem.write().unwrap().write_byte(addr, a);
+    cpu.write_byte(addr, a);
 }
 
 /// LD (FF00+C), A
 pub fn op_e2(cpu: &mut Cpu) {
     let addr = 0xff00 + cpu.regs.c as u16;
     let a = cpu.regs.a;
-    cpu.mem.write().unwrap().write_byte(addr, a);
+    cpu.write_byte(addr, a);
 }
 
 /// LD (u16), A
 pub fn op_ea(cpu: &mut Cpu) {
     let addr = cpu.next_word();
     let a = cpu.regs.a;
-    cpu.mem.write().unwrap().write_byte(addr, a);
+    cpu.write_byte(addr, a);
 }
 
 /// LD C, u8
@@ -118,5 +120,359 @@ pub fn op_3e(cpu: &mut Cpu) {
 /// LD A, (FF00+u8)
 pub fn op_f0(cpu: &mut Cpu) {
     let addr = 0xff00 + cpu.next_byte() as u16;
-    cpu.regs.a = cpu.mem.read().unwrap().read_byte(addr);
+    cpu.regs.a = cpu.read_byte(addr);
+}
+
+/// LD (BC), A
+pub fn op_02(cpu: &mut Cpu) {
+    let addr = cpu.regs.bc();
+    let a = cpu.regs.a;
+    cpu.write_byte(addr, a);
+}
+
+/// LD A, (BC)
+pub fn op_0a(cpu: &mut Cpu) {
+    let addr = cpu.regs.bc();
+    cpu.regs.a = cpu.read_byte(addr);
+}
+
+/// LD (DE), A
+pub fn op_12(cpu: &mut Cpu) {
+    let addr = cpu.regs.de();
+    let a = cpu.regs.a;
+    cpu.write_byte(addr, a);
+}
+
+/// LD H, u8
+pub fn op_26(cpu: &mut Cpu) {
+    cpu.regs.h = cpu.next_byte();
+}
+
+/// LD A, (HL+)
+pub fn op_2a(cpu: &mut Cpu) {
+    let addr = cpu.regs.hl();
+    cpu.regs.a = cpu.read_byte(addr);
+    cpu.mem.corrupt_oam(addr);
+    cpu.regs.set_hl(addr.wrapping_add(1));
+}
+
+/// LD (HL), u8
+pub fn op_36(cpu: &mut Cpu) {
+    let byte = cpu.next_byte();
+    let addr = cpu.regs.hl();
+    cpu.write_byte(addr, byte);
+}
+
+/// LD A, (HL-)
+pub fn op_3a(cpu: &mut Cpu) {
+    let addr = cpu.regs.hl();
+    cpu.regs.a = cpu.read_byte(addr);
+    cpu.mem.corrupt_oam(addr);
+    cpu.regs.set_hl(addr.wrapping_sub(1));
+}
+
+/// LD B, B
+pub fn op_40(_cpu: &mut Cpu) {
+    // Loading B into itself changes nothing
+}
+
+/// LD B, C
+pub fn op_41(cpu: &mut Cpu) {
+    cpu.regs.b = cpu.regs.c;
+}
+
+/// LD B, D
+pub fn op_42(cpu: &mut Cpu) {
+    cpu.regs.b = cpu.regs.d;
+}
+
+/// LD B, E
+pub fn op_43(cpu: &mut Cpu) {
+    cpu.regs.b = cpu.regs.e;
+}
+
+/// LD B, H
+pub fn op_44(cpu: &mut Cpu) {
+    cpu.regs.b = cpu.regs.h;
+}
+
+/// LD B, L
+pub fn op_45(cpu: &mut Cpu) {
+    cpu.regs.b = cpu.regs.l;
+}
+
+/// LD B, (HL)
+pub fn op_46(cpu: &mut Cpu) {
+    let addr = cpu.regs.hl();
+    cpu.regs.b = cpu.read_byte(addr);
+}
+
+/// LD B, A
+pub fn op_47(cpu: &mut Cpu) {
+    cpu.regs.b = cpu.regs.a;
+}
+
+/// LD C, B
+pub fn op_48(cpu: &mut Cpu) {
+    cpu.regs.c = cpu.regs.b;
+}
+
+/// LD C, C
+pub fn op_49(_cpu: &mut Cpu) {
+    // Loading C into itself changes nothing
+}
+
+/// LD C, D
+pub fn op_4a(cpu: &mut Cpu) {
+    cpu.regs.c = cpu.regs.d;
+}
+
+/// LD C, E
+pub fn op_4b(cpu: &mut Cpu) {
+    cpu.regs.c = cpu.regs.e;
+}
+
+/// LD C, H
+pub fn op_4c(cpu: &mut Cpu) {
+    cpu.regs.c = cpu.regs.h;
+}
+
+/// LD C, L
+pub fn op_4d(cpu: &mut Cpu) {
+    cpu.regs.c = cpu.regs.l;
+}
+
+/// LD C, (HL)
+pub fn op_4e(cpu: &mut Cpu) {
+    let addr = cpu.regs.hl();
+    cpu.regs.c = cpu.read_byte(addr);
+}
+
+/// LD D, B
+pub fn op_50(cpu: &mut Cpu) {
+    cpu.regs.d = cpu.regs.b;
+}
+
+/// LD D, C
+pub fn op_51(cpu: &mut Cpu) {
+    cpu.regs.d = cpu.regs.c;
+}
+
+/// LD D, D
+pub fn op_52(_cpu: &mut Cpu) {
+    // Loading D into itself changes nothing
+}
+
+/// LD D, E
+pub fn op_53(cpu: &mut Cpu) {
+    cpu.regs.d = cpu.regs.e;
+}
+
+/// LD D, H
+pub fn op_54(cpu: &mut Cpu) {
+    cpu.regs.d = cpu.regs.h;
+}
+
+/// LD D, L
+pub fn op_55(cpu: &mut Cpu) {
+    cpu.regs.d = cpu.regs.l;
+}
+
+/// LD D, (HL)
+pub fn op_56(cpu: &mut Cpu) {
+    let addr = cpu.regs.hl();
+    cpu.regs.d = cpu.read_byte(addr);
+}
+
+/// LD E, B
+pub fn op_58(cpu: &mut Cpu) {
+    cpu.regs.e = cpu.regs.b;
+}
+
+/// LD E, C
+pub fn op_59(cpu: &mut Cpu) {
+    cpu.regs.e = cpu.regs.c;
+}
+
+/// LD E, D
+pub fn op_5a(cpu: &mut Cpu) {
+    cpu.regs.e = cpu.regs.d;
+}
+
+/// LD E, E
+pub fn op_5b(_cpu: &mut Cpu) {
+    // Loading E into itself changes nothing
+}
+
+/// LD E, H
+pub fn op_5c(cpu: &mut Cpu) {
+    cpu.regs.e = cpu.regs.h;
+}
+
+/// LD E, L
+pub fn op_5d(cpu: &mut Cpu) {
+    cpu.regs.e = cpu.regs.l;
+}
+
+/// LD E, (HL)
+pub fn op_5e(cpu: &mut Cpu) {
+    let addr = cpu.regs.hl();
+    cpu.regs.e = cpu.read_byte(addr);
+}
+
+/// LD E, A
+pub fn op_5f(cpu: &mut Cpu) {
+    cpu.regs.e = cpu.regs.a;
+}
+
+/// LD H, B
+pub fn op_60(cpu: &mut Cpu) {
+    cpu.regs.h = cpu.regs.b;
+}
+
+/// LD H, C
+pub fn op_61(cpu: &mut Cpu) {
+    cpu.regs.h = cpu.regs.c;
+}
+
+/// LD H, D
+pub fn op_62(cpu: &mut Cpu) {
+    cpu.regs.h = cpu.regs.d;
+}
+
+/// LD H, E
+pub fn op_63(cpu: &mut Cpu) {
+    cpu.regs.h = cpu.regs.e;
+}
+
+/// LD H, H
+pub fn op_64(_cpu: &mut Cpu) {
+    // Loading H into itself changes nothing
+}
+
+/// LD H, L
+pub fn op_65(cpu: &mut Cpu) {
+    cpu.regs.h = cpu.regs.l;
+}
+
+/// LD H, (HL)
+pub fn op_66(cpu: &mut Cpu) {
+    let addr = cpu.regs.hl();
+    cpu.regs.h = cpu.read_byte(addr);
+}
+
+/// LD L, B
+pub fn op_68(cpu: &mut Cpu) {
+    cpu.regs.l = cpu.regs.b;
+}
+
+/// LD L, C
+pub fn op_69(cpu: &mut Cpu) {
+    cpu.regs.l = cpu.regs.c;
+}
+
+/// LD L, D
+pub fn op_6a(cpu: &mut Cpu) {
+    cpu.regs.l = cpu.regs.d;
+}
+
+/// LD L, E
+pub fn op_6b(cpu: &mut Cpu) {
+    cpu.regs.l = cpu.regs.e;
+}
+
+/// LD L, H
+pub fn op_6c(cpu: &mut Cpu) {
+    cpu.regs.l = cpu.regs.h;
+}
+
+/// LD L, L
+pub fn op_6d(_cpu: &mut Cpu) {
+    // Loading L into itself changes nothing
+}
+
+/// LD L, (HL)
+pub fn op_6e(cpu: &mut Cpu) {
+    let addr = cpu.regs.hl();
+    cpu.regs.l = cpu.read_byte(addr);
+}
+
+/// LD L, A
+pub fn op_6f(cpu: &mut Cpu) {
+    cpu.regs.l = cpu.regs.a;
+}
+
+/// LD (HL), B
+pub fn op_70(cpu: &mut Cpu) {
+    let addr = cpu.regs.hl();
+    let b = cpu.regs.b;
+    cpu.write_byte(addr, b);
+}
+
+/// LD (HL), C
+pub fn op_71(cpu: &mut Cpu) {
+    let addr = cpu.regs.hl();
+    let c = cpu.regs.c;
+    cpu.write_byte(addr, c);
+}
+
+/// LD (HL), D
+pub fn op_72(cpu: &mut Cpu) {
+    let addr = cpu.regs.hl();
+    let d = cpu.regs.d;
+    cpu.write_byte(addr, d);
+}
+
+/// LD (HL), E
+pub fn op_73(cpu: &mut Cpu) {
+    let addr = cpu.regs.hl();
+    let e = cpu.regs.e;
+    cpu.write_byte(addr, e);
+}
+
+/// LD (HL), H
+pub fn op_74(cpu: &mut Cpu) {
+    let addr = cpu.regs.hl();
+    let h = cpu.regs.h;
+    cpu.write_byte(addr, h);
+}
+
+/// LD (HL), L
+pub fn op_75(cpu: &mut Cpu) {
+    let addr = cpu.regs.hl();
+    let l = cpu.regs.l;
+    cpu.write_byte(addr, l);
+}
+
+/// LD A, C
+pub fn op_79(cpu: &mut Cpu) {
+    cpu.regs.a = cpu.regs.c;
+}
+
+/// LD A, D
+pub fn op_7a(cpu: &mut Cpu) {
+    cpu.regs.a = cpu.regs.d;
+}
+
+/// LD A, (HL)
+pub fn op_7e(cpu: &mut Cpu) {
+    let addr = cpu.regs.hl();
+    cpu.regs.a = cpu.read_byte(addr);
+}
+
+/// LD A, A
+pub fn op_7f(_cpu: &mut Cpu) {
+    // Loading A into itself changes nothing
+}
+
+/// LD A, (FF00+C)
+pub fn op_f2(cpu: &mut Cpu) {
+    let addr = 0xff00 + cpu.regs.c as u16;
+    cpu.regs.a = cpu.read_byte(addr);
+}
+
+/// LD A, (u16)
+pub fn op_fa(cpu: &mut Cpu) {
+    let addr = cpu.next_word();
+    cpu.regs.a = cpu.read_byte(addr);
 }
@@ -17,10 +17,12 @@ pub mod util;
 /// Represents an instruction of the GameBoy.
 #[derive(Debug, Clone)]
 pub struct Instruction {
-    //cycles: u8,
     //length: u8,
     pub addr: u16,
     pub opcode: u8,
+    /// The number of T-cycles the instruction takes. Conditional instructions
+    /// add the extra cycles of a taken branch themselves.
+    pub cycles: u8,
     pub description: String,
     pub is_prefixed: bool,
     pub execute: fn(&mut Cpu),
@@ -32,12 +34,14 @@ impl Instruction {
         execute: fn(&mut Cpu),
         addr: u16,
         opcode: u8,
+        cycles: u8,
         description: &str,
         is_prefixed: bool,
     ) -> Self {
         Self {
             addr,
             opcode,
+            cycles,
             description: description.to_string(),
             is_prefixed,
             execute,
@@ -45,12 +49,24 @@ impl Instruction {
     }
 
     /// Creates a new normal `Instruction`. (Shortcut)
-    pub fn normal(execute: fn(&mut Cpu), addr: u16, opcode: u8, description: &str) -> Self {
-        Self::new(execute, addr, opcode, description, false)
+    pub fn normal(
+        execute: fn(&mut Cpu),
+        addr: u16,
+        opcode: u8,
+        cycles: u8,
+        description: &str,
+    ) -> Self {
+        Self::new(execute, addr, opcode, cycles, description, false)
     }
 
     /// Creates a new prefixed `Instruction`. (Shortcut)
-    pub fn prefixed(execute: fn(&mut Cpu), addr: u16, opcode: u8, description: &str) -> Self {
-        Self::new(execute, addr, opcode, description, true)
+    pub fn prefixed(
+        execute: fn(&mut Cpu),
+        addr: u16,
+        opcode: u8,
+        cycles: u8,
+        description: &str,
+    ) -> Self {
+        Self::new(execute, addr, opcode, cycles, description, true)
     }
 }
@@ -1,2 +1 @@
 pub mod x8;
-pub use x8::*;
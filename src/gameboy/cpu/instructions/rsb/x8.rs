@@ -1,4 +1,8 @@
-use crate::gameboy::cpu::instructions::util::{bit, rotate_left, rotate_left_through_carry};
+use crate::gameboy::cpu::instructions::util::{
+    bit, reset_bit, rotate_left, rotate_left_through_carry, rotate_right,
+    rotate_right_through_carry, set_bit, shift_left_arithmetic, shift_right_arithmetic,
+    shift_right_logical, swap,
+};
 use crate::gameboy::cpu::Cpu;
 
 /// BIT 7, H
@@ -7,12 +11,1385 @@ pub fn op_cb7c(cpu: &mut Cpu) {
     cpu.regs.f = bit(7, cpu.regs.h, cpu.regs.f);
 }
 
-// RL C
+/// RL C
 pub fn op_cb11(cpu: &mut Cpu) {
     (cpu.regs.c, cpu.regs.f) = rotate_left_through_carry(cpu.regs.c, cpu.regs.f);
 }
 
 /// RLA
 pub fn op_17(cpu: &mut Cpu) {
+    (cpu.regs.a, cpu.regs.f) = rotate_left_through_carry(cpu.regs.a, cpu.regs.f);
+    // Unlike RL A, the zero flag is always cleared
+    cpu.set_flag_z(false);
+}
+
+/// RLCA
+pub fn op_07(cpu: &mut Cpu) {
+    (cpu.regs.a, cpu.regs.f) = rotate_left(cpu.regs.a, cpu.regs.f);
+    // Unlike RLC A, the zero flag is always cleared
+    cpu.set_flag_z(false);
+}
+
+/// RRCA
+pub fn op_0f(cpu: &mut Cpu) {
+    (cpu.regs.a, cpu.regs.f) = rotate_right(cpu.regs.a, cpu.regs.f);
+    // Unlike RRC A, the zero flag is always cleared
+    cpu.set_flag_z(false);
+}
+
+/// RRA
+pub fn op_1f(cpu: &mut Cpu) {
+    (cpu.regs.a, cpu.regs.f) = rotate_right_through_carry(cpu.regs.a, cpu.regs.f);
+    // Unlike RR A, the zero flag is always cleared
+    cpu.set_flag_z(false);
+}
+
+/// RLC B
+pub fn op_cb00(cpu: &mut Cpu) {
+    (cpu.regs.b, cpu.regs.f) = rotate_left(cpu.regs.b, cpu.regs.f);
+}
+
+/// RLC C
+pub fn op_cb01(cpu: &mut Cpu) {
+    (cpu.regs.c, cpu.regs.f) = rotate_left(cpu.regs.c, cpu.regs.f);
+}
+
+/// RLC D
+pub fn op_cb02(cpu: &mut Cpu) {
+    (cpu.regs.d, cpu.regs.f) = rotate_left(cpu.regs.d, cpu.regs.f);
+}
+
+/// RLC E
+pub fn op_cb03(cpu: &mut Cpu) {
+    (cpu.regs.e, cpu.regs.f) = rotate_left(cpu.regs.e, cpu.regs.f);
+}
+
+/// RLC H
+pub fn op_cb04(cpu: &mut Cpu) {
+    (cpu.regs.h, cpu.regs.f) = rotate_left(cpu.regs.h, cpu.regs.f);
+}
+
+/// RLC L
+pub fn op_cb05(cpu: &mut Cpu) {
+    (cpu.regs.l, cpu.regs.f) = rotate_left(cpu.regs.l, cpu.regs.f);
+}
+
+/// RLC (HL)
+pub fn op_cb06(cpu: &mut Cpu) {
+    let addr = cpu.regs.hl();
+    let byte = cpu.read_byte(addr);
+    let (new_byte, new_flags) = rotate_left(byte, cpu.regs.f);
+    cpu.write_byte(addr, new_byte);
+    cpu.regs.f = new_flags;
+}
+
+/// RLC A
+pub fn op_cb07(cpu: &mut Cpu) {
     (cpu.regs.a, cpu.regs.f) = rotate_left(cpu.regs.a, cpu.regs.f);
 }
+
+/// RRC B
+pub fn op_cb08(cpu: &mut Cpu) {
+    (cpu.regs.b, cpu.regs.f) = rotate_right(cpu.regs.b, cpu.regs.f);
+}
+
+/// RRC C
+pub fn op_cb09(cpu: &mut Cpu) {
+    (cpu.regs.c, cpu.regs.f) = rotate_right(cpu.regs.c, cpu.regs.f);
+}
+
+/// RRC D
+pub fn op_cb0a(cpu: &mut Cpu) {
+    (cpu.regs.d, cpu.regs.f) = rotate_right(cpu.regs.d, cpu.regs.f);
+}
+
+/// RRC E
+pub fn op_cb0b(cpu: &mut Cpu) {
+    (cpu.regs.e, cpu.regs.f) = rotate_right(cpu.regs.e, cpu.regs.f);
+}
+
+/// RRC H
+pub fn op_cb0c(cpu: &mut Cpu) {
+    (cpu.regs.h, cpu.regs.f) = rotate_right(cpu.regs.h, cpu.regs.f);
+}
+
+/// RRC L
+pub fn op_cb0d(cpu: &mut Cpu) {
+    (cpu.regs.l, cpu.regs.f) = rotate_right(cpu.regs.l, cpu.regs.f);
+}
+
+/// RRC (HL)
+pub fn op_cb0e(cpu: &mut Cpu) {
+    let addr = cpu.regs.hl();
+    let byte = cpu.read_byte(addr);
+    let (new_byte, new_flags) = rotate_right(byte, cpu.regs.f);
+    cpu.write_byte(addr, new_byte);
+    cpu.regs.f = new_flags;
+}
+
+/// RRC A
+pub fn op_cb0f(cpu: &mut Cpu) {
+    (cpu.regs.a, cpu.regs.f) = rotate_right(cpu.regs.a, cpu.regs.f);
+}
+
+/// RL B
+pub fn op_cb10(cpu: &mut Cpu) {
+    (cpu.regs.b, cpu.regs.f) = rotate_left_through_carry(cpu.regs.b, cpu.regs.f);
+}
+
+/// RL D
+pub fn op_cb12(cpu: &mut Cpu) {
+    (cpu.regs.d, cpu.regs.f) = rotate_left_through_carry(cpu.regs.d, cpu.regs.f);
+}
+
+/// RL E
+pub fn op_cb13(cpu: &mut Cpu) {
+    (cpu.regs.e, cpu.regs.f) = rotate_left_through_carry(cpu.regs.e, cpu.regs.f);
+}
+
+/// RL H
+pub fn op_cb14(cpu: &mut Cpu) {
+    (cpu.regs.h, cpu.regs.f) = rotate_left_through_carry(cpu.regs.h, cpu.regs.f);
+}
+
+/// RL L
+pub fn op_cb15(cpu: &mut Cpu) {
+    (cpu.regs.l, cpu.regs.f) = rotate_left_through_carry(cpu.regs.l, cpu.regs.f);
+}
+
+/// RL (HL)
+pub fn op_cb16(cpu: &mut Cpu) {
+    let addr = cpu.regs.hl();
+    let byte = cpu.read_byte(addr);
+    let (new_byte, new_flags) = rotate_left_through_carry(byte, cpu.regs.f);
+    cpu.write_byte(addr, new_byte);
+    cpu.regs.f = new_flags;
+}
+
+/// RL A
+pub fn op_cb17(cpu: &mut Cpu) {
+    (cpu.regs.a, cpu.regs.f) = rotate_left_through_carry(cpu.regs.a, cpu.regs.f);
+}
+
+/// RR B
+pub fn op_cb18(cpu: &mut Cpu) {
+    (cpu.regs.b, cpu.regs.f) = rotate_right_through_carry(cpu.regs.b, cpu.regs.f);
+}
+
+/// RR C
+pub fn op_cb19(cpu: &mut Cpu) {
+    (cpu.regs.c, cpu.regs.f) = rotate_right_through_carry(cpu.regs.c, cpu.regs.f);
+}
+
+/// RR D
+pub fn op_cb1a(cpu: &mut Cpu) {
+    (cpu.regs.d, cpu.regs.f) = rotate_right_through_carry(cpu.regs.d, cpu.regs.f);
+}
+
+/// RR E
+pub fn op_cb1b(cpu: &mut Cpu) {
+    (cpu.regs.e, cpu.regs.f) = rotate_right_through_carry(cpu.regs.e, cpu.regs.f);
+}
+
+/// RR H
+pub fn op_cb1c(cpu: &mut Cpu) {
+    (cpu.regs.h, cpu.regs.f) = rotate_right_through_carry(cpu.regs.h, cpu.regs.f);
+}
+
+/// RR L
+pub fn op_cb1d(cpu: &mut Cpu) {
+    (cpu.regs.l, cpu.regs.f) = rotate_right_through_carry(cpu.regs.l, cpu.regs.f);
+}
+
+/// RR (HL)
+pub fn op_cb1e(cpu: &mut Cpu) {
+    let addr = cpu.regs.hl();
+    let byte = cpu.read_byte(addr);
+    let (new_byte, new_flags) = rotate_right_through_carry(byte, cpu.regs.f);
+    cpu.write_byte(addr, new_byte);
+    cpu.regs.f = new_flags;
+}
+
+/// RR A
+pub fn op_cb1f(cpu: &mut Cpu) {
+    (cpu.regs.a, cpu.regs.f) = rotate_right_through_carry(cpu.regs.a, cpu.regs.f);
+}
+
+/// SLA B
+pub fn op_cb20(cpu: &mut Cpu) {
+    (cpu.regs.b, cpu.regs.f) = shift_left_arithmetic(cpu.regs.b);
+}
+
+/// SLA C
+pub fn op_cb21(cpu: &mut Cpu) {
+    (cpu.regs.c, cpu.regs.f) = shift_left_arithmetic(cpu.regs.c);
+}
+
+/// SLA D
+pub fn op_cb22(cpu: &mut Cpu) {
+    (cpu.regs.d, cpu.regs.f) = shift_left_arithmetic(cpu.regs.d);
+}
+
+/// SLA E
+pub fn op_cb23(cpu: &mut Cpu) {
+    (cpu.regs.e, cpu.regs.f) = shift_left_arithmetic(cpu.regs.e);
+}
+
+/// SLA H
+pub fn op_cb24(cpu: &mut Cpu) {
+    (cpu.regs.h, cpu.regs.f) = shift_left_arithmetic(cpu.regs.h);
+}
+
+/// SLA L
+pub fn op_cb25(cpu: &mut Cpu) {
+    (cpu.regs.l, cpu.regs.f) = shift_left_arithmetic(cpu.regs.l);
+}
+
+/// SLA (HL)
+pub fn op_cb26(cpu: &mut Cpu) {
+    let addr = cpu.regs.hl();
+    let byte = cpu.read_byte(addr);
+    let (new_byte, new_flags) = shift_left_arithmetic(byte);
+    cpu.write_byte(addr, new_byte);
+    cpu.regs.f = new_flags;
+}
+
+/// SLA A
+pub fn op_cb27(cpu: &mut Cpu) {
+    (cpu.regs.a, cpu.regs.f) = shift_left_arithmetic(cpu.regs.a);
+}
+
+/// SRA B
+pub fn op_cb28(cpu: &mut Cpu) {
+    (cpu.regs.b, cpu.regs.f) = shift_right_arithmetic(cpu.regs.b);
+}
+
+/// SRA C
+pub fn op_cb29(cpu: &mut Cpu) {
+    (cpu.regs.c, cpu.regs.f) = shift_right_arithmetic(cpu.regs.c);
+}
+
+/// SRA D
+pub fn op_cb2a(cpu: &mut Cpu) {
+    (cpu.regs.d, cpu.regs.f) = shift_right_arithmetic(cpu.regs.d);
+}
+
+/// SRA E
+pub fn op_cb2b(cpu: &mut Cpu) {
+    (cpu.regs.e, cpu.regs.f) = shift_right_arithmetic(cpu.regs.e);
+}
+
+/// SRA H
+pub fn op_cb2c(cpu: &mut Cpu) {
+    (cpu.regs.h, cpu.regs.f) = shift_right_arithmetic(cpu.regs.h);
+}
+
+/// SRA L
+pub fn op_cb2d(cpu: &mut Cpu) {
+    (cpu.regs.l, cpu.regs.f) = shift_right_arithmetic(cpu.regs.l);
+}
+
+/// SRA (HL)
+pub fn op_cb2e(cpu: &mut Cpu) {
+    let addr = cpu.regs.hl();
+    let byte = cpu.read_byte(addr);
+    let (new_byte, new_flags) = shift_right_arithmetic(byte);
+    cpu.write_byte(addr, new_byte);
+    cpu.regs.f = new_flags;
+}
+
+/// SRA A
+pub fn op_cb2f(cpu: &mut Cpu) {
+    (cpu.regs.a, cpu.regs.f) = shift_right_arithmetic(cpu.regs.a);
+}
+
+/// SWAP B
+pub fn op_cb30(cpu: &mut Cpu) {
+    (cpu.regs.b, cpu.regs.f) = swap(cpu.regs.b);
+}
+
+/// SWAP C
+pub fn op_cb31(cpu: &mut Cpu) {
+    (cpu.regs.c, cpu.regs.f) = swap(cpu.regs.c);
+}
+
+/// SWAP D
+pub fn op_cb32(cpu: &mut Cpu) {
+    (cpu.regs.d, cpu.regs.f) = swap(cpu.regs.d);
+}
+
+/// SWAP E
+pub fn op_cb33(cpu: &mut Cpu) {
+    (cpu.regs.e, cpu.regs.f) = swap(cpu.regs.e);
+}
+
+/// SWAP H
+pub fn op_cb34(cpu: &mut Cpu) {
+    (cpu.regs.h, cpu.regs.f) = swap(cpu.regs.h);
+}
+
+/// SWAP L
+pub fn op_cb35(cpu: &mut Cpu) {
+    (cpu.regs.l, cpu.regs.f) = swap(cpu.regs.l);
+}
+
+/// SWAP (HL)
+pub fn op_cb36(cpu: &mut Cpu) {
+    let addr = cpu.regs.hl();
+    let byte = cpu.read_byte(addr);
+    let (new_byte, new_flags) = swap(byte);
+    cpu.write_byte(addr, new_byte);
+    cpu.regs.f = new_flags;
+}
+
+/// SWAP A
+pub fn op_cb37(cpu: &mut Cpu) {
+    (cpu.regs.a, cpu.regs.f) = swap(cpu.regs.a);
+}
+
+/// SRL B
+pub fn op_cb38(cpu: &mut Cpu) {
+    (cpu.regs.b, cpu.regs.f) = shift_right_logical(cpu.regs.b);
+}
+
+/// SRL C
+pub fn op_cb39(cpu: &mut Cpu) {
+    (cpu.regs.c, cpu.regs.f) = shift_right_logical(cpu.regs.c);
+}
+
+/// SRL D
+pub fn op_cb3a(cpu: &mut Cpu) {
+    (cpu.regs.d, cpu.regs.f) = shift_right_logical(cpu.regs.d);
+}
+
+/// SRL E
+pub fn op_cb3b(cpu: &mut Cpu) {
+    (cpu.regs.e, cpu.regs.f) = shift_right_logical(cpu.regs.e);
+}
+
+/// SRL H
+pub fn op_cb3c(cpu: &mut Cpu) {
+    (cpu.regs.h, cpu.regs.f) = shift_right_logical(cpu.regs.h);
+}
+
+/// SRL L
+pub fn op_cb3d(cpu: &mut Cpu) {
+    (cpu.regs.l, cpu.regs.f) = shift_right_logical(cpu.regs.l);
+}
+
+/// SRL (HL)
+pub fn op_cb3e(cpu: &mut Cpu) {
+    let addr = cpu.regs.hl();
+    let byte = cpu.read_byte(addr);
+    let (new_byte, new_flags) = shift_right_logical(byte);
+    cpu.write_byte(addr, new_byte);
+    cpu.regs.f = new_flags;
+}
+
+/// SRL A
+pub fn op_cb3f(cpu: &mut Cpu) {
+    (cpu.regs.a, cpu.regs.f) = shift_right_logical(cpu.regs.a);
+}
+
+/// BIT 0, B
+pub fn op_cb40(cpu: &mut Cpu) {
+    cpu.regs.f = bit(0, cpu.regs.b, cpu.regs.f);
+}
+
+/// BIT 0, C
+pub fn op_cb41(cpu: &mut Cpu) {
+    cpu.regs.f = bit(0, cpu.regs.c, cpu.regs.f);
+}
+
+/// BIT 0, D
+pub fn op_cb42(cpu: &mut Cpu) {
+    cpu.regs.f = bit(0, cpu.regs.d, cpu.regs.f);
+}
+
+/// BIT 0, E
+pub fn op_cb43(cpu: &mut Cpu) {
+    cpu.regs.f = bit(0, cpu.regs.e, cpu.regs.f);
+}
+
+/// BIT 0, H
+pub fn op_cb44(cpu: &mut Cpu) {
+    cpu.regs.f = bit(0, cpu.regs.h, cpu.regs.f);
+}
+
+/// BIT 0, L
+pub fn op_cb45(cpu: &mut Cpu) {
+    cpu.regs.f = bit(0, cpu.regs.l, cpu.regs.f);
+}
+
+/// BIT 0, (HL)
+pub fn op_cb46(cpu: &mut Cpu) {
+    let addr = cpu.regs.hl();
+    let byte = cpu.read_byte(addr);
+    cpu.regs.f = bit(0, byte, cpu.regs.f);
+}
+
+/// BIT 0, A
+pub fn op_cb47(cpu: &mut Cpu) {
+    cpu.regs.f = bit(0, cpu.regs.a, cpu.regs.f);
+}
+
+/// BIT 1, B
+pub fn op_cb48(cpu: &mut Cpu) {
+    cpu.regs.f = bit(1, cpu.regs.b, cpu.regs.f);
+}
+
+/// BIT 1, C
+pub fn op_cb49(cpu: &mut Cpu) {
+    cpu.regs.f = bit(1, cpu.regs.c, cpu.regs.f);
+}
+
+/// BIT 1, D
+pub fn op_cb4a(cpu: &mut Cpu) {
+    cpu.regs.f = bit(1, cpu.regs.d, cpu.regs.f);
+}
+
+/// BIT 1, E
+pub fn op_cb4b(cpu: &mut Cpu) {
+    cpu.regs.f = bit(1, cpu.regs.e, cpu.regs.f);
+}
+
+/// BIT 1, H
+pub fn op_cb4c(cpu: &mut Cpu) {
+    cpu.regs.f = bit(1, cpu.regs.h, cpu.regs.f);
+}
+
+/// BIT 1, L
+pub fn op_cb4d(cpu: &mut Cpu) {
+    cpu.regs.f = bit(1, cpu.regs.l, cpu.regs.f);
+}
+
+/// BIT 1, (HL)
+pub fn op_cb4e(cpu: &mut Cpu) {
+    let addr = cpu.regs.hl();
+    let byte = cpu.read_byte(addr);
+    cpu.regs.f = bit(1, byte, cpu.regs.f);
+}
+
+/// BIT 1, A
+pub fn op_cb4f(cpu: &mut Cpu) {
+    cpu.regs.f = bit(1, cpu.regs.a, cpu.regs.f);
+}
+
+/// BIT 2, B
+pub fn op_cb50(cpu: &mut Cpu) {
+    cpu.regs.f = bit(2, cpu.regs.b, cpu.regs.f);
+}
+
+/// BIT 2, C
+pub fn op_cb51(cpu: &mut Cpu) {
+    cpu.regs.f = bit(2, cpu.regs.c, cpu.regs.f);
+}
+
+/// BIT 2, D
+pub fn op_cb52(cpu: &mut Cpu) {
+    cpu.regs.f = bit(2, cpu.regs.d, cpu.regs.f);
+}
+
+/// BIT 2, E
+pub fn op_cb53(cpu: &mut Cpu) {
+    cpu.regs.f = bit(2, cpu.regs.e, cpu.regs.f);
+}
+
+/// BIT 2, H
+pub fn op_cb54(cpu: &mut Cpu) {
+    cpu.regs.f = bit(2, cpu.regs.h, cpu.regs.f);
+}
+
+/// BIT 2, L
+pub fn op_cb55(cpu: &mut Cpu) {
+    cpu.regs.f = bit(2, cpu.regs.l, cpu.regs.f);
+}
+
+/// BIT 2, (HL)
+pub fn op_cb56(cpu: &mut Cpu) {
+    let addr = cpu.regs.hl();
+    let byte = cpu.read_byte(addr);
+    cpu.regs.f = bit(2, byte, cpu.regs.f);
+}
+
+/// BIT 2, A
+pub fn op_cb57(cpu: &mut Cpu) {
+    cpu.regs.f = bit(2, cpu.regs.a, cpu.regs.f);
+}
+
+/// BIT 3, B
+pub fn op_cb58(cpu: &mut Cpu) {
+    cpu.regs.f = bit(3, cpu.regs.b, cpu.regs.f);
+}
+
+/// BIT 3, C
+pub fn op_cb59(cpu: &mut Cpu) {
+    cpu.regs.f = bit(3, cpu.regs.c, cpu.regs.f);
+}
+
+/// BIT 3, D
+pub fn op_cb5a(cpu: &mut Cpu) {
+    cpu.regs.f = bit(3, cpu.regs.d, cpu.regs.f);
+}
+
+/// BIT 3, E
+pub fn op_cb5b(cpu: &mut Cpu) {
+    cpu.regs.f = bit(3, cpu.regs.e, cpu.regs.f);
+}
+
+/// BIT 3, H
+pub fn op_cb5c(cpu: &mut Cpu) {
+    cpu.regs.f = bit(3, cpu.regs.h, cpu.regs.f);
+}
+
+/// BIT 3, L
+pub fn op_cb5d(cpu: &mut Cpu) {
+    cpu.regs.f = bit(3, cpu.regs.l, cpu.regs.f);
+}
+
+/// BIT 3, (HL)
+pub fn op_cb5e(cpu: &mut Cpu) {
+    let addr = cpu.regs.hl();
+    let byte = cpu.read_byte(addr);
+    cpu.regs.f = bit(3, byte, cpu.regs.f);
+}
+
+/// BIT 3, A
+pub fn op_cb5f(cpu: &mut Cpu) {
+    cpu.regs.f = bit(3, cpu.regs.a, cpu.regs.f);
+}
+
+/// BIT 4, B
+pub fn op_cb60(cpu: &mut Cpu) {
+    cpu.regs.f = bit(4, cpu.regs.b, cpu.regs.f);
+}
+
+/// BIT 4, C
+pub fn op_cb61(cpu: &mut Cpu) {
+    cpu.regs.f = bit(4, cpu.regs.c, cpu.regs.f);
+}
+
+/// BIT 4, D
+pub fn op_cb62(cpu: &mut Cpu) {
+    cpu.regs.f = bit(4, cpu.regs.d, cpu.regs.f);
+}
+
+/// BIT 4, E
+pub fn op_cb63(cpu: &mut Cpu) {
+    cpu.regs.f = bit(4, cpu.regs.e, cpu.regs.f);
+}
+
+/// BIT 4, H
+pub fn op_cb64(cpu: &mut Cpu) {
+    cpu.regs.f = bit(4, cpu.regs.h, cpu.regs.f);
+}
+
+/// BIT 4, L
+pub fn op_cb65(cpu: &mut Cpu) {
+    cpu.regs.f = bit(4, cpu.regs.l, cpu.regs.f);
+}
+
+/// BIT 4, (HL)
+pub fn op_cb66(cpu: &mut Cpu) {
+    let addr = cpu.regs.hl();
+    let byte = cpu.read_byte(addr);
+    cpu.regs.f = bit(4, byte, cpu.regs.f);
+}
+
+/// BIT 4, A
+pub fn op_cb67(cpu: &mut Cpu) {
+    cpu.regs.f = bit(4, cpu.regs.a, cpu.regs.f);
+}
+
+/// BIT 5, B
+pub fn op_cb68(cpu: &mut Cpu) {
+    cpu.regs.f = bit(5, cpu.regs.b, cpu.regs.f);
+}
+
+/// BIT 5, C
+pub fn op_cb69(cpu: &mut Cpu) {
+    cpu.regs.f = bit(5, cpu.regs.c, cpu.regs.f);
+}
+
+/// BIT 5, D
+pub fn op_cb6a(cpu: &mut Cpu) {
+    cpu.regs.f = bit(5, cpu.regs.d, cpu.regs.f);
+}
+
+/// BIT 5, E
+pub fn op_cb6b(cpu: &mut Cpu) {
+    cpu.regs.f = bit(5, cpu.regs.e, cpu.regs.f);
+}
+
+/// BIT 5, H
+pub fn op_cb6c(cpu: &mut Cpu) {
+    cpu.regs.f = bit(5, cpu.regs.h, cpu.regs.f);
+}
+
+/// BIT 5, L
+pub fn op_cb6d(cpu: &mut Cpu) {
+    cpu.regs.f = bit(5, cpu.regs.l, cpu.regs.f);
+}
+
+/// BIT 5, (HL)
+pub fn op_cb6e(cpu: &mut Cpu) {
+    let addr = cpu.regs.hl();
+    let byte = cpu.read_byte(addr);
+    cpu.regs.f = bit(5, byte, cpu.regs.f);
+}
+
+/// BIT 5, A
+pub fn op_cb6f(cpu: &mut Cpu) {
+    cpu.regs.f = bit(5, cpu.regs.a, cpu.regs.f);
+}
+
+/// BIT 6, B
+pub fn op_cb70(cpu: &mut Cpu) {
+    cpu.regs.f = bit(6, cpu.regs.b, cpu.regs.f);
+}
+
+/// BIT 6, C
+pub fn op_cb71(cpu: &mut Cpu) {
+    cpu.regs.f = bit(6, cpu.regs.c, cpu.regs.f);
+}
+
+/// BIT 6, D
+pub fn op_cb72(cpu: &mut Cpu) {
+    cpu.regs.f = bit(6, cpu.regs.d, cpu.regs.f);
+}
+
+/// BIT 6, E
+pub fn op_cb73(cpu: &mut Cpu) {
+    cpu.regs.f = bit(6, cpu.regs.e, cpu.regs.f);
+}
+
+/// BIT 6, H
+pub fn op_cb74(cpu: &mut Cpu) {
+    cpu.regs.f = bit(6, cpu.regs.h, cpu.regs.f);
+}
+
+/// BIT 6, L
+pub fn op_cb75(cpu: &mut Cpu) {
+    cpu.regs.f = bit(6, cpu.regs.l, cpu.regs.f);
+}
+
+/// BIT 6, (HL)
+pub fn op_cb76(cpu: &mut Cpu) {
+    let addr = cpu.regs.hl();
+    let byte = cpu.read_byte(addr);
+    cpu.regs.f = bit(6, byte, cpu.regs.f);
+}
+
+/// BIT 6, A
+pub fn op_cb77(cpu: &mut Cpu) {
+    cpu.regs.f = bit(6, cpu.regs.a, cpu.regs.f);
+}
+
+/// BIT 7, B
+pub fn op_cb78(cpu: &mut Cpu) {
+    cpu.regs.f = bit(7, cpu.regs.b, cpu.regs.f);
+}
+
+/// BIT 7, C
+pub fn op_cb79(cpu: &mut Cpu) {
+    cpu.regs.f = bit(7, cpu.regs.c, cpu.regs.f);
+}
+
+/// BIT 7, D
+pub fn op_cb7a(cpu: &mut Cpu) {
+    cpu.regs.f = bit(7, cpu.regs.d, cpu.regs.f);
+}
+
+/// BIT 7, E
+pub fn op_cb7b(cpu: &mut Cpu) {
+    cpu.regs.f = bit(7, cpu.regs.e, cpu.regs.f);
+}
+
+/// BIT 7, L
+pub fn op_cb7d(cpu: &mut Cpu) {
+    cpu.regs.f = bit(7, cpu.regs.l, cpu.regs.f);
+}
+
+/// BIT 7, (HL)
+pub fn op_cb7e(cpu: &mut Cpu) {
+    let addr = cpu.regs.hl();
+    let byte = cpu.read_byte(addr);
+    cpu.regs.f = bit(7, byte, cpu.regs.f);
+}
+
+/// BIT 7, A
+pub fn op_cb7f(cpu: &mut Cpu) {
+    cpu.regs.f = bit(7, cpu.regs.a, cpu.regs.f);
+}
+
+/// RES 0, B
+pub fn op_cb80(cpu: &mut Cpu) {
+    cpu.regs.b = reset_bit(0, cpu.regs.b);
+}
+
+/// RES 0, C
+pub fn op_cb81(cpu: &mut Cpu) {
+    cpu.regs.c = reset_bit(0, cpu.regs.c);
+}
+
+/// RES 0, D
+pub fn op_cb82(cpu: &mut Cpu) {
+    cpu.regs.d = reset_bit(0, cpu.regs.d);
+}
+
+/// RES 0, E
+pub fn op_cb83(cpu: &mut Cpu) {
+    cpu.regs.e = reset_bit(0, cpu.regs.e);
+}
+
+/// RES 0, H
+pub fn op_cb84(cpu: &mut Cpu) {
+    cpu.regs.h = reset_bit(0, cpu.regs.h);
+}
+
+/// RES 0, L
+pub fn op_cb85(cpu: &mut Cpu) {
+    cpu.regs.l = reset_bit(0, cpu.regs.l);
+}
+
+/// RES 0, (HL)
+pub fn op_cb86(cpu: &mut Cpu) {
+    let addr = cpu.regs.hl();
+    let byte = cpu.read_byte(addr);
+    cpu.write_byte(addr, reset_bit(0, byte));
+}
+
+/// RES 0, A
+pub fn op_cb87(cpu: &mut Cpu) {
+    cpu.regs.a = reset_bit(0, cpu.regs.a);
+}
+
+/// RES 1, B
+pub fn op_cb88(cpu: &mut Cpu) {
+    cpu.regs.b = reset_bit(1, cpu.regs.b);
+}
+
+/// RES 1, C
+pub fn op_cb89(cpu: &mut Cpu) {
+    cpu.regs.c = reset_bit(1, cpu.regs.c);
+}
+
+/// RES 1, D
+pub fn op_cb8a(cpu: &mut Cpu) {
+    cpu.regs.d = reset_bit(1, cpu.regs.d);
+}
+
+/// RES 1, E
+pub fn op_cb8b(cpu: &mut Cpu) {
+    cpu.regs.e = reset_bit(1, cpu.regs.e);
+}
+
+/// RES 1, H
+pub fn op_cb8c(cpu: &mut Cpu) {
+    cpu.regs.h = reset_bit(1, cpu.regs.h);
+}
+
+/// RES 1, L
+pub fn op_cb8d(cpu: &mut Cpu) {
+    cpu.regs.l = reset_bit(1, cpu.regs.l);
+}
+
+/// RES 1, (HL)
+pub fn op_cb8e(cpu: &mut Cpu) {
+    let addr = cpu.regs.hl();
+    let byte = cpu.read_byte(addr);
+    cpu.write_byte(addr, reset_bit(1, byte));
+}
+
+/// RES 1, A
+pub fn op_cb8f(cpu: &mut Cpu) {
+    cpu.regs.a = reset_bit(1, cpu.regs.a);
+}
+
+/// RES 2, B
+pub fn op_cb90(cpu: &mut Cpu) {
+    cpu.regs.b = reset_bit(2, cpu.regs.b);
+}
+
+/// RES 2, C
+pub fn op_cb91(cpu: &mut Cpu) {
+    cpu.regs.c = reset_bit(2, cpu.regs.c);
+}
+
+/// RES 2, D
+pub fn op_cb92(cpu: &mut Cpu) {
+    cpu.regs.d = reset_bit(2, cpu.regs.d);
+}
+
+/// RES 2, E
+pub fn op_cb93(cpu: &mut Cpu) {
+    cpu.regs.e = reset_bit(2, cpu.regs.e);
+}
+
+/// RES 2, H
+pub fn op_cb94(cpu: &mut Cpu) {
+    cpu.regs.h = reset_bit(2, cpu.regs.h);
+}
+
+/// RES 2, L
+pub fn op_cb95(cpu: &mut Cpu) {
+    cpu.regs.l = reset_bit(2, cpu.regs.l);
+}
+
+/// RES 2, (HL)
+pub fn op_cb96(cpu: &mut Cpu) {
+    let addr = cpu.regs.hl();
+    let byte = cpu.read_byte(addr);
+    cpu.write_byte(addr, reset_bit(2, byte));
+}
+
+/// RES 2, A
+pub fn op_cb97(cpu: &mut Cpu) {
+    cpu.regs.a = reset_bit(2, cpu.regs.a);
+}
+
+/// RES 3, B
+pub fn op_cb98(cpu: &mut Cpu) {
+    cpu.regs.b = reset_bit(3, cpu.regs.b);
+}
+
+/// RES 3, C
+pub fn op_cb99(cpu: &mut Cpu) {
+    cpu.regs.c = reset_bit(3, cpu.regs.c);
+}
+
+/// RES 3, D
+pub fn op_cb9a(cpu: &mut Cpu) {
+    cpu.regs.d = reset_bit(3, cpu.regs.d);
+}
+
+/// RES 3, E
+pub fn op_cb9b(cpu: &mut Cpu) {
+    cpu.regs.e = reset_bit(3, cpu.regs.e);
+}
+
+/// RES 3, H
+pub fn op_cb9c(cpu: &mut Cpu) {
+    cpu.regs.h = reset_bit(3, cpu.regs.h);
+}
+
+/// RES 3, L
+pub fn op_cb9d(cpu: &mut Cpu) {
+    cpu.regs.l = reset_bit(3, cpu.regs.l);
+}
+
+/// RES 3, (HL)
+pub fn op_cb9e(cpu: &mut Cpu) {
+    let addr = cpu.regs.hl();
+    let byte = cpu.read_byte(addr);
+    cpu.write_byte(addr, reset_bit(3, byte));
+}
+
+/// RES 3, A
+pub fn op_cb9f(cpu: &mut Cpu) {
+    cpu.regs.a = reset_bit(3, cpu.regs.a);
+}
+
+/// RES 4, B
+pub fn op_cba0(cpu: &mut Cpu) {
+    cpu.regs.b = reset_bit(4, cpu.regs.b);
+}
+
+/// RES 4, C
+pub fn op_cba1(cpu: &mut Cpu) {
+    cpu.regs.c = reset_bit(4, cpu.regs.c);
+}
+
+/// RES 4, D
+pub fn op_cba2(cpu: &mut Cpu) {
+    cpu.regs.d = reset_bit(4, cpu.regs.d);
+}
+
+/// RES 4, E
+pub fn op_cba3(cpu: &mut Cpu) {
+    cpu.regs.e = reset_bit(4, cpu.regs.e);
+}
+
+/// RES 4, H
+pub fn op_cba4(cpu: &mut Cpu) {
+    cpu.regs.h = reset_bit(4, cpu.regs.h);
+}
+
+/// RES 4, L
+pub fn op_cba5(cpu: &mut Cpu) {
+    cpu.regs.l = reset_bit(4, cpu.regs.l);
+}
+
+/// RES 4, (HL)
+pub fn op_cba6(cpu: &mut Cpu) {
+    let addr = cpu.regs.hl();
+    let byte = cpu.read_byte(addr);
+    cpu.write_byte(addr, reset_bit(4, byte));
+}
+
+/// RES 4, A
+pub fn op_cba7(cpu: &mut Cpu) {
+    cpu.regs.a = reset_bit(4, cpu.regs.a);
+}
+
+/// RES 5, B
+pub fn op_cba8(cpu: &mut Cpu) {
+    cpu.regs.b = reset_bit(5, cpu.regs.b);
+}
+
+/// RES 5, C
+pub fn op_cba9(cpu: &mut Cpu) {
+    cpu.regs.c = reset_bit(5, cpu.regs.c);
+}
+
+/// RES 5, D
+pub fn op_cbaa(cpu: &mut Cpu) {
+    cpu.regs.d = reset_bit(5, cpu.regs.d);
+}
+
+/// RES 5, E
+pub fn op_cbab(cpu: &mut Cpu) {
+    cpu.regs.e = reset_bit(5, cpu.regs.e);
+}
+
+/// RES 5, H
+pub fn op_cbac(cpu: &mut Cpu) {
+    cpu.regs.h = reset_bit(5, cpu.regs.h);
+}
+
+/// RES 5, L
+pub fn op_cbad(cpu: &mut Cpu) {
+    cpu.regs.l = reset_bit(5, cpu.regs.l);
+}
+
+/// RES 5, (HL)
+pub fn op_cbae(cpu: &mut Cpu) {
+    let addr = cpu.regs.hl();
+    let byte = cpu.read_byte(addr);
+    cpu.write_byte(addr, reset_bit(5, byte));
+}
+
+/// RES 5, A
+pub fn op_cbaf(cpu: &mut Cpu) {
+    cpu.regs.a = reset_bit(5, cpu.regs.a);
+}
+
+/// RES 6, B
+pub fn op_cbb0(cpu: &mut Cpu) {
+    cpu.regs.b = reset_bit(6, cpu.regs.b);
+}
+
+/// RES 6, C
+pub fn op_cbb1(cpu: &mut Cpu) {
+    cpu.regs.c = reset_bit(6, cpu.regs.c);
+}
+
+/// RES 6, D
+pub fn op_cbb2(cpu: &mut Cpu) {
+    cpu.regs.d = reset_bit(6, cpu.regs.d);
+}
+
+/// RES 6, E
+pub fn op_cbb3(cpu: &mut Cpu) {
+    cpu.regs.e = reset_bit(6, cpu.regs.e);
+}
+
+/// RES 6, H
+pub fn op_cbb4(cpu: &mut Cpu) {
+    cpu.regs.h = reset_bit(6, cpu.regs.h);
+}
+
+/// RES 6, L
+pub fn op_cbb5(cpu: &mut Cpu) {
+    cpu.regs.l = reset_bit(6, cpu.regs.l);
+}
+
+/// RES 6, (HL)
+pub fn op_cbb6(cpu: &mut Cpu) {
+    let addr = cpu.regs.hl();
+    let byte = cpu.read_byte(addr);
+    cpu.write_byte(addr, reset_bit(6, byte));
+}
+
+/// RES 6, A
+pub fn op_cbb7(cpu: &mut Cpu) {
+    cpu.regs.a = reset_bit(6, cpu.regs.a);
+}
+
+/// RES 7, B
+pub fn op_cbb8(cpu: &mut Cpu) {
+    cpu.regs.b = reset_bit(7, cpu.regs.b);
+}
+
+/// RES 7, C
+pub fn op_cbb9(cpu: &mut Cpu) {
+    cpu.regs.c = reset_bit(7, cpu.regs.c);
+}
+
+/// RES 7, D
+pub fn op_cbba(cpu: &mut Cpu) {
+    cpu.regs.d = reset_bit(7, cpu.regs.d);
+}
+
+/// RES 7, E
+pub fn op_cbbb(cpu: &mut Cpu) {
+    cpu.regs.e = reset_bit(7, cpu.regs.e);
+}
+
+/// RES 7, H
+pub fn op_cbbc(cpu: &mut Cpu) {
+    cpu.regs.h = reset_bit(7, cpu.regs.h);
+}
+
+/// RES 7, L
+pub fn op_cbbd(cpu: &mut Cpu) {
+    cpu.regs.l = reset_bit(7, cpu.regs.l);
+}
+
+/// RES 7, (HL)
+pub fn op_cbbe(cpu: &mut Cpu) {
+    let addr = cpu.regs.hl();
+    let byte = cpu.read_byte(addr);
+    cpu.write_byte(addr, reset_bit(7, byte));
+}
+
+/// RES 7, A
+pub fn op_cbbf(cpu: &mut Cpu) {
+    cpu.regs.a = reset_bit(7, cpu.regs.a);
+}
+
+/// SET 0, B
+pub fn op_cbc0(cpu: &mut Cpu) {
+    cpu.regs.b = set_bit(0, cpu.regs.b);
+}
+
+/// SET 0, C
+pub fn op_cbc1(cpu: &mut Cpu) {
+    cpu.regs.c = set_bit(0, cpu.regs.c);
+}
+
+/// SET 0, D
+pub fn op_cbc2(cpu: &mut Cpu) {
+    cpu.regs.d = set_bit(0, cpu.regs.d);
+}
+
+/// SET 0, E
+pub fn op_cbc3(cpu: &mut Cpu) {
+    cpu.regs.e = set_bit(0, cpu.regs.e);
+}
+
+/// SET 0, H
+pub fn op_cbc4(cpu: &mut Cpu) {
+    cpu.regs.h = set_bit(0, cpu.regs.h);
+}
+
+/// SET 0, L
+pub fn op_cbc5(cpu: &mut Cpu) {
+    cpu.regs.l = set_bit(0, cpu.regs.l);
+}
+
+/// SET 0, (HL)
+pub fn op_cbc6(cpu: &mut Cpu) {
+    let addr = cpu.regs.hl();
+    let byte = cpu.read_byte(addr);
+    cpu.write_byte(addr, set_bit(0, byte));
+}
+
+/// SET 0, A
+pub fn op_cbc7(cpu: &mut Cpu) {
+    cpu.regs.a = set_bit(0, cpu.regs.a);
+}
+
+/// SET 1, B
+pub fn op_cbc8(cpu: &mut Cpu) {
+    cpu.regs.b = set_bit(1, cpu.regs.b);
+}
+
+/// SET 1, C
+pub fn op_cbc9(cpu: &mut Cpu) {
+    cpu.regs.c = set_bit(1, cpu.regs.c);
+}
+
+/// SET 1, D
+pub fn op_cbca(cpu: &mut Cpu) {
+    cpu.regs.d = set_bit(1, cpu.regs.d);
+}
+
+/// SET 1, E
+pub fn op_cbcb(cpu: &mut Cpu) {
+    cpu.regs.e = set_bit(1, cpu.regs.e);
+}
+
+/// SET 1, H
+pub fn op_cbcc(cpu: &mut Cpu) {
+    cpu.regs.h = set_bit(1, cpu.regs.h);
+}
+
+/// SET 1, L
+pub fn op_cbcd(cpu: &mut Cpu) {
+    cpu.regs.l = set_bit(1, cpu.regs.l);
+}
+
+/// SET 1, (HL)
+pub fn op_cbce(cpu: &mut Cpu) {
+    let addr = cpu.regs.hl();
+    let byte = cpu.read_byte(addr);
+    cpu.write_byte(addr, set_bit(1, byte));
+}
+
+/// SET 1, A
+pub fn op_cbcf(cpu: &mut Cpu) {
+    cpu.regs.a = set_bit(1, cpu.regs.a);
+}
+
+/// SET 2, B
+pub fn op_cbd0(cpu: &mut Cpu) {
+    cpu.regs.b = set_bit(2, cpu.regs.b);
+}
+
+/// SET 2, C
+pub fn op_cbd1(cpu: &mut Cpu) {
+    cpu.regs.c = set_bit(2, cpu.regs.c);
+}
+
+/// SET 2, D
+pub fn op_cbd2(cpu: &mut Cpu) {
+    cpu.regs.d = set_bit(2, cpu.regs.d);
+}
+
+/// SET 2, E
+pub fn op_cbd3(cpu: &mut Cpu) {
+    cpu.regs.e = set_bit(2, cpu.regs.e);
+}
+
+/// SET 2, H
+pub fn op_cbd4(cpu: &mut Cpu) {
+    cpu.regs.h = set_bit(2, cpu.regs.h);
+}
+
+/// SET 2, L
+pub fn op_cbd5(cpu: &mut Cpu) {
+    cpu.regs.l = set_bit(2, cpu.regs.l);
+}
+
+/// SET 2, (HL)
+pub fn op_cbd6(cpu: &mut Cpu) {
+    let addr = cpu.regs.hl();
+    let byte = cpu.read_byte(addr);
+    cpu.write_byte(addr, set_bit(2, byte));
+}
+
+/// SET 2, A
+pub fn op_cbd7(cpu: &mut Cpu) {
+    cpu.regs.a = set_bit(2, cpu.regs.a);
+}
+
+/// SET 3, B
+pub fn op_cbd8(cpu: &mut Cpu) {
+    cpu.regs.b = set_bit(3, cpu.regs.b);
+}
+
+/// SET 3, C
+pub fn op_cbd9(cpu: &mut Cpu) {
+    cpu.regs.c = set_bit(3, cpu.regs.c);
+}
+
+/// SET 3, D
+pub fn op_cbda(cpu: &mut Cpu) {
+    cpu.regs.d = set_bit(3, cpu.regs.d);
+}
+
+/// SET 3, E
+pub fn op_cbdb(cpu: &mut Cpu) {
+    cpu.regs.e = set_bit(3, cpu.regs.e);
+}
+
+/// SET 3, H
+pub fn op_cbdc(cpu: &mut Cpu) {
+    cpu.regs.h = set_bit(3, cpu.regs.h);
+}
+
+/// SET 3, L
+pub fn op_cbdd(cpu: &mut Cpu) {
+    cpu.regs.l = set_bit(3, cpu.regs.l);
+}
+
+/// SET 3, (HL)
+pub fn op_cbde(cpu: &mut Cpu) {
+    let addr = cpu.regs.hl();
+    let byte = cpu.read_byte(addr);
+    cpu.write_byte(addr, set_bit(3, byte));
+}
+
+/// SET 3, A
+pub fn op_cbdf(cpu: &mut Cpu) {
+    cpu.regs.a = set_bit(3, cpu.regs.a);
+}
+
+/// SET 4, B
+pub fn op_cbe0(cpu: &mut Cpu) {
+    cpu.regs.b = set_bit(4, cpu.regs.b);
+}
+
+/// SET 4, C
+pub fn op_cbe1(cpu: &mut Cpu) {
+    cpu.regs.c = set_bit(4, cpu.regs.c);
+}
+
+/// SET 4, D
+pub fn op_cbe2(cpu: &mut Cpu) {
+    cpu.regs.d = set_bit(4, cpu.regs.d);
+}
+
+/// SET 4, E
+pub fn op_cbe3(cpu: &mut Cpu) {
+    cpu.regs.e = set_bit(4, cpu.regs.e);
+}
+
+/// SET 4, H
+pub fn op_cbe4(cpu: &mut Cpu) {
+    cpu.regs.h = set_bit(4, cpu.regs.h);
+}
+
+/// SET 4, L
+pub fn op_cbe5(cpu: &mut Cpu) {
+    cpu.regs.l = set_bit(4, cpu.regs.l);
+}
+
+/// SET 4, (HL)
+pub fn op_cbe6(cpu: &mut Cpu) {
+    let addr = cpu.regs.hl();
+    let byte = cpu.read_byte(addr);
+    cpu.write_byte(addr, set_bit(4, byte));
+}
+
+/// SET 4, A
+pub fn op_cbe7(cpu: &mut Cpu) {
+    cpu.regs.a = set_bit(4, cpu.regs.a);
+}
+
+/// SET 5, B
+pub fn op_cbe8(cpu: &mut Cpu) {
+    cpu.regs.b = set_bit(5, cpu.regs.b);
+}
+
+/// SET 5, C
+pub fn op_cbe9(cpu: &mut Cpu) {
+    cpu.regs.c = set_bit(5, cpu.regs.c);
+}
+
+/// SET 5, D
+pub fn op_cbea(cpu: &mut Cpu) {
+    cpu.regs.d = set_bit(5, cpu.regs.d);
+}
+
+/// SET 5, E
+pub fn op_cbeb(cpu: &mut Cpu) {
+    cpu.regs.e = set_bit(5, cpu.regs.e);
+}
+
+/// SET 5, H
+pub fn op_cbec(cpu: &mut Cpu) {
+    cpu.regs.h = set_bit(5, cpu.regs.h);
+}
+
+/// SET 5, L
+pub fn op_cbed(cpu: &mut Cpu) {
+    cpu.regs.l = set_bit(5, cpu.regs.l);
+}
+
+/// SET 5, (HL)
+pub fn op_cbee(cpu: &mut Cpu) {
+    let addr = cpu.regs.hl();
+    let byte = cpu.read_byte(addr);
+    cpu.write_byte(addr, set_bit(5, byte));
+}
+
+/// SET 5, A
+pub fn op_cbef(cpu: &mut Cpu) {
+    cpu.regs.a = set_bit(5, cpu.regs.a);
+}
+
+/// SET 6, B
+pub fn op_cbf0(cpu: &mut Cpu) {
+    cpu.regs.b = set_bit(6, cpu.regs.b);
+}
+
+/// SET 6, C
+pub fn op_cbf1(cpu: &mut Cpu) {
+    cpu.regs.c = set_bit(6, cpu.regs.c);
+}
+
+/// SET 6, D
+pub fn op_cbf2(cpu: &mut Cpu) {
+    cpu.regs.d = set_bit(6, cpu.regs.d);
+}
+
+/// SET 6, E
+pub fn op_cbf3(cpu: &mut Cpu) {
+    cpu.regs.e = set_bit(6, cpu.regs.e);
+}
+
+/// SET 6, H
+pub fn op_cbf4(cpu: &mut Cpu) {
+    cpu.regs.h = set_bit(6, cpu.regs.h);
+}
+
+/// SET 6, L
+pub fn op_cbf5(cpu: &mut Cpu) {
+    cpu.regs.l = set_bit(6, cpu.regs.l);
+}
+
+/// SET 6, (HL)
+pub fn op_cbf6(cpu: &mut Cpu) {
+    let addr = cpu.regs.hl();
+    let byte = cpu.read_byte(addr);
+    cpu.write_byte(addr, set_bit(6, byte));
+}
+
+/// SET 6, A
+pub fn op_cbf7(cpu: &mut Cpu) {
+    cpu.regs.a = set_bit(6, cpu.regs.a);
+}
+
+/// SET 7, B
+pub fn op_cbf8(cpu: &mut Cpu) {
+    cpu.regs.b = set_bit(7, cpu.regs.b);
+}
+
+/// SET 7, C
+pub fn op_cbf9(cpu: &mut Cpu) {
+    cpu.regs.c = set_bit(7, cpu.regs.c);
+}
+
+/// SET 7, D
+pub fn op_cbfa(cpu: &mut Cpu) {
+    cpu.regs.d = set_bit(7, cpu.regs.d);
+}
+
+/// SET 7, E
+pub fn op_cbfb(cpu: &mut Cpu) {
+    cpu.regs.e = set_bit(7, cpu.regs.e);
+}
+
+/// SET 7, H
+pub fn op_cbfc(cpu: &mut Cpu) {
+    cpu.regs.h = set_bit(7, cpu.regs.h);
+}
+
+/// SET 7, L
+pub fn op_cbfd(cpu: &mut Cpu) {
+    cpu.regs.l = set_bit(7, cpu.regs.l);
+}
+
+/// SET 7, (HL)
+pub fn op_cbfe(cpu: &mut Cpu) {
+    let addr = cpu.regs.hl();
+    let byte = cpu.read_byte(addr);
+    cpu.write_byte(addr, set_bit(7, byte));
+}
+
+/// SET 7, A
+pub fn op_cbff(cpu: &mut Cpu) {
+    cpu.regs.a = set_bit(7, cpu.regs.a);
+}
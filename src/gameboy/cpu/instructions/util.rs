@@ -34,15 +34,12 @@ pub fn rotate_left(reg: u8, mut flags: u8) -> (u8, u8) {
     let new_carry = (reg & 0b1000_0000) >> 7;
     let new_reg = (reg << 1) | new_carry;
 
-    //// Zero Flag: set if new register value is 0
-    //if new_reg == 0 {
-    //    flags |= 0b1000_0000;
-    //} else {
-    //    flags &= 0b0111_1111;
-    //}
-
-    // Zero Flag: unset
-    flags &= 0b0111_1111;
+    // Zero Flag: set if new register value is 0
+    if new_reg == 0 {
+        flags |= 0b1000_0000;
+    } else {
+        flags &= 0b0111_1111;
+    }
 
     // Subtract Flag: cleared as this is a rotate operation
     flags &= 0b1011_1111;
@@ -267,6 +264,32 @@ pub fn add8(reg: u8, byte: u8) -> (u8, u8) {
     (result, flags)
 }
 
+/// Utility function for adding a byte plus the carry flag to a register.
+pub fn adc(reg: u8, byte: u8, flags: u8) -> (u8, u8) {
+    let carry = (flags & 0b0001_0000) >> 4;
+    let result = reg.wrapping_add(byte).wrapping_add(carry);
+
+    // Subtract Flag: cleared as this is an addition operation
+    let mut flags = 0b0000_0000;
+
+    // Zero Flag: set if the result is 0
+    if result == 0 {
+        flags |= 0b1000_0000;
+    }
+
+    // Half-Carry Flag: set if there's a carry from bit 3
+    if (reg & 0xf) + (byte & 0xf) + carry > 0xf {
+        flags |= 0b0010_0000;
+    }
+
+    // Carry Flag: set if there's a carry from bit 7
+    if reg as u16 + byte as u16 + carry as u16 > 0xff {
+        flags |= 0b0001_0000;
+    }
+
+    (result, flags)
+}
+
 /// Utility function for adding a register and a word.
 pub fn add16(reg: u16, word: u16, mut flags: u8) -> (u16, u8) {
     let result = reg.wrapping_add(word);
@@ -345,7 +368,7 @@ pub fn sbc(reg: u8, byte: u8, mut flags: u8) -> (u8, u8) {
     }
 
     // Carry Flag: set if there's a borrow from bit 8
-    if result > reg {
+    if (reg as u16) < byte as u16 + carry as u16 {
         flags |= 0b0001_0000;
     } else {
         flags &= 0b1110_1111;
@@ -373,3 +396,150 @@ pub fn bit(bit: u8, reg: u8, mut flags: u8) -> u8 {
 
     flags
 }
+
+/// Utility function for AND'ing two bytes.
+/// Returns the resulting value and flags.
+pub fn and(reg: u8, byte: u8) -> (u8, u8) {
+    let result = reg & byte;
+
+    // Half-Carry Flag: set, as this is an AND operation
+    let mut flags = 0b0010_0000;
+
+    // Zero Flag: set if result is 0
+    if result == 0 {
+        flags |= 0b1000_0000;
+    }
+
+    // Subtract and Carry Flags: cleared
+
+    (result, flags)
+}
+
+/// Utility function for OR'ing two bytes.
+/// Returns the resulting value and flags.
+pub fn or(reg: u8, byte: u8) -> (u8, u8) {
+    let result = reg | byte;
+
+    // Zero Flag: set if result is 0, every other flag is cleared
+    let flags = if result == 0 { 0b1000_0000 } else { 0 };
+
+    (result, flags)
+}
+
+/// Returns the flags of a shift or swap producing the given value, with the
+/// given bit shifted out into the carry.
+fn shift_flags(result: u8, carry: bool) -> u8 {
+    let mut flags = 0;
+
+    // Zero Flag: set if result is 0
+    if result == 0 {
+        flags |= 0b1000_0000;
+    }
+
+    // Subtract and Half-Carry Flags: cleared
+
+    // Carry Flag: set to the bit shifted out
+    if carry {
+        flags |= 0b0001_0000;
+    }
+
+    flags
+}
+
+/// Utility function for shifting left into the carry, bit 0 becoming 0.
+pub fn shift_left_arithmetic(reg: u8) -> (u8, u8) {
+    let result = reg << 1;
+    (result, shift_flags(result, reg & 0b1000_0000 != 0))
+}
+
+/// Utility function for shifting right into the carry, bit 7 keeping its value.
+pub fn shift_right_arithmetic(reg: u8) -> (u8, u8) {
+    let result = (reg >> 1) | (reg & 0b1000_0000);
+    (result, shift_flags(result, reg & 0b0000_0001 != 0))
+}
+
+/// Utility function for shifting right into the carry, bit 7 becoming 0.
+pub fn shift_right_logical(reg: u8) -> (u8, u8) {
+    let result = reg >> 1;
+    (result, shift_flags(result, reg & 0b0000_0001 != 0))
+}
+
+/// Utility function for swapping the nibbles of a register.
+pub fn swap(reg: u8) -> (u8, u8) {
+    let result = reg.rotate_left(4);
+    (result, shift_flags(result, false))
+}
+
+/// Utility function for clearing a specified bit of a register.
+pub fn reset_bit(bit: u8, reg: u8) -> u8 {
+    reg & !(1 << bit)
+}
+
+/// Utility function for setting a specified bit of a register.
+pub fn set_bit(bit: u8, reg: u8) -> u8 {
+    reg | (1 << bit)
+}
+
+/// Utility function for adding a signed byte to the stack pointer.
+/// The flags come from the addition of the low bytes, as if it was unsigned.
+pub fn add_signed(sp: u16, offset: u8) -> (u16, u8) {
+    let result = sp.wrapping_add(offset as i8 as u16);
+
+    // Zero and Subtract Flags: cleared
+    let mut flags = 0b0000_0000;
+
+    // Half-Carry Flag: set if there's a carry from bit 3
+    if (sp & 0xf) + (offset as u16 & 0xf) > 0xf {
+        flags |= 0b0010_0000;
+    }
+
+    // Carry Flag: set if there's a carry from bit 7
+    if (sp & 0xff) + offset as u16 > 0xff {
+        flags |= 0b0001_0000;
+    }
+
+    (result, flags)
+}
+
+/// Utility function for adjusting the result of a BCD addition or subtraction
+/// in `A`, according to the flags it left.
+pub fn decimal_adjust(reg: u8, mut flags: u8) -> (u8, u8) {
+    let subtract = flags & 0b0100_0000 != 0;
+    let half_carry = flags & 0b0010_0000 != 0;
+    let mut carry = flags & 0b0001_0000 != 0;
+
+    let mut correction = 0;
+    if half_carry || (!subtract && reg & 0xf > 0x9) {
+        correction |= 0x06;
+    }
+    if carry || (!subtract && reg > 0x99) {
+        correction |= 0x60;
+        carry = true;
+    }
+    let result = if subtract {
+        reg.wrapping_sub(correction)
+    } else {
+        reg.wrapping_add(correction)
+    };
+
+    // Zero Flag: set if result is 0
+    if result == 0 {
+        flags |= 0b1000_0000;
+    } else {
+        flags &= 0b0111_1111;
+    }
+
+    // Half-Carry Flag: cleared
+    flags &= 0b1101_1111;
+
+    // Carry Flag: set if the adjustment went past 0x99
+    if carry {
+        flags |= 0b0001_0000;
+    } else {
+        flags &= 0b1110_1111;
+    }
+
+    // Subtract Flag: unchanged
+
+    (result, flags)
+}
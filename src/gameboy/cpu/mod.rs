@@ -1,12 +1,17 @@
-use std::sync::{Arc, RwLock};
-
+use super::interrupts;
 use super::memory::Memory;
+use super::Model;
 
 mod instructions;
 use instructions::*;
 
 mod registers;
-use registers::Registers;
+pub use registers::Registers;
+
+/// The number of T-cycles of a memory access.
+const CYCLES_PER_ACCESS: u32 = 4;
+/// The number of T-cycles taken to call an interrupt handler.
+const INTERRUPT_CYCLES: u32 = 20;
 
 /// Represents the CPU of the GameBoy.
 #[derive(Debug, Clone)]
@@ -19,17 +24,32 @@ pub struct Cpu {
     pub regs: Registers,
     /// The interrupt master enable flag.
     pub ime: bool,
+    /// Whether `EI` was just executed, enabling interrupts after the next instruction.
+    pub ime_pending: bool,
     /// The halt flag.
     pub halt: bool,
-    /// The memory.
-    mem: Arc<RwLock<Memory>>,
-    /// The number of cycles that have elapsed.
-    cycles: u32,
+    /// Whether the next opcode fetch fails to increment the program counter,
+    /// after a `HALT` that didn't halt.
+    pub halt_bug: bool,
+    /// Whether `STOP` stopped the CPU until a button is pressed.
+    pub stopped: bool,
+    /// The memory, which the CPU owns as its bus.
+    pub mem: Memory,
+    /// The number of T-cycles that have elapsed.
+    pub cycles: u64,
+    /// Whether the rest of the system is advanced on every memory access,
+    /// rather than after every instruction, so that accesses see it at the
+    /// cycle they happen on. Internal M-cycles that come before an access
+    /// are advanced through as well.
+    pub accurate_timing: bool,
+    /// The number of T-cycles the rest of the system was already advanced by
+    /// during the current step.
+    ticked: u32,
 }
 
 impl Cpu {
     /// Creates a new `Cpu` instance.
-    pub fn new(mem: Arc<RwLock<Memory>>) -> Self {
+    pub fn new(mem: Memory) -> Self {
         Self {
             pc: 0,
             sp: 0,
@@ -46,13 +66,48 @@ impl Cpu {
             mem,
             cycles: 0,
             ime: false,
+            ime_pending: false,
             halt: false,
+            halt_bug: false,
+            stopped: false,
+            accurate_timing: false,
+            ticked: 0,
         }
     }
 
+    /// Puts the registers into the state the boot ROM of the given model leaves them in.
+    pub fn reset_post_boot(&mut self, model: Model) {
+        self.regs = match model {
+            // The SGB boot ROM is not emulated, the DMG one runs in its place
+            Model::Dmg | Model::Sgb => Registers {
+                a: 0x01,
+                f: 0xb0,
+                b: 0x00,
+                c: 0x13,
+                d: 0x00,
+                e: 0xd8,
+                h: 0x01,
+                l: 0x4d,
+            },
+            Model::Cgb => Registers {
+                a: 0x11,
+                f: 0x80,
+                b: 0x00,
+                c: 0x00,
+                d: 0xff,
+                e: 0x56,
+                h: 0x00,
+                l: 0x0d,
+            },
+        };
+        self.sp = 0xfffe;
+        self.pc = 0x0100;
+    }
+
     pub fn run(&mut self) {
         loop {
-            self.step();
+            let cycles = self.step();
+            self.mem.tick(cycles);
         }
     }
 
@@ -112,88 +167,621 @@ impl Cpu {
         }
     }
 
+    /// Advances the rest of the system by the cycles of a memory access when
+    /// timing accurately, so that the access happens at the end of them.
+    fn tick_access(&mut self) {
+        if self.accurate_timing {
+            self.mem.tick(CYCLES_PER_ACCESS);
+            self.ticked += CYCLES_PER_ACCESS;
+        }
+    }
+
+    /// Spends an M-cycle without accessing the memory, as some instructions
+    /// do before their accesses.
+    pub fn internal_cycle(&mut self) {
+        self.tick_access();
+    }
+
+    /// Reads a byte from the memory.
+    pub fn read_byte(&mut self, addr: u16) -> u8 {
+        self.tick_access();
+        self.mem.read_byte(addr)
+    }
+
+    /// Writes a byte to the memory.
+    pub fn write_byte(&mut self, addr: u16, value: u8) {
+        self.tick_access();
+        self.mem.write_byte(addr, value);
+    }
+
     /// Returns a byte from the memory at the address of the program counter.
     pub fn next_byte(&mut self) -> u8 {
-        let byte = self.mem.try_read().unwrap().read_byte(self.pc);
+        let byte = self.read_byte(self.pc);
         self.pc += 1;
         byte
     }
 
     /// Returns a word from the memory at the address of the program counter.
     pub fn next_word(&mut self) -> u16 {
-        let word = self.mem.try_read().unwrap().read_word(self.pc);
-        self.pc += 2;
-        word
+        let low = self.next_byte();
+        let high = self.next_byte();
+        ((high as u16) << 8) | low as u16
     }
 
     /// Decodes an opcode into an instruction.
     fn decode(&mut self, opcode: u8, prev_pc: Option<u16>) -> Instruction {
         match opcode {
-            0x00 => Instruction::normal(op_00, prev_pc.unwrap(), opcode, "NOP"),
+            0x00 => Instruction::normal(op_00, prev_pc.unwrap(), opcode, 4, "NOP"),
+            0x10 => Instruction::normal(op_10, prev_pc.unwrap(), opcode, 4, "STOP"),
+            0x76 => Instruction::normal(op_76, prev_pc.unwrap(), opcode, 4, "HALT"),
+            0xf3 => Instruction::normal(op_f3, prev_pc.unwrap(), opcode, 4, "DI"),
+            0xfb => Instruction::normal(op_fb, prev_pc.unwrap(), opcode, 4, "EI"),
+
             // Arithmetic Instructions
-            0x04 => Instruction::normal(op_04, prev_pc.unwrap(), opcode, "INC B"),
-            0x05 => Instruction::normal(op_05, prev_pc.unwrap(), opcode, "DEC B"),
-            0x0c => Instruction::normal(op_0c, prev_pc.unwrap(), opcode, "INC C"),
-            0x0d => Instruction::normal(op_0d, prev_pc.unwrap(), opcode, "DEC C"),
-            0x13 => Instruction::normal(op_13, prev_pc.unwrap(), opcode, "INC DE"),
-            0x15 => Instruction::normal(op_15, prev_pc.unwrap(), opcode, "DEC D"),
-            0x19 => Instruction::normal(op_19, prev_pc.unwrap(), opcode, "ADD HL, DE"),
-            0x1c => Instruction::normal(op_1c, prev_pc.unwrap(), opcode, "INC E"),
-            0x1d => Instruction::normal(op_1d, prev_pc.unwrap(), opcode, "DEC E"),
-            0x23 => Instruction::normal(op_23, prev_pc.unwrap(), opcode, "INC HL"),
-            0x24 => Instruction::normal(op_24, prev_pc.unwrap(), opcode, "INC H"),
-            0x3c => Instruction::normal(op_3c, prev_pc.unwrap(), opcode, "INC A"),
-            0x3d => Instruction::normal(op_3d, prev_pc.unwrap(), opcode, "DEC A"),
-            0x86 => Instruction::normal(op_86, prev_pc.unwrap(), opcode, "ADD A, (HL)"),
-            0x90 => Instruction::normal(op_90, prev_pc.unwrap(), opcode, "SUB A, B"),
-            0x9f => Instruction::normal(op_9f, prev_pc.unwrap(), opcode, "SBC A, A"),
-            0xaf => Instruction::normal(op_af, prev_pc.unwrap(), opcode, "XOR A, A"),
-            0xbe => Instruction::normal(op_be, prev_pc.unwrap(), opcode, "CP A, (HL)"),
-            0xfe => Instruction::normal(op_fe, prev_pc.unwrap(), opcode, "CP A, u8"),
+            0x03 => Instruction::normal(op_03, prev_pc.unwrap(), opcode, 8, "INC BC"),
+            0x04 => Instruction::normal(op_04, prev_pc.unwrap(), opcode, 4, "INC B"),
+            0x05 => Instruction::normal(op_05, prev_pc.unwrap(), opcode, 4, "DEC B"),
+            0x09 => Instruction::normal(op_09, prev_pc.unwrap(), opcode, 8, "ADD HL, BC"),
+            0x0b => Instruction::normal(op_0b, prev_pc.unwrap(), opcode, 8, "DEC BC"),
+            0x0c => Instruction::normal(op_0c, prev_pc.unwrap(), opcode, 4, "INC C"),
+            0x0d => Instruction::normal(op_0d, prev_pc.unwrap(), opcode, 4, "DEC C"),
+            0x13 => Instruction::normal(op_13, prev_pc.unwrap(), opcode, 8, "INC DE"),
+            0x14 => Instruction::normal(op_14, prev_pc.unwrap(), opcode, 4, "INC D"),
+            0x15 => Instruction::normal(op_15, prev_pc.unwrap(), opcode, 4, "DEC D"),
+            0x19 => Instruction::normal(op_19, prev_pc.unwrap(), opcode, 8, "ADD HL, DE"),
+            0x1b => Instruction::normal(op_1b, prev_pc.unwrap(), opcode, 8, "DEC DE"),
+            0x1c => Instruction::normal(op_1c, prev_pc.unwrap(), opcode, 4, "INC E"),
+            0x1d => Instruction::normal(op_1d, prev_pc.unwrap(), opcode, 4, "DEC E"),
+            0x23 => Instruction::normal(op_23, prev_pc.unwrap(), opcode, 8, "INC HL"),
+            0x24 => Instruction::normal(op_24, prev_pc.unwrap(), opcode, 4, "INC H"),
+            0x25 => Instruction::normal(op_25, prev_pc.unwrap(), opcode, 4, "DEC H"),
+            0x27 => Instruction::normal(op_27, prev_pc.unwrap(), opcode, 4, "DAA"),
+            0x29 => Instruction::normal(op_29, prev_pc.unwrap(), opcode, 8, "ADD HL, HL"),
+            0x2b => Instruction::normal(op_2b, prev_pc.unwrap(), opcode, 8, "DEC HL"),
+            0x2c => Instruction::normal(op_2c, prev_pc.unwrap(), opcode, 4, "INC L"),
+            0x2d => Instruction::normal(op_2d, prev_pc.unwrap(), opcode, 4, "DEC L"),
+            0x2f => Instruction::normal(op_2f, prev_pc.unwrap(), opcode, 4, "CPL"),
+            0x33 => Instruction::normal(op_33, prev_pc.unwrap(), opcode, 8, "INC SP"),
+            0x34 => Instruction::normal(op_34, prev_pc.unwrap(), opcode, 12, "INC (HL)"),
+            0x35 => Instruction::normal(op_35, prev_pc.unwrap(), opcode, 12, "DEC (HL)"),
+            0x37 => Instruction::normal(op_37, prev_pc.unwrap(), opcode, 4, "SCF"),
+            0x39 => Instruction::normal(op_39, prev_pc.unwrap(), opcode, 8, "ADD HL, SP"),
+            0x3b => Instruction::normal(op_3b, prev_pc.unwrap(), opcode, 8, "DEC SP"),
+            0x3c => Instruction::normal(op_3c, prev_pc.unwrap(), opcode, 4, "INC A"),
+            0x3d => Instruction::normal(op_3d, prev_pc.unwrap(), opcode, 4, "DEC A"),
+            0x3f => Instruction::normal(op_3f, prev_pc.unwrap(), opcode, 4, "CCF"),
+            0x80 => Instruction::normal(op_80, prev_pc.unwrap(), opcode, 4, "ADD A, B"),
+            0x81 => Instruction::normal(op_81, prev_pc.unwrap(), opcode, 4, "ADD A, C"),
+            0x82 => Instruction::normal(op_82, prev_pc.unwrap(), opcode, 4, "ADD A, D"),
+            0x83 => Instruction::normal(op_83, prev_pc.unwrap(), opcode, 4, "ADD A, E"),
+            0x84 => Instruction::normal(op_84, prev_pc.unwrap(), opcode, 4, "ADD A, H"),
+            0x85 => Instruction::normal(op_85, prev_pc.unwrap(), opcode, 4, "ADD A, L"),
+            0x86 => Instruction::normal(op_86, prev_pc.unwrap(), opcode, 8, "ADD A, (HL)"),
+            0x87 => Instruction::normal(op_87, prev_pc.unwrap(), opcode, 4, "ADD A, A"),
+            0x88 => Instruction::normal(op_88, prev_pc.unwrap(), opcode, 4, "ADC A, B"),
+            0x89 => Instruction::normal(op_89, prev_pc.unwrap(), opcode, 4, "ADC A, C"),
+            0x8a => Instruction::normal(op_8a, prev_pc.unwrap(), opcode, 4, "ADC A, D"),
+            0x8b => Instruction::normal(op_8b, prev_pc.unwrap(), opcode, 4, "ADC A, E"),
+            0x8c => Instruction::normal(op_8c, prev_pc.unwrap(), opcode, 4, "ADC A, H"),
+            0x8d => Instruction::normal(op_8d, prev_pc.unwrap(), opcode, 4, "ADC A, L"),
+            0x8e => Instruction::normal(op_8e, prev_pc.unwrap(), opcode, 8, "ADC A, (HL)"),
+            0x8f => Instruction::normal(op_8f, prev_pc.unwrap(), opcode, 4, "ADC A, A"),
+            0x90 => Instruction::normal(op_90, prev_pc.unwrap(), opcode, 4, "SUB A, B"),
+            0x91 => Instruction::normal(op_91, prev_pc.unwrap(), opcode, 4, "SUB A, C"),
+            0x92 => Instruction::normal(op_92, prev_pc.unwrap(), opcode, 4, "SUB A, D"),
+            0x93 => Instruction::normal(op_93, prev_pc.unwrap(), opcode, 4, "SUB A, E"),
+            0x94 => Instruction::normal(op_94, prev_pc.unwrap(), opcode, 4, "SUB A, H"),
+            0x95 => Instruction::normal(op_95, prev_pc.unwrap(), opcode, 4, "SUB A, L"),
+            0x96 => Instruction::normal(op_96, prev_pc.unwrap(), opcode, 8, "SUB A, (HL)"),
+            0x97 => Instruction::normal(op_97, prev_pc.unwrap(), opcode, 4, "SUB A, A"),
+            0x98 => Instruction::normal(op_98, prev_pc.unwrap(), opcode, 4, "SBC A, B"),
+            0x99 => Instruction::normal(op_99, prev_pc.unwrap(), opcode, 4, "SBC A, C"),
+            0x9a => Instruction::normal(op_9a, prev_pc.unwrap(), opcode, 4, "SBC A, D"),
+            0x9b => Instruction::normal(op_9b, prev_pc.unwrap(), opcode, 4, "SBC A, E"),
+            0x9c => Instruction::normal(op_9c, prev_pc.unwrap(), opcode, 4, "SBC A, H"),
+            0x9d => Instruction::normal(op_9d, prev_pc.unwrap(), opcode, 4, "SBC A, L"),
+            0x9e => Instruction::normal(op_9e, prev_pc.unwrap(), opcode, 8, "SBC A, (HL)"),
+            0x9f => Instruction::normal(op_9f, prev_pc.unwrap(), opcode, 4, "SBC A, A"),
+            0xa0 => Instruction::normal(op_a0, prev_pc.unwrap(), opcode, 4, "AND A, B"),
+            0xa1 => Instruction::normal(op_a1, prev_pc.unwrap(), opcode, 4, "AND A, C"),
+            0xa2 => Instruction::normal(op_a2, prev_pc.unwrap(), opcode, 4, "AND A, D"),
+            0xa3 => Instruction::normal(op_a3, prev_pc.unwrap(), opcode, 4, "AND A, E"),
+            0xa4 => Instruction::normal(op_a4, prev_pc.unwrap(), opcode, 4, "AND A, H"),
+            0xa5 => Instruction::normal(op_a5, prev_pc.unwrap(), opcode, 4, "AND A, L"),
+            0xa6 => Instruction::normal(op_a6, prev_pc.unwrap(), opcode, 8, "AND A, (HL)"),
+            0xa7 => Instruction::normal(op_a7, prev_pc.unwrap(), opcode, 4, "AND A, A"),
+            0xa8 => Instruction::normal(op_a8, prev_pc.unwrap(), opcode, 4, "XOR A, B"),
+            0xa9 => Instruction::normal(op_a9, prev_pc.unwrap(), opcode, 4, "XOR A, C"),
+            0xaa => Instruction::normal(op_aa, prev_pc.unwrap(), opcode, 4, "XOR A, D"),
+            0xab => Instruction::normal(op_ab, prev_pc.unwrap(), opcode, 4, "XOR A, E"),
+            0xac => Instruction::normal(op_ac, prev_pc.unwrap(), opcode, 4, "XOR A, H"),
+            0xad => Instruction::normal(op_ad, prev_pc.unwrap(), opcode, 4, "XOR A, L"),
+            0xae => Instruction::normal(op_ae, prev_pc.unwrap(), opcode, 8, "XOR A, (HL)"),
+            0xaf => Instruction::normal(op_af, prev_pc.unwrap(), opcode, 4, "XOR A, A"),
+            0xb0 => Instruction::normal(op_b0, prev_pc.unwrap(), opcode, 4, "OR A, B"),
+            0xb1 => Instruction::normal(op_b1, prev_pc.unwrap(), opcode, 4, "OR A, C"),
+            0xb2 => Instruction::normal(op_b2, prev_pc.unwrap(), opcode, 4, "OR A, D"),
+            0xb3 => Instruction::normal(op_b3, prev_pc.unwrap(), opcode, 4, "OR A, E"),
+            0xb4 => Instruction::normal(op_b4, prev_pc.unwrap(), opcode, 4, "OR A, H"),
+            0xb5 => Instruction::normal(op_b5, prev_pc.unwrap(), opcode, 4, "OR A, L"),
+            0xb6 => Instruction::normal(op_b6, prev_pc.unwrap(), opcode, 8, "OR A, (HL)"),
+            0xb7 => Instruction::normal(op_b7, prev_pc.unwrap(), opcode, 4, "OR A, A"),
+            0xb8 => Instruction::normal(op_b8, prev_pc.unwrap(), opcode, 4, "CP A, B"),
+            0xb9 => Instruction::normal(op_b9, prev_pc.unwrap(), opcode, 4, "CP A, C"),
+            0xba => Instruction::normal(op_ba, prev_pc.unwrap(), opcode, 4, "CP A, D"),
+            0xbb => Instruction::normal(op_bb, prev_pc.unwrap(), opcode, 4, "CP A, E"),
+            0xbc => Instruction::normal(op_bc, prev_pc.unwrap(), opcode, 4, "CP A, H"),
+            0xbd => Instruction::normal(op_bd, prev_pc.unwrap(), opcode, 4, "CP A, L"),
+            0xbe => Instruction::normal(op_be, prev_pc.unwrap(), opcode, 8, "CP A, (HL)"),
+            0xbf => Instruction::normal(op_bf, prev_pc.unwrap(), opcode, 4, "CP A, A"),
+            0xc6 => Instruction::normal(op_c6, prev_pc.unwrap(), opcode, 8, "ADD A, u8"),
+            0xce => Instruction::normal(op_ce, prev_pc.unwrap(), opcode, 8, "ADC A, u8"),
+            0xd6 => Instruction::normal(op_d6, prev_pc.unwrap(), opcode, 8, "SUB A, u8"),
+            0xde => Instruction::normal(op_de, prev_pc.unwrap(), opcode, 8, "SBC A, u8"),
+            0xe6 => Instruction::normal(op_e6, prev_pc.unwrap(), opcode, 8, "AND A, u8"),
+            0xe8 => Instruction::normal(op_e8, prev_pc.unwrap(), opcode, 16, "ADD SP, i8"),
+            0xee => Instruction::normal(op_ee, prev_pc.unwrap(), opcode, 8, "XOR A, u8"),
+            0xf6 => Instruction::normal(op_f6, prev_pc.unwrap(), opcode, 8, "OR A, u8"),
+            0xfe => Instruction::normal(op_fe, prev_pc.unwrap(), opcode, 8, "CP A, u8"),
 
             // Load/Store/Move Instructions
-            0x0e => Instruction::normal(op_0e, prev_pc.unwrap(), opcode, "LD C, u8"),
-            0x06 => Instruction::normal(op_06, prev_pc.unwrap(), opcode, "LD B, u8"),
-            0x11 => Instruction::normal(op_11, prev_pc.unwrap(), opcode, "LD DE, u16"),
-            0x16 => Instruction::normal(op_16, prev_pc.unwrap(), opcode, "LD D, u8"),
-            0x1a => Instruction::normal(op_1a, prev_pc.unwrap(), opcode, "LD A, (DE)"),
-            0x1e => Instruction::normal(op_1e, prev_pc.unwrap(), opcode, "LD E, u8"),
-            0x21 => Instruction::normal(op_21, prev_pc.unwrap(), opcode, "LD HL, u16"),
-            0x22 => Instruction::normal(op_22, prev_pc.unwrap(), opcode, "LD (HL+), A"),
-            0x2e => Instruction::normal(op_2e, prev_pc.unwrap(), opcode, "LD L, u8"),
-            0x31 => Instruction::normal(op_31, prev_pc.unwrap(), opcode, "LD SP, u16"),
-            0x32 => Instruction::normal(op_32, prev_pc.unwrap(), opcode, "LD (HL-), A"),
-            0x3e => Instruction::normal(op_3e, prev_pc.unwrap(), opcode, "LD A, u8"),
-            0x4f => Instruction::normal(op_4f, prev_pc.unwrap(), opcode, "LD C, A"),
-            0x57 => Instruction::normal(op_57, prev_pc.unwrap(), opcode, "LD D, A"),
-            0x67 => Instruction::normal(op_67, prev_pc.unwrap(), opcode, "LD H, A"),
-            0x77 => Instruction::normal(op_77, prev_pc.unwrap(), opcode, "LD (HL), A"),
-            0x78 => Instruction::normal(op_78, prev_pc.unwrap(), opcode, "LD A, B"),
-            0x7b => Instruction::normal(op_7b, prev_pc.unwrap(), opcode, "LD A, E"),
-            0x7c => Instruction::normal(op_7c, prev_pc.unwrap(), opcode, "LD A, H"),
-            0x7d => Instruction::normal(op_7d, prev_pc.unwrap(), opcode, "LD A, L"),
-            0xc1 => Instruction::normal(op_c1, prev_pc.unwrap(), opcode, "POP BC"),
-            0xc5 => Instruction::normal(op_c5, prev_pc.unwrap(), opcode, "PUSH BC"),
-            0xe0 => Instruction::normal(op_e0, prev_pc.unwrap(), opcode, "LD (FF00 + u8), A"),
-            0xe2 => Instruction::normal(op_e2, prev_pc.unwrap(), opcode, "LD (FF00 + C), A"),
-            0xea => Instruction::normal(op_ea, prev_pc.unwrap(), opcode, "LD (u16), A"),
-            0xf0 => Instruction::normal(op_f0, prev_pc.unwrap(), opcode, "LD A, (FF00 + u8)"),
+            0x01 => Instruction::normal(op_01, prev_pc.unwrap(), opcode, 12, "LD BC, u16"),
+            0x02 => Instruction::normal(op_02, prev_pc.unwrap(), opcode, 8, "LD (BC), A"),
+            0x06 => Instruction::normal(op_06, prev_pc.unwrap(), opcode, 8, "LD B, u8"),
+            0x08 => Instruction::normal(op_08, prev_pc.unwrap(), opcode, 20, "LD (u16), SP"),
+            0x0a => Instruction::normal(op_0a, prev_pc.unwrap(), opcode, 8, "LD A, (BC)"),
+            0x0e => Instruction::normal(op_0e, prev_pc.unwrap(), opcode, 8, "LD C, u8"),
+            0x11 => Instruction::normal(op_11, prev_pc.unwrap(), opcode, 12, "LD DE, u16"),
+            0x12 => Instruction::normal(op_12, prev_pc.unwrap(), opcode, 8, "LD (DE), A"),
+            0x16 => Instruction::normal(op_16, prev_pc.unwrap(), opcode, 8, "LD D, u8"),
+            0x1a => Instruction::normal(op_1a, prev_pc.unwrap(), opcode, 8, "LD A, (DE)"),
+            0x1e => Instruction::normal(op_1e, prev_pc.unwrap(), opcode, 8, "LD E, u8"),
+            0x21 => Instruction::normal(op_21, prev_pc.unwrap(), opcode, 12, "LD HL, u16"),
+            0x22 => Instruction::normal(op_22, prev_pc.unwrap(), opcode, 8, "LD (HL+), A"),
+            0x26 => Instruction::normal(op_26, prev_pc.unwrap(), opcode, 8, "LD H, u8"),
+            0x2a => Instruction::normal(op_2a, prev_pc.unwrap(), opcode, 8, "LD A, (HL+)"),
+            0x2e => Instruction::normal(op_2e, prev_pc.unwrap(), opcode, 8, "LD L, u8"),
+            0x31 => Instruction::normal(op_31, prev_pc.unwrap(), opcode, 12, "LD SP, u16"),
+            0x32 => Instruction::normal(op_32, prev_pc.unwrap(), opcode, 8, "LD (HL-), A"),
+            0x36 => Instruction::normal(op_36, prev_pc.unwrap(), opcode, 12, "LD (HL), u8"),
+            0x3a => Instruction::normal(op_3a, prev_pc.unwrap(), opcode, 8, "LD A, (HL-)"),
+            0x3e => Instruction::normal(op_3e, prev_pc.unwrap(), opcode, 8, "LD A, u8"),
+            0x40 => Instruction::normal(op_40, prev_pc.unwrap(), opcode, 4, "LD B, B"),
+            0x41 => Instruction::normal(op_41, prev_pc.unwrap(), opcode, 4, "LD B, C"),
+            0x42 => Instruction::normal(op_42, prev_pc.unwrap(), opcode, 4, "LD B, D"),
+            0x43 => Instruction::normal(op_43, prev_pc.unwrap(), opcode, 4, "LD B, E"),
+            0x44 => Instruction::normal(op_44, prev_pc.unwrap(), opcode, 4, "LD B, H"),
+            0x45 => Instruction::normal(op_45, prev_pc.unwrap(), opcode, 4, "LD B, L"),
+            0x46 => Instruction::normal(op_46, prev_pc.unwrap(), opcode, 8, "LD B, (HL)"),
+            0x47 => Instruction::normal(op_47, prev_pc.unwrap(), opcode, 4, "LD B, A"),
+            0x48 => Instruction::normal(op_48, prev_pc.unwrap(), opcode, 4, "LD C, B"),
+            0x49 => Instruction::normal(op_49, prev_pc.unwrap(), opcode, 4, "LD C, C"),
+            0x4a => Instruction::normal(op_4a, prev_pc.unwrap(), opcode, 4, "LD C, D"),
+            0x4b => Instruction::normal(op_4b, prev_pc.unwrap(), opcode, 4, "LD C, E"),
+            0x4c => Instruction::normal(op_4c, prev_pc.unwrap(), opcode, 4, "LD C, H"),
+            0x4d => Instruction::normal(op_4d, prev_pc.unwrap(), opcode, 4, "LD C, L"),
+            0x4e => Instruction::normal(op_4e, prev_pc.unwrap(), opcode, 8, "LD C, (HL)"),
+            0x4f => Instruction::normal(op_4f, prev_pc.unwrap(), opcode, 4, "LD C, A"),
+            0x50 => Instruction::normal(op_50, prev_pc.unwrap(), opcode, 4, "LD D, B"),
+            0x51 => Instruction::normal(op_51, prev_pc.unwrap(), opcode, 4, "LD D, C"),
+            0x52 => Instruction::normal(op_52, prev_pc.unwrap(), opcode, 4, "LD D, D"),
+            0x53 => Instruction::normal(op_53, prev_pc.unwrap(), opcode, 4, "LD D, E"),
+            0x54 => Instruction::normal(op_54, prev_pc.unwrap(), opcode, 4, "LD D, H"),
+            0x55 => Instruction::normal(op_55, prev_pc.unwrap(), opcode, 4, "LD D, L"),
+            0x56 => Instruction::normal(op_56, prev_pc.unwrap(), opcode, 8, "LD D, (HL)"),
+            0x57 => Instruction::normal(op_57, prev_pc.unwrap(), opcode, 4, "LD D, A"),
+            0x58 => Instruction::normal(op_58, prev_pc.unwrap(), opcode, 4, "LD E, B"),
+            0x59 => Instruction::normal(op_59, prev_pc.unwrap(), opcode, 4, "LD E, C"),
+            0x5a => Instruction::normal(op_5a, prev_pc.unwrap(), opcode, 4, "LD E, D"),
+            0x5b => Instruction::normal(op_5b, prev_pc.unwrap(), opcode, 4, "LD E, E"),
+            0x5c => Instruction::normal(op_5c, prev_pc.unwrap(), opcode, 4, "LD E, H"),
+            0x5d => Instruction::normal(op_5d, prev_pc.unwrap(), opcode, 4, "LD E, L"),
+            0x5e => Instruction::normal(op_5e, prev_pc.unwrap(), opcode, 8, "LD E, (HL)"),
+            0x5f => Instruction::normal(op_5f, prev_pc.unwrap(), opcode, 4, "LD E, A"),
+            0x60 => Instruction::normal(op_60, prev_pc.unwrap(), opcode, 4, "LD H, B"),
+            0x61 => Instruction::normal(op_61, prev_pc.unwrap(), opcode, 4, "LD H, C"),
+            0x62 => Instruction::normal(op_62, prev_pc.unwrap(), opcode, 4, "LD H, D"),
+            0x63 => Instruction::normal(op_63, prev_pc.unwrap(), opcode, 4, "LD H, E"),
+            0x64 => Instruction::normal(op_64, prev_pc.unwrap(), opcode, 4, "LD H, H"),
+            0x65 => Instruction::normal(op_65, prev_pc.unwrap(), opcode, 4, "LD H, L"),
+            0x66 => Instruction::normal(op_66, prev_pc.unwrap(), opcode, 8, "LD H, (HL)"),
+            0x67 => Instruction::normal(op_67, prev_pc.unwrap(), opcode, 4, "LD H, A"),
+            0x68 => Instruction::normal(op_68, prev_pc.unwrap(), opcode, 4, "LD L, B"),
+            0x69 => Instruction::normal(op_69, prev_pc.unwrap(), opcode, 4, "LD L, C"),
+            0x6a => Instruction::normal(op_6a, prev_pc.unwrap(), opcode, 4, "LD L, D"),
+            0x6b => Instruction::normal(op_6b, prev_pc.unwrap(), opcode, 4, "LD L, E"),
+            0x6c => Instruction::normal(op_6c, prev_pc.unwrap(), opcode, 4, "LD L, H"),
+            0x6d => Instruction::normal(op_6d, prev_pc.unwrap(), opcode, 4, "LD L, L"),
+            0x6e => Instruction::normal(op_6e, prev_pc.unwrap(), opcode, 8, "LD L, (HL)"),
+            0x6f => Instruction::normal(op_6f, prev_pc.unwrap(), opcode, 4, "LD L, A"),
+            0x70 => Instruction::normal(op_70, prev_pc.unwrap(), opcode, 8, "LD (HL), B"),
+            0x71 => Instruction::normal(op_71, prev_pc.unwrap(), opcode, 8, "LD (HL), C"),
+            0x72 => Instruction::normal(op_72, prev_pc.unwrap(), opcode, 8, "LD (HL), D"),
+            0x73 => Instruction::normal(op_73, prev_pc.unwrap(), opcode, 8, "LD (HL), E"),
+            0x74 => Instruction::normal(op_74, prev_pc.unwrap(), opcode, 8, "LD (HL), H"),
+            0x75 => Instruction::normal(op_75, prev_pc.unwrap(), opcode, 8, "LD (HL), L"),
+            0x77 => Instruction::normal(op_77, prev_pc.unwrap(), opcode, 8, "LD (HL), A"),
+            0x78 => Instruction::normal(op_78, prev_pc.unwrap(), opcode, 4, "LD A, B"),
+            0x79 => Instruction::normal(op_79, prev_pc.unwrap(), opcode, 4, "LD A, C"),
+            0x7a => Instruction::normal(op_7a, prev_pc.unwrap(), opcode, 4, "LD A, D"),
+            0x7b => Instruction::normal(op_7b, prev_pc.unwrap(), opcode, 4, "LD A, E"),
+            0x7c => Instruction::normal(op_7c, prev_pc.unwrap(), opcode, 4, "LD A, H"),
+            0x7d => Instruction::normal(op_7d, prev_pc.unwrap(), opcode, 4, "LD A, L"),
+            0x7e => Instruction::normal(op_7e, prev_pc.unwrap(), opcode, 8, "LD A, (HL)"),
+            0x7f => Instruction::normal(op_7f, prev_pc.unwrap(), opcode, 4, "LD A, A"),
+            0xc1 => Instruction::normal(op_c1, prev_pc.unwrap(), opcode, 12, "POP BC"),
+            0xc5 => Instruction::normal(op_c5, prev_pc.unwrap(), opcode, 16, "PUSH BC"),
+            0xd1 => Instruction::normal(op_d1, prev_pc.unwrap(), opcode, 12, "POP DE"),
+            0xd5 => Instruction::normal(op_d5, prev_pc.unwrap(), opcode, 16, "PUSH DE"),
+            0xe0 => Instruction::normal(op_e0, prev_pc.unwrap(), opcode, 12, "LD (FF00 + u8), A"),
+            0xe1 => Instruction::normal(op_e1, prev_pc.unwrap(), opcode, 12, "POP HL"),
+            0xe2 => Instruction::normal(op_e2, prev_pc.unwrap(), opcode, 8, "LD (FF00 + C), A"),
+            0xe5 => Instruction::normal(op_e5, prev_pc.unwrap(), opcode, 16, "PUSH HL"),
+            0xea => Instruction::normal(op_ea, prev_pc.unwrap(), opcode, 16, "LD (u16), A"),
+            0xf0 => Instruction::normal(op_f0, prev_pc.unwrap(), opcode, 12, "LD A, (FF00 + u8)"),
+            0xf1 => Instruction::normal(op_f1, prev_pc.unwrap(), opcode, 12, "POP AF"),
+            0xf2 => Instruction::normal(op_f2, prev_pc.unwrap(), opcode, 8, "LD A, (FF00 + C)"),
+            0xf5 => Instruction::normal(op_f5, prev_pc.unwrap(), opcode, 16, "PUSH AF"),
+            0xf8 => Instruction::normal(op_f8, prev_pc.unwrap(), opcode, 12, "LD HL, SP + i8"),
+            0xf9 => Instruction::normal(op_f9, prev_pc.unwrap(), opcode, 8, "LD SP, HL"),
+            0xfa => Instruction::normal(op_fa, prev_pc.unwrap(), opcode, 16, "LD A, (u16)"),
 
             // Control Instructions
-            0x18 => Instruction::normal(op_18, prev_pc.unwrap(), opcode, "JR i8"),
-            0x20 => Instruction::normal(op_20, prev_pc.unwrap(), opcode, "JR NZ, i8"),
-            0x28 => Instruction::normal(op_28, prev_pc.unwrap(), opcode, "JR Z, i8"),
-            0xc9 => Instruction::normal(op_c9, prev_pc.unwrap(), opcode, "RET"),
-            0xcd => Instruction::normal(op_cd, prev_pc.unwrap(), opcode, "CALL u16"),
+            0x18 => Instruction::normal(op_18, prev_pc.unwrap(), opcode, 12, "JR i8"),
+            0x20 => Instruction::normal(op_20, prev_pc.unwrap(), opcode, 8, "JR NZ, i8"),
+            0x28 => Instruction::normal(op_28, prev_pc.unwrap(), opcode, 8, "JR Z, i8"),
+            0x30 => Instruction::normal(op_30, prev_pc.unwrap(), opcode, 8, "JR NC, i8"),
+            0x38 => Instruction::normal(op_38, prev_pc.unwrap(), opcode, 8, "JR C, i8"),
+            0xc0 => Instruction::normal(op_c0, prev_pc.unwrap(), opcode, 8, "RET NZ"),
+            0xc2 => Instruction::normal(op_c2, prev_pc.unwrap(), opcode, 12, "JP NZ, u16"),
+            0xc3 => Instruction::normal(op_c3, prev_pc.unwrap(), opcode, 16, "JP u16"),
+            0xc4 => Instruction::normal(op_c4, prev_pc.unwrap(), opcode, 12, "CALL NZ, u16"),
+            0xc7 => Instruction::normal(op_c7, prev_pc.unwrap(), opcode, 16, "RST 00h"),
+            0xc8 => Instruction::normal(op_c8, prev_pc.unwrap(), opcode, 8, "RET Z"),
+            0xc9 => Instruction::normal(op_c9, prev_pc.unwrap(), opcode, 16, "RET"),
+            0xca => Instruction::normal(op_ca, prev_pc.unwrap(), opcode, 12, "JP Z, u16"),
+            0xcc => Instruction::normal(op_cc, prev_pc.unwrap(), opcode, 12, "CALL Z, u16"),
+            0xcd => Instruction::normal(op_cd, prev_pc.unwrap(), opcode, 24, "CALL u16"),
+            0xcf => Instruction::normal(op_cf, prev_pc.unwrap(), opcode, 16, "RST 08h"),
+            0xd0 => Instruction::normal(op_d0, prev_pc.unwrap(), opcode, 8, "RET NC"),
+            0xd2 => Instruction::normal(op_d2, prev_pc.unwrap(), opcode, 12, "JP NC, u16"),
+            0xd4 => Instruction::normal(op_d4, prev_pc.unwrap(), opcode, 12, "CALL NC, u16"),
+            0xd7 => Instruction::normal(op_d7, prev_pc.unwrap(), opcode, 16, "RST 10h"),
+            0xd8 => Instruction::normal(op_d8, prev_pc.unwrap(), opcode, 8, "RET C"),
+            0xd9 => Instruction::normal(op_d9, prev_pc.unwrap(), opcode, 16, "RETI"),
+            0xda => Instruction::normal(op_da, prev_pc.unwrap(), opcode, 12, "JP C, u16"),
+            0xdc => Instruction::normal(op_dc, prev_pc.unwrap(), opcode, 12, "CALL C, u16"),
+            0xdf => Instruction::normal(op_df, prev_pc.unwrap(), opcode, 16, "RST 18h"),
+            0xe7 => Instruction::normal(op_e7, prev_pc.unwrap(), opcode, 16, "RST 20h"),
+            0xe9 => Instruction::normal(op_e9, prev_pc.unwrap(), opcode, 4, "JP HL"),
+            0xef => Instruction::normal(op_ef, prev_pc.unwrap(), opcode, 16, "RST 28h"),
+            0xf7 => Instruction::normal(op_f7, prev_pc.unwrap(), opcode, 16, "RST 30h"),
+            0xff => Instruction::normal(op_ff, prev_pc.unwrap(), opcode, 16, "RST 38h"),
 
             // Rotate/Shift/Bitwise Instructions
-            0x17 => Instruction::normal(op_17, prev_pc.unwrap(), opcode, "RLA"),
+            0x07 => Instruction::normal(op_07, prev_pc.unwrap(), opcode, 4, "RLCA"),
+            0x0f => Instruction::normal(op_0f, prev_pc.unwrap(), opcode, 4, "RRCA"),
+            0x17 => Instruction::normal(op_17, prev_pc.unwrap(), opcode, 4, "RLA"),
+            0x1f => Instruction::normal(op_1f, prev_pc.unwrap(), opcode, 4, "RRA"),
             0xcb => {
                 let opcode = self.next_byte();
                 match opcode {
-                    0x11 => Instruction::prefixed(op_cb11, prev_pc.unwrap(), opcode, "RL C"),
-                    0x7c => Instruction::prefixed(op_cb7c, prev_pc.unwrap(), opcode, "BIT 7, H"),
-                    _ => panic!("Unknown (prefixed) opcode: {:#04x}", opcode),
+                    0x00 => Instruction::prefixed(op_cb00, prev_pc.unwrap(), opcode, 8, "RLC B"),
+                    0x01 => Instruction::prefixed(op_cb01, prev_pc.unwrap(), opcode, 8, "RLC C"),
+                    0x02 => Instruction::prefixed(op_cb02, prev_pc.unwrap(), opcode, 8, "RLC D"),
+                    0x03 => Instruction::prefixed(op_cb03, prev_pc.unwrap(), opcode, 8, "RLC E"),
+                    0x04 => Instruction::prefixed(op_cb04, prev_pc.unwrap(), opcode, 8, "RLC H"),
+                    0x05 => Instruction::prefixed(op_cb05, prev_pc.unwrap(), opcode, 8, "RLC L"),
+                    0x06 => {
+                        Instruction::prefixed(op_cb06, prev_pc.unwrap(), opcode, 16, "RLC (HL)")
+                    }
+                    0x07 => Instruction::prefixed(op_cb07, prev_pc.unwrap(), opcode, 8, "RLC A"),
+                    0x08 => Instruction::prefixed(op_cb08, prev_pc.unwrap(), opcode, 8, "RRC B"),
+                    0x09 => Instruction::prefixed(op_cb09, prev_pc.unwrap(), opcode, 8, "RRC C"),
+                    0x0a => Instruction::prefixed(op_cb0a, prev_pc.unwrap(), opcode, 8, "RRC D"),
+                    0x0b => Instruction::prefixed(op_cb0b, prev_pc.unwrap(), opcode, 8, "RRC E"),
+                    0x0c => Instruction::prefixed(op_cb0c, prev_pc.unwrap(), opcode, 8, "RRC H"),
+                    0x0d => Instruction::prefixed(op_cb0d, prev_pc.unwrap(), opcode, 8, "RRC L"),
+                    0x0e => {
+                        Instruction::prefixed(op_cb0e, prev_pc.unwrap(), opcode, 16, "RRC (HL)")
+                    }
+                    0x0f => Instruction::prefixed(op_cb0f, prev_pc.unwrap(), opcode, 8, "RRC A"),
+                    0x10 => Instruction::prefixed(op_cb10, prev_pc.unwrap(), opcode, 8, "RL B"),
+                    0x11 => Instruction::prefixed(op_cb11, prev_pc.unwrap(), opcode, 8, "RL C"),
+                    0x12 => Instruction::prefixed(op_cb12, prev_pc.unwrap(), opcode, 8, "RL D"),
+                    0x13 => Instruction::prefixed(op_cb13, prev_pc.unwrap(), opcode, 8, "RL E"),
+                    0x14 => Instruction::prefixed(op_cb14, prev_pc.unwrap(), opcode, 8, "RL H"),
+                    0x15 => Instruction::prefixed(op_cb15, prev_pc.unwrap(), opcode, 8, "RL L"),
+                    0x16 => Instruction::prefixed(op_cb16, prev_pc.unwrap(), opcode, 16, "RL (HL)"),
+                    0x17 => Instruction::prefixed(op_cb17, prev_pc.unwrap(), opcode, 8, "RL A"),
+                    0x18 => Instruction::prefixed(op_cb18, prev_pc.unwrap(), opcode, 8, "RR B"),
+                    0x19 => Instruction::prefixed(op_cb19, prev_pc.unwrap(), opcode, 8, "RR C"),
+                    0x1a => Instruction::prefixed(op_cb1a, prev_pc.unwrap(), opcode, 8, "RR D"),
+                    0x1b => Instruction::prefixed(op_cb1b, prev_pc.unwrap(), opcode, 8, "RR E"),
+                    0x1c => Instruction::prefixed(op_cb1c, prev_pc.unwrap(), opcode, 8, "RR H"),
+                    0x1d => Instruction::prefixed(op_cb1d, prev_pc.unwrap(), opcode, 8, "RR L"),
+                    0x1e => Instruction::prefixed(op_cb1e, prev_pc.unwrap(), opcode, 16, "RR (HL)"),
+                    0x1f => Instruction::prefixed(op_cb1f, prev_pc.unwrap(), opcode, 8, "RR A"),
+                    0x20 => Instruction::prefixed(op_cb20, prev_pc.unwrap(), opcode, 8, "SLA B"),
+                    0x21 => Instruction::prefixed(op_cb21, prev_pc.unwrap(), opcode, 8, "SLA C"),
+                    0x22 => Instruction::prefixed(op_cb22, prev_pc.unwrap(), opcode, 8, "SLA D"),
+                    0x23 => Instruction::prefixed(op_cb23, prev_pc.unwrap(), opcode, 8, "SLA E"),
+                    0x24 => Instruction::prefixed(op_cb24, prev_pc.unwrap(), opcode, 8, "SLA H"),
+                    0x25 => Instruction::prefixed(op_cb25, prev_pc.unwrap(), opcode, 8, "SLA L"),
+                    0x26 => {
+                        Instruction::prefixed(op_cb26, prev_pc.unwrap(), opcode, 16, "SLA (HL)")
+                    }
+                    0x27 => Instruction::prefixed(op_cb27, prev_pc.unwrap(), opcode, 8, "SLA A"),
+                    0x28 => Instruction::prefixed(op_cb28, prev_pc.unwrap(), opcode, 8, "SRA B"),
+                    0x29 => Instruction::prefixed(op_cb29, prev_pc.unwrap(), opcode, 8, "SRA C"),
+                    0x2a => Instruction::prefixed(op_cb2a, prev_pc.unwrap(), opcode, 8, "SRA D"),
+                    0x2b => Instruction::prefixed(op_cb2b, prev_pc.unwrap(), opcode, 8, "SRA E"),
+                    0x2c => Instruction::prefixed(op_cb2c, prev_pc.unwrap(), opcode, 8, "SRA H"),
+                    0x2d => Instruction::prefixed(op_cb2d, prev_pc.unwrap(), opcode, 8, "SRA L"),
+                    0x2e => {
+                        Instruction::prefixed(op_cb2e, prev_pc.unwrap(), opcode, 16, "SRA (HL)")
+                    }
+                    0x2f => Instruction::prefixed(op_cb2f, prev_pc.unwrap(), opcode, 8, "SRA A"),
+                    0x30 => Instruction::prefixed(op_cb30, prev_pc.unwrap(), opcode, 8, "SWAP B"),
+                    0x31 => Instruction::prefixed(op_cb31, prev_pc.unwrap(), opcode, 8, "SWAP C"),
+                    0x32 => Instruction::prefixed(op_cb32, prev_pc.unwrap(), opcode, 8, "SWAP D"),
+                    0x33 => Instruction::prefixed(op_cb33, prev_pc.unwrap(), opcode, 8, "SWAP E"),
+                    0x34 => Instruction::prefixed(op_cb34, prev_pc.unwrap(), opcode, 8, "SWAP H"),
+                    0x35 => Instruction::prefixed(op_cb35, prev_pc.unwrap(), opcode, 8, "SWAP L"),
+                    0x36 => {
+                        Instruction::prefixed(op_cb36, prev_pc.unwrap(), opcode, 16, "SWAP (HL)")
+                    }
+                    0x37 => Instruction::prefixed(op_cb37, prev_pc.unwrap(), opcode, 8, "SWAP A"),
+                    0x38 => Instruction::prefixed(op_cb38, prev_pc.unwrap(), opcode, 8, "SRL B"),
+                    0x39 => Instruction::prefixed(op_cb39, prev_pc.unwrap(), opcode, 8, "SRL C"),
+                    0x3a => Instruction::prefixed(op_cb3a, prev_pc.unwrap(), opcode, 8, "SRL D"),
+                    0x3b => Instruction::prefixed(op_cb3b, prev_pc.unwrap(), opcode, 8, "SRL E"),
+                    0x3c => Instruction::prefixed(op_cb3c, prev_pc.unwrap(), opcode, 8, "SRL H"),
+                    0x3d => Instruction::prefixed(op_cb3d, prev_pc.unwrap(), opcode, 8, "SRL L"),
+                    0x3e => {
+                        Instruction::prefixed(op_cb3e, prev_pc.unwrap(), opcode, 16, "SRL (HL)")
+                    }
+                    0x3f => Instruction::prefixed(op_cb3f, prev_pc.unwrap(), opcode, 8, "SRL A"),
+                    0x40 => Instruction::prefixed(op_cb40, prev_pc.unwrap(), opcode, 8, "BIT 0, B"),
+                    0x41 => Instruction::prefixed(op_cb41, prev_pc.unwrap(), opcode, 8, "BIT 0, C"),
+                    0x42 => Instruction::prefixed(op_cb42, prev_pc.unwrap(), opcode, 8, "BIT 0, D"),
+                    0x43 => Instruction::prefixed(op_cb43, prev_pc.unwrap(), opcode, 8, "BIT 0, E"),
+                    0x44 => Instruction::prefixed(op_cb44, prev_pc.unwrap(), opcode, 8, "BIT 0, H"),
+                    0x45 => Instruction::prefixed(op_cb45, prev_pc.unwrap(), opcode, 8, "BIT 0, L"),
+                    0x46 => {
+                        Instruction::prefixed(op_cb46, prev_pc.unwrap(), opcode, 12, "BIT 0, (HL)")
+                    }
+                    0x47 => Instruction::prefixed(op_cb47, prev_pc.unwrap(), opcode, 8, "BIT 0, A"),
+                    0x48 => Instruction::prefixed(op_cb48, prev_pc.unwrap(), opcode, 8, "BIT 1, B"),
+                    0x49 => Instruction::prefixed(op_cb49, prev_pc.unwrap(), opcode, 8, "BIT 1, C"),
+                    0x4a => Instruction::prefixed(op_cb4a, prev_pc.unwrap(), opcode, 8, "BIT 1, D"),
+                    0x4b => Instruction::prefixed(op_cb4b, prev_pc.unwrap(), opcode, 8, "BIT 1, E"),
+                    0x4c => Instruction::prefixed(op_cb4c, prev_pc.unwrap(), opcode, 8, "BIT 1, H"),
+                    0x4d => Instruction::prefixed(op_cb4d, prev_pc.unwrap(), opcode, 8, "BIT 1, L"),
+                    0x4e => {
+                        Instruction::prefixed(op_cb4e, prev_pc.unwrap(), opcode, 12, "BIT 1, (HL)")
+                    }
+                    0x4f => Instruction::prefixed(op_cb4f, prev_pc.unwrap(), opcode, 8, "BIT 1, A"),
+                    0x50 => Instruction::prefixed(op_cb50, prev_pc.unwrap(), opcode, 8, "BIT 2, B"),
+                    0x51 => Instruction::prefixed(op_cb51, prev_pc.unwrap(), opcode, 8, "BIT 2, C"),
+                    0x52 => Instruction::prefixed(op_cb52, prev_pc.unwrap(), opcode, 8, "BIT 2, D"),
+                    0x53 => Instruction::prefixed(op_cb53, prev_pc.unwrap(), opcode, 8, "BIT 2, E"),
+                    0x54 => Instruction::prefixed(op_cb54, prev_pc.unwrap(), opcode, 8, "BIT 2, H"),
+                    0x55 => Instruction::prefixed(op_cb55, prev_pc.unwrap(), opcode, 8, "BIT 2, L"),
+                    0x56 => {
+                        Instruction::prefixed(op_cb56, prev_pc.unwrap(), opcode, 12, "BIT 2, (HL)")
+                    }
+                    0x57 => Instruction::prefixed(op_cb57, prev_pc.unwrap(), opcode, 8, "BIT 2, A"),
+                    0x58 => Instruction::prefixed(op_cb58, prev_pc.unwrap(), opcode, 8, "BIT 3, B"),
+                    0x59 => Instruction::prefixed(op_cb59, prev_pc.unwrap(), opcode, 8, "BIT 3, C"),
+                    0x5a => Instruction::prefixed(op_cb5a, prev_pc.unwrap(), opcode, 8, "BIT 3, D"),
+                    0x5b => Instruction::prefixed(op_cb5b, prev_pc.unwrap(), opcode, 8, "BIT 3, E"),
+                    0x5c => Instruction::prefixed(op_cb5c, prev_pc.unwrap(), opcode, 8, "BIT 3, H"),
+                    0x5d => Instruction::prefixed(op_cb5d, prev_pc.unwrap(), opcode, 8, "BIT 3, L"),
+                    0x5e => {
+                        Instruction::prefixed(op_cb5e, prev_pc.unwrap(), opcode, 12, "BIT 3, (HL)")
+                    }
+                    0x5f => Instruction::prefixed(op_cb5f, prev_pc.unwrap(), opcode, 8, "BIT 3, A"),
+                    0x60 => Instruction::prefixed(op_cb60, prev_pc.unwrap(), opcode, 8, "BIT 4, B"),
+                    0x61 => Instruction::prefixed(op_cb61, prev_pc.unwrap(), opcode, 8, "BIT 4, C"),
+                    0x62 => Instruction::prefixed(op_cb62, prev_pc.unwrap(), opcode, 8, "BIT 4, D"),
+                    0x63 => Instruction::prefixed(op_cb63, prev_pc.unwrap(), opcode, 8, "BIT 4, E"),
+                    0x64 => Instruction::prefixed(op_cb64, prev_pc.unwrap(), opcode, 8, "BIT 4, H"),
+                    0x65 => Instruction::prefixed(op_cb65, prev_pc.unwrap(), opcode, 8, "BIT 4, L"),
+                    0x66 => {
+                        Instruction::prefixed(op_cb66, prev_pc.unwrap(), opcode, 12, "BIT 4, (HL)")
+                    }
+                    0x67 => Instruction::prefixed(op_cb67, prev_pc.unwrap(), opcode, 8, "BIT 4, A"),
+                    0x68 => Instruction::prefixed(op_cb68, prev_pc.unwrap(), opcode, 8, "BIT 5, B"),
+                    0x69 => Instruction::prefixed(op_cb69, prev_pc.unwrap(), opcode, 8, "BIT 5, C"),
+                    0x6a => Instruction::prefixed(op_cb6a, prev_pc.unwrap(), opcode, 8, "BIT 5, D"),
+                    0x6b => Instruction::prefixed(op_cb6b, prev_pc.unwrap(), opcode, 8, "BIT 5, E"),
+                    0x6c => Instruction::prefixed(op_cb6c, prev_pc.unwrap(), opcode, 8, "BIT 5, H"),
+                    0x6d => Instruction::prefixed(op_cb6d, prev_pc.unwrap(), opcode, 8, "BIT 5, L"),
+                    0x6e => {
+                        Instruction::prefixed(op_cb6e, prev_pc.unwrap(), opcode, 12, "BIT 5, (HL)")
+                    }
+                    0x6f => Instruction::prefixed(op_cb6f, prev_pc.unwrap(), opcode, 8, "BIT 5, A"),
+                    0x70 => Instruction::prefixed(op_cb70, prev_pc.unwrap(), opcode, 8, "BIT 6, B"),
+                    0x71 => Instruction::prefixed(op_cb71, prev_pc.unwrap(), opcode, 8, "BIT 6, C"),
+                    0x72 => Instruction::prefixed(op_cb72, prev_pc.unwrap(), opcode, 8, "BIT 6, D"),
+                    0x73 => Instruction::prefixed(op_cb73, prev_pc.unwrap(), opcode, 8, "BIT 6, E"),
+                    0x74 => Instruction::prefixed(op_cb74, prev_pc.unwrap(), opcode, 8, "BIT 6, H"),
+                    0x75 => Instruction::prefixed(op_cb75, prev_pc.unwrap(), opcode, 8, "BIT 6, L"),
+                    0x76 => {
+                        Instruction::prefixed(op_cb76, prev_pc.unwrap(), opcode, 12, "BIT 6, (HL)")
+                    }
+                    0x77 => Instruction::prefixed(op_cb77, prev_pc.unwrap(), opcode, 8, "BIT 6, A"),
+                    0x78 => Instruction::prefixed(op_cb78, prev_pc.unwrap(), opcode, 8, "BIT 7, B"),
+                    0x79 => Instruction::prefixed(op_cb79, prev_pc.unwrap(), opcode, 8, "BIT 7, C"),
+                    0x7a => Instruction::prefixed(op_cb7a, prev_pc.unwrap(), opcode, 8, "BIT 7, D"),
+                    0x7b => Instruction::prefixed(op_cb7b, prev_pc.unwrap(), opcode, 8, "BIT 7, E"),
+                    0x7c => Instruction::prefixed(op_cb7c, prev_pc.unwrap(), opcode, 8, "BIT 7, H"),
+                    0x7d => Instruction::prefixed(op_cb7d, prev_pc.unwrap(), opcode, 8, "BIT 7, L"),
+                    0x7e => {
+                        Instruction::prefixed(op_cb7e, prev_pc.unwrap(), opcode, 12, "BIT 7, (HL)")
+                    }
+                    0x7f => Instruction::prefixed(op_cb7f, prev_pc.unwrap(), opcode, 8, "BIT 7, A"),
+                    0x80 => Instruction::prefixed(op_cb80, prev_pc.unwrap(), opcode, 8, "RES 0, B"),
+                    0x81 => Instruction::prefixed(op_cb81, prev_pc.unwrap(), opcode, 8, "RES 0, C"),
+                    0x82 => Instruction::prefixed(op_cb82, prev_pc.unwrap(), opcode, 8, "RES 0, D"),
+                    0x83 => Instruction::prefixed(op_cb83, prev_pc.unwrap(), opcode, 8, "RES 0, E"),
+                    0x84 => Instruction::prefixed(op_cb84, prev_pc.unwrap(), opcode, 8, "RES 0, H"),
+                    0x85 => Instruction::prefixed(op_cb85, prev_pc.unwrap(), opcode, 8, "RES 0, L"),
+                    0x86 => {
+                        Instruction::prefixed(op_cb86, prev_pc.unwrap(), opcode, 16, "RES 0, (HL)")
+                    }
+                    0x87 => Instruction::prefixed(op_cb87, prev_pc.unwrap(), opcode, 8, "RES 0, A"),
+                    0x88 => Instruction::prefixed(op_cb88, prev_pc.unwrap(), opcode, 8, "RES 1, B"),
+                    0x89 => Instruction::prefixed(op_cb89, prev_pc.unwrap(), opcode, 8, "RES 1, C"),
+                    0x8a => Instruction::prefixed(op_cb8a, prev_pc.unwrap(), opcode, 8, "RES 1, D"),
+                    0x8b => Instruction::prefixed(op_cb8b, prev_pc.unwrap(), opcode, 8, "RES 1, E"),
+                    0x8c => Instruction::prefixed(op_cb8c, prev_pc.unwrap(), opcode, 8, "RES 1, H"),
+                    0x8d => Instruction::prefixed(op_cb8d, prev_pc.unwrap(), opcode, 8, "RES 1, L"),
+                    0x8e => {
+                        Instruction::prefixed(op_cb8e, prev_pc.unwrap(), opcode, 16, "RES 1, (HL)")
+                    }
+                    0x8f => Instruction::prefixed(op_cb8f, prev_pc.unwrap(), opcode, 8, "RES 1, A"),
+                    0x90 => Instruction::prefixed(op_cb90, prev_pc.unwrap(), opcode, 8, "RES 2, B"),
+                    0x91 => Instruction::prefixed(op_cb91, prev_pc.unwrap(), opcode, 8, "RES 2, C"),
+                    0x92 => Instruction::prefixed(op_cb92, prev_pc.unwrap(), opcode, 8, "RES 2, D"),
+                    0x93 => Instruction::prefixed(op_cb93, prev_pc.unwrap(), opcode, 8, "RES 2, E"),
+                    0x94 => Instruction::prefixed(op_cb94, prev_pc.unwrap(), opcode, 8, "RES 2, H"),
+                    0x95 => Instruction::prefixed(op_cb95, prev_pc.unwrap(), opcode, 8, "RES 2, L"),
+                    0x96 => {
+                        Instruction::prefixed(op_cb96, prev_pc.unwrap(), opcode, 16, "RES 2, (HL)")
+                    }
+                    0x97 => Instruction::prefixed(op_cb97, prev_pc.unwrap(), opcode, 8, "RES 2, A"),
+                    0x98 => Instruction::prefixed(op_cb98, prev_pc.unwrap(), opcode, 8, "RES 3, B"),
+                    0x99 => Instruction::prefixed(op_cb99, prev_pc.unwrap(), opcode, 8, "RES 3, C"),
+                    0x9a => Instruction::prefixed(op_cb9a, prev_pc.unwrap(), opcode, 8, "RES 3, D"),
+                    0x9b => Instruction::prefixed(op_cb9b, prev_pc.unwrap(), opcode, 8, "RES 3, E"),
+                    0x9c => Instruction::prefixed(op_cb9c, prev_pc.unwrap(), opcode, 8, "RES 3, H"),
+                    0x9d => Instruction::prefixed(op_cb9d, prev_pc.unwrap(), opcode, 8, "RES 3, L"),
+                    0x9e => {
+                        Instruction::prefixed(op_cb9e, prev_pc.unwrap(), opcode, 16, "RES 3, (HL)")
+                    }
+                    0x9f => Instruction::prefixed(op_cb9f, prev_pc.unwrap(), opcode, 8, "RES 3, A"),
+                    0xa0 => Instruction::prefixed(op_cba0, prev_pc.unwrap(), opcode, 8, "RES 4, B"),
+                    0xa1 => Instruction::prefixed(op_cba1, prev_pc.unwrap(), opcode, 8, "RES 4, C"),
+                    0xa2 => Instruction::prefixed(op_cba2, prev_pc.unwrap(), opcode, 8, "RES 4, D"),
+                    0xa3 => Instruction::prefixed(op_cba3, prev_pc.unwrap(), opcode, 8, "RES 4, E"),
+                    0xa4 => Instruction::prefixed(op_cba4, prev_pc.unwrap(), opcode, 8, "RES 4, H"),
+                    0xa5 => Instruction::prefixed(op_cba5, prev_pc.unwrap(), opcode, 8, "RES 4, L"),
+                    0xa6 => {
+                        Instruction::prefixed(op_cba6, prev_pc.unwrap(), opcode, 16, "RES 4, (HL)")
+                    }
+                    0xa7 => Instruction::prefixed(op_cba7, prev_pc.unwrap(), opcode, 8, "RES 4, A"),
+                    0xa8 => Instruction::prefixed(op_cba8, prev_pc.unwrap(), opcode, 8, "RES 5, B"),
+                    0xa9 => Instruction::prefixed(op_cba9, prev_pc.unwrap(), opcode, 8, "RES 5, C"),
+                    0xaa => Instruction::prefixed(op_cbaa, prev_pc.unwrap(), opcode, 8, "RES 5, D"),
+                    0xab => Instruction::prefixed(op_cbab, prev_pc.unwrap(), opcode, 8, "RES 5, E"),
+                    0xac => Instruction::prefixed(op_cbac, prev_pc.unwrap(), opcode, 8, "RES 5, H"),
+                    0xad => Instruction::prefixed(op_cbad, prev_pc.unwrap(), opcode, 8, "RES 5, L"),
+                    0xae => {
+                        Instruction::prefixed(op_cbae, prev_pc.unwrap(), opcode, 16, "RES 5, (HL)")
+                    }
+                    0xaf => Instruction::prefixed(op_cbaf, prev_pc.unwrap(), opcode, 8, "RES 5, A"),
+                    0xb0 => Instruction::prefixed(op_cbb0, prev_pc.unwrap(), opcode, 8, "RES 6, B"),
+                    0xb1 => Instruction::prefixed(op_cbb1, prev_pc.unwrap(), opcode, 8, "RES 6, C"),
+                    0xb2 => Instruction::prefixed(op_cbb2, prev_pc.unwrap(), opcode, 8, "RES 6, D"),
+                    0xb3 => Instruction::prefixed(op_cbb3, prev_pc.unwrap(), opcode, 8, "RES 6, E"),
+                    0xb4 => Instruction::prefixed(op_cbb4, prev_pc.unwrap(), opcode, 8, "RES 6, H"),
+                    0xb5 => Instruction::prefixed(op_cbb5, prev_pc.unwrap(), opcode, 8, "RES 6, L"),
+                    0xb6 => {
+                        Instruction::prefixed(op_cbb6, prev_pc.unwrap(), opcode, 16, "RES 6, (HL)")
+                    }
+                    0xb7 => Instruction::prefixed(op_cbb7, prev_pc.unwrap(), opcode, 8, "RES 6, A"),
+                    0xb8 => Instruction::prefixed(op_cbb8, prev_pc.unwrap(), opcode, 8, "RES 7, B"),
+                    0xb9 => Instruction::prefixed(op_cbb9, prev_pc.unwrap(), opcode, 8, "RES 7, C"),
+                    0xba => Instruction::prefixed(op_cbba, prev_pc.unwrap(), opcode, 8, "RES 7, D"),
+                    0xbb => Instruction::prefixed(op_cbbb, prev_pc.unwrap(), opcode, 8, "RES 7, E"),
+                    0xbc => Instruction::prefixed(op_cbbc, prev_pc.unwrap(), opcode, 8, "RES 7, H"),
+                    0xbd => Instruction::prefixed(op_cbbd, prev_pc.unwrap(), opcode, 8, "RES 7, L"),
+                    0xbe => {
+                        Instruction::prefixed(op_cbbe, prev_pc.unwrap(), opcode, 16, "RES 7, (HL)")
+                    }
+                    0xbf => Instruction::prefixed(op_cbbf, prev_pc.unwrap(), opcode, 8, "RES 7, A"),
+                    0xc0 => Instruction::prefixed(op_cbc0, prev_pc.unwrap(), opcode, 8, "SET 0, B"),
+                    0xc1 => Instruction::prefixed(op_cbc1, prev_pc.unwrap(), opcode, 8, "SET 0, C"),
+                    0xc2 => Instruction::prefixed(op_cbc2, prev_pc.unwrap(), opcode, 8, "SET 0, D"),
+                    0xc3 => Instruction::prefixed(op_cbc3, prev_pc.unwrap(), opcode, 8, "SET 0, E"),
+                    0xc4 => Instruction::prefixed(op_cbc4, prev_pc.unwrap(), opcode, 8, "SET 0, H"),
+                    0xc5 => Instruction::prefixed(op_cbc5, prev_pc.unwrap(), opcode, 8, "SET 0, L"),
+                    0xc6 => {
+                        Instruction::prefixed(op_cbc6, prev_pc.unwrap(), opcode, 16, "SET 0, (HL)")
+                    }
+                    0xc7 => Instruction::prefixed(op_cbc7, prev_pc.unwrap(), opcode, 8, "SET 0, A"),
+                    0xc8 => Instruction::prefixed(op_cbc8, prev_pc.unwrap(), opcode, 8, "SET 1, B"),
+                    0xc9 => Instruction::prefixed(op_cbc9, prev_pc.unwrap(), opcode, 8, "SET 1, C"),
+                    0xca => Instruction::prefixed(op_cbca, prev_pc.unwrap(), opcode, 8, "SET 1, D"),
+                    0xcb => Instruction::prefixed(op_cbcb, prev_pc.unwrap(), opcode, 8, "SET 1, E"),
+                    0xcc => Instruction::prefixed(op_cbcc, prev_pc.unwrap(), opcode, 8, "SET 1, H"),
+                    0xcd => Instruction::prefixed(op_cbcd, prev_pc.unwrap(), opcode, 8, "SET 1, L"),
+                    0xce => {
+                        Instruction::prefixed(op_cbce, prev_pc.unwrap(), opcode, 16, "SET 1, (HL)")
+                    }
+                    0xcf => Instruction::prefixed(op_cbcf, prev_pc.unwrap(), opcode, 8, "SET 1, A"),
+                    0xd0 => Instruction::prefixed(op_cbd0, prev_pc.unwrap(), opcode, 8, "SET 2, B"),
+                    0xd1 => Instruction::prefixed(op_cbd1, prev_pc.unwrap(), opcode, 8, "SET 2, C"),
+                    0xd2 => Instruction::prefixed(op_cbd2, prev_pc.unwrap(), opcode, 8, "SET 2, D"),
+                    0xd3 => Instruction::prefixed(op_cbd3, prev_pc.unwrap(), opcode, 8, "SET 2, E"),
+                    0xd4 => Instruction::prefixed(op_cbd4, prev_pc.unwrap(), opcode, 8, "SET 2, H"),
+                    0xd5 => Instruction::prefixed(op_cbd5, prev_pc.unwrap(), opcode, 8, "SET 2, L"),
+                    0xd6 => {
+                        Instruction::prefixed(op_cbd6, prev_pc.unwrap(), opcode, 16, "SET 2, (HL)")
+                    }
+                    0xd7 => Instruction::prefixed(op_cbd7, prev_pc.unwrap(), opcode, 8, "SET 2, A"),
+                    0xd8 => Instruction::prefixed(op_cbd8, prev_pc.unwrap(), opcode, 8, "SET 3, B"),
+                    0xd9 => Instruction::prefixed(op_cbd9, prev_pc.unwrap(), opcode, 8, "SET 3, C"),
+                    0xda => Instruction::prefixed(op_cbda, prev_pc.unwrap(), opcode, 8, "SET 3, D"),
+                    0xdb => Instruction::prefixed(op_cbdb, prev_pc.unwrap(), opcode, 8, "SET 3, E"),
+                    0xdc => Instruction::prefixed(op_cbdc, prev_pc.unwrap(), opcode, 8, "SET 3, H"),
+                    0xdd => Instruction::prefixed(op_cbdd, prev_pc.unwrap(), opcode, 8, "SET 3, L"),
+                    0xde => {
+                        Instruction::prefixed(op_cbde, prev_pc.unwrap(), opcode, 16, "SET 3, (HL)")
+                    }
+                    0xdf => Instruction::prefixed(op_cbdf, prev_pc.unwrap(), opcode, 8, "SET 3, A"),
+                    0xe0 => Instruction::prefixed(op_cbe0, prev_pc.unwrap(), opcode, 8, "SET 4, B"),
+                    0xe1 => Instruction::prefixed(op_cbe1, prev_pc.unwrap(), opcode, 8, "SET 4, C"),
+                    0xe2 => Instruction::prefixed(op_cbe2, prev_pc.unwrap(), opcode, 8, "SET 4, D"),
+                    0xe3 => Instruction::prefixed(op_cbe3, prev_pc.unwrap(), opcode, 8, "SET 4, E"),
+                    0xe4 => Instruction::prefixed(op_cbe4, prev_pc.unwrap(), opcode, 8, "SET 4, H"),
+                    0xe5 => Instruction::prefixed(op_cbe5, prev_pc.unwrap(), opcode, 8, "SET 4, L"),
+                    0xe6 => {
+                        Instruction::prefixed(op_cbe6, prev_pc.unwrap(), opcode, 16, "SET 4, (HL)")
+                    }
+                    0xe7 => Instruction::prefixed(op_cbe7, prev_pc.unwrap(), opcode, 8, "SET 4, A"),
+                    0xe8 => Instruction::prefixed(op_cbe8, prev_pc.unwrap(), opcode, 8, "SET 5, B"),
+                    0xe9 => Instruction::prefixed(op_cbe9, prev_pc.unwrap(), opcode, 8, "SET 5, C"),
+                    0xea => Instruction::prefixed(op_cbea, prev_pc.unwrap(), opcode, 8, "SET 5, D"),
+                    0xeb => Instruction::prefixed(op_cbeb, prev_pc.unwrap(), opcode, 8, "SET 5, E"),
+                    0xec => Instruction::prefixed(op_cbec, prev_pc.unwrap(), opcode, 8, "SET 5, H"),
+                    0xed => Instruction::prefixed(op_cbed, prev_pc.unwrap(), opcode, 8, "SET 5, L"),
+                    0xee => {
+                        Instruction::prefixed(op_cbee, prev_pc.unwrap(), opcode, 16, "SET 5, (HL)")
+                    }
+                    0xef => Instruction::prefixed(op_cbef, prev_pc.unwrap(), opcode, 8, "SET 5, A"),
+                    0xf0 => Instruction::prefixed(op_cbf0, prev_pc.unwrap(), opcode, 8, "SET 6, B"),
+                    0xf1 => Instruction::prefixed(op_cbf1, prev_pc.unwrap(), opcode, 8, "SET 6, C"),
+                    0xf2 => Instruction::prefixed(op_cbf2, prev_pc.unwrap(), opcode, 8, "SET 6, D"),
+                    0xf3 => Instruction::prefixed(op_cbf3, prev_pc.unwrap(), opcode, 8, "SET 6, E"),
+                    0xf4 => Instruction::prefixed(op_cbf4, prev_pc.unwrap(), opcode, 8, "SET 6, H"),
+                    0xf5 => Instruction::prefixed(op_cbf5, prev_pc.unwrap(), opcode, 8, "SET 6, L"),
+                    0xf6 => {
+                        Instruction::prefixed(op_cbf6, prev_pc.unwrap(), opcode, 16, "SET 6, (HL)")
+                    }
+                    0xf7 => Instruction::prefixed(op_cbf7, prev_pc.unwrap(), opcode, 8, "SET 6, A"),
+                    0xf8 => Instruction::prefixed(op_cbf8, prev_pc.unwrap(), opcode, 8, "SET 7, B"),
+                    0xf9 => Instruction::prefixed(op_cbf9, prev_pc.unwrap(), opcode, 8, "SET 7, C"),
+                    0xfa => Instruction::prefixed(op_cbfa, prev_pc.unwrap(), opcode, 8, "SET 7, D"),
+                    0xfb => Instruction::prefixed(op_cbfb, prev_pc.unwrap(), opcode, 8, "SET 7, E"),
+                    0xfc => Instruction::prefixed(op_cbfc, prev_pc.unwrap(), opcode, 8, "SET 7, H"),
+                    0xfd => Instruction::prefixed(op_cbfd, prev_pc.unwrap(), opcode, 8, "SET 7, L"),
+                    0xfe => {
+                        Instruction::prefixed(op_cbfe, prev_pc.unwrap(), opcode, 16, "SET 7, (HL)")
+                    }
+                    0xff => Instruction::prefixed(op_cbff, prev_pc.unwrap(), opcode, 8, "SET 7, A"),
                 }
             }
             _ => panic!("Unknown opcode: {:#04x}", opcode),
@@ -202,13 +790,56 @@ impl Cpu {
 
     /// Executes an instruction.
     fn execute(&mut self, instr: Instruction) {
+        self.cycles += instr.cycles as u64;
         (instr.execute)(self);
     }
 
     /// Simulates one step of the CPU.
-    pub fn step(&mut self) {
+    /// Returns the number of cycles elapsed that the rest of the system has yet to catch up with,
+    /// which excludes those it was advanced by on memory accesses when timing accurately.
+    pub fn step(&mut self) -> u32 {
+        // The CPU is halted while a DMA transfer copies its data
+        let stall = self.mem.take_stall_cycles();
+        if stall > 0 {
+            self.cycles += stall as u64;
+            return stall;
+        }
+
+        // A stopped CPU waits for a selected button to pull a P1 line low,
+        // while the clock of the rest of the system stays stopped too
+        if self.stopped {
+            if self.mem.joypad.read() & 0b1111 == 0b1111 {
+                self.cycles += CYCLES_PER_ACCESS as u64;
+                return 0;
+            }
+            self.stopped = false;
+        }
+
+        let start_cycles = self.cycles;
+        self.ticked = 0;
+
+        // A halted CPU idles until an interrupt is requested, even if it isn't serviced
+        let pending = self.mem.pending_interrupts();
+        if self.halt {
+            if pending == 0 {
+                self.cycles += CYCLES_PER_ACCESS as u64;
+                return CYCLES_PER_ACCESS;
+            }
+            self.halt = false;
+        }
+
+        if self.ime && pending != 0 {
+            self.service_interrupt();
+            return ((self.cycles - start_cycles) as u32).saturating_sub(self.ticked);
+        }
+
+        let enabling_interrupts = self.ime_pending;
         let prev_pc = self.pc;
         let opcode = self.next_byte();
+        if self.halt_bug {
+            self.halt_bug = false;
+            self.pc = prev_pc;
+        }
         let instr = self.decode(opcode, Some(prev_pc));
 
         let instr_info = format!(
@@ -223,19 +854,56 @@ impl Cpu {
         }
 
         self.execute(instr);
+
+        // DI right after EI clears the pending enable
+        if enabling_interrupts && self.ime_pending {
+            self.ime_pending = false;
+            self.ime = true;
+        }
+
+        ((self.cycles - start_cycles) as u32).saturating_sub(self.ticked)
+    }
+
+    /// Calls the handler of the highest priority interrupt requested and
+    /// enabled, acknowledging it and disabling interrupts.
+    fn service_interrupt(&mut self) {
+        self.ime = false;
+        self.cycles += INTERRUPT_CYCLES as u64;
+        self.internal_cycle();
+        self.internal_cycle();
+
+        // Pushing the high byte of PC may overwrite IE, in which case the
+        // interrupt is chosen again, or cancelled and PC set to 0
+        self.sp = self.sp.wrapping_sub(1);
+        self.write_byte(self.sp, (self.pc >> 8) as u8);
+        let pending = self.mem.pending_interrupts();
+        self.sp = self.sp.wrapping_sub(1);
+        self.write_byte(self.sp, self.pc as u8);
+
+        self.pc = match interrupts::highest_priority(pending) {
+            Some(interrupt) => {
+                self.mem.acknowledge_interrupt(interrupt);
+                interrupts::vector(interrupt)
+            }
+            None => 0x0000,
+        };
     }
 
-    /// Pushes a value onto the stack.
+    /// Pushes a value onto the stack, the high byte first.
     pub fn push_stack(&mut self, value: u16) {
-        self.sp -= 2;
-        self.mem.write().unwrap().write_word(self.sp, value);
+        self.sp = self.sp.wrapping_sub(1);
+        self.write_byte(self.sp, (value >> 8) as u8);
+        self.sp = self.sp.wrapping_sub(1);
+        self.write_byte(self.sp, value as u8);
     }
 
-    /// Pops a value off the stack.
+    /// Pops a value off the stack, the low byte first.
     pub fn pop_stack(&mut self) -> u16 {
-        let value = self.mem.read().unwrap().read_word(self.sp);
-        self.sp += 2;
-        value
+        let low = self.read_byte(self.sp);
+        self.sp = self.sp.wrapping_add(1);
+        let high = self.read_byte(self.sp);
+        self.sp = self.sp.wrapping_add(1);
+        ((high as u16) << 8) | low as u16
     }
 }
 
@@ -244,12 +912,242 @@ mod tests {
     use super::*;
     #[test]
     fn fetch_decode_execute() {
-        let mem = Memory::new();
-        let mut cpu = Cpu::new(Arc::new(RwLock::new(mem)));
+        let mut mem = Memory::new();
+        mem.rom.bank0[0] = 0x00; // NOP
+        let mut cpu = Cpu::new(mem);
         let prev_pc = cpu.pc;
         let opcode = cpu.next_byte();
         let instr = cpu.decode(opcode, Some(prev_pc));
         cpu.execute(instr);
         assert_eq!(cpu.pc, 0x01);
     }
+
+    #[test]
+    fn test_rotate_right_and_decrement() {
+        // RRC C, RR C, DEC DE
+        let mut mem = Memory::new();
+        mem.rom.bank0[..5].copy_from_slice(&[0xcb, 0x09, 0xcb, 0x19, 0x1b]);
+        let mut cpu = Cpu::new(mem);
+        cpu.regs.c = 0b0000_0011;
+        cpu.regs.set_de(0x0000);
+
+        assert_eq!(cpu.step(), 8);
+        assert_eq!(cpu.regs.c, 0b1000_0001);
+        assert_eq!(cpu.regs.f & 0b0001_0000, 0b0001_0000);
+
+        assert_eq!(cpu.step(), 8);
+        assert_eq!(cpu.regs.c, 0b1100_0000);
+        assert_eq!(cpu.regs.f & 0b0001_0000, 0b0001_0000);
+
+        assert_eq!(cpu.step(), 8);
+        assert_eq!(cpu.regs.de(), 0xffff);
+    }
+
+    #[test]
+    fn test_accumulator_rotates_clear_zero() {
+        // RLA, then RL A
+        let mut mem = Memory::new();
+        mem.rom.bank0[..3].copy_from_slice(&[0x17, 0xcb, 0x17]);
+        let mut cpu = Cpu::new(mem);
+        cpu.regs.a = 0b1000_0000;
+        cpu.regs.f = 0;
+
+        cpu.step();
+        assert_eq!(cpu.regs.a, 0);
+        assert_eq!(cpu.regs.f, 0b0001_0000);
+        cpu.step();
+        assert_eq!(cpu.regs.a, 0b0000_0001);
+        assert_eq!(cpu.regs.f, 0);
+    }
+
+    #[test]
+    fn test_decimal_adjust() {
+        // ADD A, B then DAA: 0x19 + 0x28 = 0x47 in BCD
+        let mut mem = Memory::new();
+        mem.rom.bank0[..2].copy_from_slice(&[0x80, 0x27]);
+        let mut cpu = Cpu::new(mem);
+        cpu.regs.a = 0x19;
+        cpu.regs.b = 0x28;
+        cpu.step();
+        cpu.step();
+        assert_eq!(cpu.regs.a, 0x47);
+        assert_eq!(cpu.get_flag_c(), 0);
+    }
+
+    /// Returns a CPU with the timer interrupt requested and enabled, running
+    /// the given code from address 0x0200 with the stack at 0xd000.
+    fn cpu_with_pending_timer(code: &[u8]) -> Cpu {
+        let mut mem = Memory::new();
+        mem.rom.bank0[0x200..0x200 + code.len()].copy_from_slice(code);
+        mem.ie = interrupts::TIMER;
+        mem.request_interrupts(interrupts::TIMER);
+        let mut cpu = Cpu::new(mem);
+        cpu.pc = 0x0200;
+        cpu.sp = 0xd000;
+        cpu
+    }
+
+    #[test]
+    fn test_interrupt_calls_handler() {
+        let mut cpu = cpu_with_pending_timer(&[0x00]);
+        cpu.ime = true;
+        assert_eq!(cpu.step(), 20);
+        assert_eq!(cpu.pc, 0x0050);
+        assert_eq!(cpu.sp, 0xcffe);
+        assert_eq!(cpu.pop_stack(), 0x0200);
+        assert!(!cpu.ime);
+        assert_eq!(cpu.mem.read_byte(interrupts::IF_ADDR), 0xe0);
+    }
+
+    #[test]
+    fn test_ei_enables_after_next_instruction() {
+        // EI, NOP
+        let mut cpu = cpu_with_pending_timer(&[0xfb, 0x00]);
+        cpu.step();
+        assert!(!cpu.ime);
+        cpu.step();
+        assert_eq!(cpu.pc, 0x0202);
+        cpu.step();
+        assert_eq!(cpu.pc, 0x0050);
+
+        // EI, DI
+        let mut cpu = cpu_with_pending_timer(&[0xfb, 0xf3, 0x00]);
+        cpu.step();
+        cpu.step();
+        cpu.step();
+        assert!(!cpu.ime);
+        assert_eq!(cpu.pc, 0x0203);
+    }
+
+    #[test]
+    fn test_halt_waits_for_interrupt() {
+        // HALT, INC A
+        let mut cpu = cpu_with_pending_timer(&[0x76, 0x3c]);
+        cpu.mem.acknowledge_interrupt(interrupts::TIMER);
+        cpu.step();
+        assert!(cpu.halt);
+        assert_eq!(cpu.step(), 4);
+        assert_eq!(cpu.pc, 0x0201);
+
+        // Without IME, the interrupt ends the halt without being serviced
+        cpu.mem.request_interrupts(interrupts::TIMER);
+        cpu.regs.a = 0;
+        cpu.step();
+        assert!(!cpu.halt);
+        assert_eq!(cpu.regs.a, 1);
+        assert_eq!(cpu.pc, 0x0202);
+    }
+
+    #[test]
+    fn test_halt_bug_repeats_next_opcode() {
+        // HALT with an interrupt pending and IME off, then INC A
+        let mut cpu = cpu_with_pending_timer(&[0x76, 0x3c, 0x00]);
+        cpu.regs.a = 0;
+        cpu.step();
+        assert!(!cpu.halt);
+        cpu.step();
+        cpu.step();
+        assert_eq!(cpu.regs.a, 2);
+        assert_eq!(cpu.pc, 0x0202);
+    }
+
+    #[test]
+    fn test_interrupt_cancelled_by_push_to_ie() {
+        // Pushing the high byte of PC (0x02) to IE disables the timer interrupt
+        let mut cpu = cpu_with_pending_timer(&[0x00]);
+        cpu.ime = true;
+        cpu.sp = 0x0000;
+        cpu.step();
+        assert_eq!(cpu.mem.ie, 0x02);
+        assert_eq!(cpu.pc, 0x0000);
+        assert_eq!(
+            cpu.mem.read_byte(interrupts::IF_ADDR) & interrupts::TIMER,
+            interrupts::TIMER
+        );
+    }
+
+    #[test]
+    fn test_oam_corruption_on_load_increment() {
+        // LD (HL+), A then LD (HL-), A while the PPU scans OAM
+        for opcode in [0x22, 0x32] {
+            let mut mem = Memory::new();
+            mem.rom.bank0[0] = opcode;
+            for (i, byte) in mem.oam.iter_mut().enumerate() {
+                *byte = i as u8;
+            }
+            mem.write_byte(crate::gameboy::ppu::LCDC_ADDR, 0x91);
+            mem.tick(8);
+
+            let mut cpu = Cpu::new(mem);
+            cpu.regs.set_hl(crate::gameboy::memory::OAM_ADDR);
+            cpu.step();
+            assert_eq!(
+                &cpu.mem.oam[16..24],
+                &[0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f]
+            );
+        }
+    }
+
+    #[test]
+    fn test_accurate_timing_reads_mid_instruction() {
+        // LD A, (FF00+u8) reading DIV 4 cycles before it increments
+        let mut mem = Memory::new();
+        mem.rom.bank0[0] = 0xf0;
+        mem.rom.bank0[1] = 0x04;
+        mem.timer.counter = 0x00f8;
+
+        let mut cpu = Cpu::new(mem.clone());
+        assert_eq!(cpu.step(), 12);
+        assert_eq!(cpu.regs.a, 0x00);
+
+        // The read happens after the opcode and operand fetches
+        let mut cpu = Cpu::new(mem);
+        cpu.accurate_timing = true;
+        assert_eq!(cpu.step(), 0);
+        assert_eq!(cpu.regs.a, 0x01);
+    }
+
+    #[test]
+    fn test_accurate_timing_internal_cycles() {
+        // RET NZ popping DIV: the condition check comes before the pop
+        let mut mem = Memory::new();
+        mem.rom.bank0[0] = 0xc0;
+        mem.timer.counter = 0x00f4;
+
+        let mut cpu = Cpu::new(mem);
+        cpu.accurate_timing = true;
+        cpu.regs.f = 0;
+        cpu.sp = crate::gameboy::memory::DIV_ADDR;
+        assert_eq!(cpu.step(), 4);
+        assert_eq!(cpu.pc & 0xff, 0x01);
+
+        // Calling an interrupt handler waits 8 cycles before pushing PC
+        let mut cpu = cpu_with_pending_timer(&[0x00]);
+        cpu.accurate_timing = true;
+        cpu.ime = true;
+        assert_eq!(cpu.step(), 4);
+        assert_eq!(cpu.mem.scheduler.now, 16);
+    }
+
+    #[test]
+    fn test_stop_waits_for_button() {
+        // STOP, padding, NOP
+        let mut mem = Memory::new();
+        mem.rom.bank0[..3].copy_from_slice(&[0x10, 0x00, 0x00]);
+        mem.write_byte(crate::gameboy::memory::JOYP_ADDR, 0x20);
+        let mut cpu = Cpu::new(mem);
+        cpu.step();
+        assert!(cpu.stopped);
+
+        assert_eq!(cpu.step(), 0);
+        assert_eq!(cpu.pc, 0x02);
+
+        cpu.mem.joypad.set_buttons(crate::gameboy::memory::Buttons {
+            right: true,
+            ..Default::default()
+        });
+        cpu.step();
+        assert!(!cpu.stopped);
+        assert_eq!(cpu.pc, 0x03);
+    }
 }
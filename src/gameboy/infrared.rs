@@ -0,0 +1,74 @@
+use std::fmt::Debug;
+use std::sync::{Arc, Mutex};
+
+/// What the infrared port of the CGB faces.
+pub trait InfraredDevice: Debug + Send {
+    /// Called when the game turns its LED on or off.
+    fn set_led(&mut self, on: bool);
+
+    /// Returns whether light is shining on the receiver.
+    fn light(&mut self) -> bool;
+}
+
+/// A device shining a constant light on the receiver, or none at all,
+/// ignoring the LED.
+#[derive(Debug, Default, Clone)]
+pub struct ConstantLight {
+    pub on: bool,
+}
+
+impl InfraredDevice for ConstantLight {
+    fn set_led(&mut self, _on: bool) {}
+
+    fn light(&mut self) -> bool {
+        self.on
+    }
+}
+
+/// The infrared port of one of two `GameBoy`s facing each other, each
+/// receiving the light of the other's LED.
+#[derive(Debug, Clone)]
+pub struct InfraredPort {
+    leds: Arc<Mutex<[bool; 2]>>,
+    /// The index of the LED of this end in `leds`.
+    index: usize,
+}
+
+/// Returns the ports of two `GameBoy`s facing each other.
+pub fn pair() -> (InfraredPort, InfraredPort) {
+    let leds = Arc::new(Mutex::new([false; 2]));
+    (
+        InfraredPort {
+            leds: leds.clone(),
+            index: 0,
+        },
+        InfraredPort { leds, index: 1 },
+    )
+}
+
+impl InfraredDevice for InfraredPort {
+    fn set_led(&mut self, on: bool) {
+        self.leds.lock().unwrap()[self.index] = on;
+    }
+
+    fn light(&mut self) -> bool {
+        self.leds.lock().unwrap()[1 - self.index]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pair_sees_other_led() {
+        let (mut first, mut second) = pair();
+        first.set_led(true);
+        assert!(!first.light());
+        assert!(second.light());
+        second.set_led(true);
+        first.set_led(false);
+        assert!(first.light());
+        assert!(!second.light());
+    }
+}
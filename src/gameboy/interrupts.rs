@@ -0,0 +1,23 @@
+/// The address of the interrupt flag register (`IF`).
+pub const IF_ADDR: u16 = 0xff0f;
+
+/// The V-Blank interrupt, requested when the PPU enters mode 1.
+pub const VBLANK: u8 = 0b0000_0001;
+/// The LCD STAT interrupt, requested on a rising edge of the STAT interrupt line.
+pub const STAT: u8 = 0b0000_0010;
+/// The timer interrupt, requested when `TIMA` overflows.
+pub const TIMER: u8 = 0b0000_0100;
+/// The serial interrupt, requested when a transfer completes.
+pub const SERIAL: u8 = 0b0000_1000;
+/// The joypad interrupt, requested on a high-to-low transition of a `P1` input line.
+pub const JOYPAD: u8 = 0b0001_0000;
+
+/// Returns the highest priority interrupt among the given ones, the lowest bit first.
+pub fn highest_priority(interrupts: u8) -> Option<u8> {
+    (interrupts != 0).then(|| interrupts & interrupts.wrapping_neg())
+}
+
+/// Returns the address of the handler of the given interrupt.
+pub fn vector(interrupt: u8) -> u16 {
+    0x40 + 8 * interrupt.trailing_zeros() as u16
+}
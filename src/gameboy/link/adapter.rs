@@ -0,0 +1,297 @@
+use std::sync::{Arc, Mutex};
+
+use crate::gameboy::serial::SerialDevice;
+use crate::gameboy::GameBoy;
+
+/// The number of Game Boys the adapter connects.
+pub const PLAYERS: usize = 4;
+
+/// The bytes of the protocol of the adapter.
+const PING_HEADER: u8 = 0xfe;
+const ACK: u8 = 0x88;
+const START: u8 = 0xaa;
+const STARTING: u8 = 0xcc;
+/// The number of bytes of a ping packet: the header, then 3 status bytes.
+const PING_LENGTH: usize = 4;
+/// The number of `STARTING` bytes sent before transmission begins.
+const STARTING_LENGTH: usize = 4;
+
+/// The number of cycles between two bytes while pinging, and the base and step
+/// of the period set by the `RATE` byte during transmission. These are
+/// approximations of the timing of the real adapter.
+const PING_PERIOD: u64 = 4096 * 4;
+const BASE_PERIOD: u64 = 4096;
+const RATE_STEP: u64 = 512;
+
+/// What the adapter is doing.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Phase {
+    /// Sending ping packets, each byte of which is at the given index.
+    Ping(usize),
+    /// Announcing the transmission with the given number of `STARTING` bytes left.
+    Starting(usize),
+    /// Relaying the packets of the players, the next byte being at the given index of the frame.
+    Transmission(usize),
+}
+
+/// The state of the port of one player.
+#[derive(Debug, Default, Clone)]
+struct Port {
+    /// The byte offered by the Game Boy waiting for a transfer on the external clock.
+    listening: Option<u8>,
+    /// The byte clocked into the Game Boy, waiting to be picked up.
+    delivered: Option<u8>,
+    /// Whether the Game Boy acknowledged the header of the last ping packet.
+    acknowledged: bool,
+    /// Whether the Game Boy answered the last ping packet.
+    connected: bool,
+    /// The packet sent by the player during the current frame.
+    packet: Vec<u8>,
+}
+
+/// The DMG-07 four-player adapter, which drives the clock of every Game Boy.
+///
+/// While pinging, it sends `PING_HEADER` followed by 3 status bytes holding
+/// the connected players in the upper nibble and the ID of the receiving
+/// player in the lower one. Each Game Boy answers `ACK` twice, then player 1
+/// also sends the `RATE` and `SIZE` of the transmission. Player 1 starts the
+/// transmission by answering `START` to a header, after which the adapter
+/// sends `STARTING` 4 times. Then, in every frame of `4 * SIZE` bytes, each
+/// player sends its packet in the first `SIZE` bytes while receiving the
+/// packets every player sent during the previous frame. The adapter returns to
+/// pinging when player 1 sends a packet of `0xff` bytes.
+#[derive(Debug)]
+pub struct Adapter {
+    ports: [Port; PLAYERS],
+    phase: Phase,
+    rate: u8,
+    size: usize,
+    /// The packets of every player sent during the previous frame, one after the other.
+    frame: Vec<u8>,
+    /// The cycle count of player 1 at which the next byte is clocked.
+    next_byte: u64,
+}
+
+impl Default for Adapter {
+    fn default() -> Self {
+        Self {
+            ports: Default::default(),
+            phase: Phase::Ping(0),
+            rate: 0,
+            size: 1,
+            frame: Vec::new(),
+            next_byte: PING_PERIOD,
+        }
+    }
+}
+
+impl Adapter {
+    /// Returns the number of cycles until the next byte.
+    fn period(&self) -> u64 {
+        match self.phase {
+            Phase::Transmission(_) => BASE_PERIOD + (self.rate & 0x0f) as u64 * RATE_STEP,
+            _ => PING_PERIOD,
+        }
+    }
+
+    /// Returns the status byte of a ping packet sent to the given player.
+    fn ping_status(&self, player: usize) -> u8 {
+        let connected = self
+            .ports
+            .iter()
+            .enumerate()
+            .filter(|(_, port)| port.connected)
+            .fold(0, |mask, (i, _)| mask | 1 << (i + 4));
+        connected | (player + 1) as u8
+    }
+
+    /// Returns the byte sent to the given player next.
+    fn outgoing(&self, player: usize) -> u8 {
+        match self.phase {
+            Phase::Ping(0) => PING_HEADER,
+            Phase::Ping(_) => self.ping_status(player),
+            Phase::Starting(_) => STARTING,
+            Phase::Transmission(index) => self.frame.get(index).copied().unwrap_or(0),
+        }
+    }
+
+    /// Swaps a byte with every Game Boy waiting for one, then moves on to the next byte.
+    fn clock_byte(&mut self) {
+        let outgoing: [u8; PLAYERS] = std::array::from_fn(|player| self.outgoing(player));
+        let mut received = [None; PLAYERS];
+        for ((port, outgoing), received) in self.ports.iter_mut().zip(outgoing).zip(&mut received) {
+            if let Some(byte) = port.listening.take() {
+                port.delivered = Some(outgoing);
+                *received = Some(byte);
+            }
+        }
+
+        self.phase = match self.phase {
+            Phase::Ping(index) => self.receive_ping(index, &received),
+            Phase::Starting(1) => {
+                self.frame = vec![0; PLAYERS * self.size];
+                self.ports.iter_mut().for_each(|port| port.packet.clear());
+                Phase::Transmission(0)
+            }
+            Phase::Starting(left) => Phase::Starting(left - 1),
+            Phase::Transmission(index) => self.receive_transmission(index, &received),
+        };
+    }
+
+    /// Handles the answers to the byte of a ping packet at the given index.
+    fn receive_ping(&mut self, index: usize, received: &[Option<u8>; PLAYERS]) -> Phase {
+        if index == 0 && received[0] == Some(START) {
+            return Phase::Starting(STARTING_LENGTH);
+        }
+
+        for (port, byte) in self.ports.iter_mut().zip(received) {
+            match index {
+                0 => port.acknowledged = *byte == Some(ACK),
+                1 => port.connected = port.acknowledged && *byte == Some(ACK),
+                _ => {}
+            }
+        }
+        match (index, received[0]) {
+            (2, Some(rate)) => self.rate = rate,
+            (3, Some(size)) => self.size = (size as usize).max(1),
+            _ => {}
+        }
+        Phase::Ping((index + 1) % PING_LENGTH)
+    }
+
+    /// Handles the bytes sent by the players at the given index of a frame.
+    fn receive_transmission(&mut self, index: usize, received: &[Option<u8>; PLAYERS]) -> Phase {
+        if index < self.size {
+            for (port, byte) in self.ports.iter_mut().zip(received) {
+                port.packet.push(byte.unwrap_or(0xff));
+            }
+        }
+        if index + 1 < PLAYERS * self.size {
+            return Phase::Transmission(index + 1);
+        }
+
+        // Only the packets of connected players are relayed
+        let size = self.size;
+        self.frame = self
+            .ports
+            .iter_mut()
+            .flat_map(|port| {
+                let packet = std::mem::take(&mut port.packet);
+                if port.connected {
+                    packet
+                } else {
+                    vec![0; size]
+                }
+            })
+            .collect();
+
+        if self.frame[..size].iter().all(|byte| *byte == 0xff) {
+            self.ports
+                .iter_mut()
+                .for_each(|port| port.connected = false);
+            return Phase::Ping(0);
+        }
+        Phase::Transmission(0)
+    }
+}
+
+/// The port of the adapter a Game Boy is plugged into.
+#[derive(Debug, Clone)]
+pub struct AdapterPort {
+    adapter: Arc<Mutex<Adapter>>,
+    player: usize,
+}
+
+/// Returns the ports of a new four-player adapter, player 1 first.
+pub fn adapter() -> [AdapterPort; PLAYERS] {
+    let adapter = Arc::new(Mutex::new(Adapter::default()));
+    std::array::from_fn(|player| AdapterPort {
+        adapter: adapter.clone(),
+        player,
+    })
+}
+
+/// Plugs the given `GameBoy`s, player 1 first, into a new four-player adapter.
+pub fn connect_adapter(gameboys: &mut [GameBoy]) {
+    for (gameboy, port) in gameboys.iter_mut().zip(adapter()) {
+        gameboy.set_serial_device(Box::new(port));
+    }
+}
+
+impl SerialDevice for AdapterPort {
+    /// The adapter drives the clock, so the Game Boys shouldn't: those that
+    /// do only shift in 1s.
+    fn transfer(&mut self, _byte: u8) -> u8 {
+        0xff
+    }
+
+    fn poll_external(&mut self, byte: u8) -> Option<u8> {
+        let mut adapter = self.adapter.lock().unwrap();
+        let port = &mut adapter.ports[self.player];
+        if port.delivered.is_some() {
+            return port.delivered.take();
+        }
+        port.listening = Some(byte);
+        None
+    }
+
+    /// The adapter keeps pace with player 1.
+    fn sync(&mut self, cycles: u64) {
+        if self.player != 0 {
+            return;
+        }
+        let mut adapter = self.adapter.lock().unwrap();
+        while cycles >= adapter.next_byte {
+            adapter.clock_byte();
+            adapter.next_byte += adapter.period();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Has each player offer a byte, then clocks one, returning the bytes received.
+    fn exchange(ports: &mut [AdapterPort; PLAYERS], bytes: [u8; PLAYERS]) -> [Option<u8>; PLAYERS] {
+        for (port, byte) in ports.iter_mut().zip(bytes) {
+            assert_eq!(port.poll_external(byte), None);
+        }
+        ports[0].adapter.lock().unwrap().clock_byte();
+        std::array::from_fn(|player| ports[player].poll_external(bytes[player]))
+    }
+
+    #[test]
+    fn test_ping_and_transmission() {
+        let mut ports = adapter();
+        // Players 1 and 3 answer the pings, player 1 asking for packets of 2 bytes
+        let answers = [
+            [ACK, 0, ACK, 0],
+            [ACK, 0, ACK, 0],
+            [0x00, 0, 0, 0],
+            [0x02, 0, 0, 0],
+        ];
+        for answer in answers {
+            exchange(&mut ports, answer);
+        }
+        assert_eq!(exchange(&mut ports, answers[0])[2], Some(PING_HEADER));
+        assert_eq!(exchange(&mut ports, answers[1])[2], Some(0b0101_0000 | 3));
+        exchange(&mut ports, answers[2]);
+        exchange(&mut ports, answers[3]);
+
+        assert_eq!(exchange(&mut ports, [START, 0, 0, 0])[0], Some(PING_HEADER));
+        for _ in 0..STARTING_LENGTH {
+            assert_eq!(exchange(&mut ports, [0; 4]), [Some(STARTING); 4]);
+        }
+
+        // Every player receives the packets of the connected players one frame later
+        let sent = [[0x11, 0x21, 0x31, 0x41], [0x12, 0x22, 0x32, 0x42]];
+        for bytes in sent.iter().chain([[0; 4]; 6].iter()) {
+            exchange(&mut ports, *bytes);
+        }
+        let received: Vec<_> = (0..8)
+            .map(|_| exchange(&mut ports, [0; 4])[1].unwrap())
+            .collect();
+        assert_eq!(received, [0x11, 0x12, 0, 0, 0x31, 0x32, 0, 0]);
+    }
+}
@@ -0,0 +1,139 @@
+use std::sync::{Arc, Mutex};
+
+use super::infrared;
+use super::serial::SerialDevice;
+use super::GameBoy;
+
+mod adapter;
+pub use adapter::*;
+
+mod socket;
+pub use socket::*;
+
+/// The state of one end of a link cable.
+#[derive(Debug, Default)]
+struct End {
+    /// The byte offered while waiting for a transfer on the external clock.
+    listening: Option<u8>,
+    /// The byte being shifted in by the other end, until its transfer finishes.
+    incoming: Option<u8>,
+    /// The byte shifted in by the other end, waiting to be picked up.
+    delivered: Option<u8>,
+}
+
+/// One end of a link cable, plugged into the link port of a `GameBoy`.
+///
+/// The end driving the clock swaps bytes with the other end if it is waiting
+/// on the external clock, which then receives its byte when the transfer
+/// finishes, as if the bits had been shifted over the cable. If it isn't
+/// waiting, the bytes shifted in are all 1s, as with no cable plugged in.
+#[derive(Debug, Clone)]
+pub struct LinkPort {
+    ends: Arc<Mutex<[End; 2]>>,
+    /// The index of this end in `ends`.
+    index: usize,
+}
+
+/// Returns the two ends of a new link cable.
+pub fn cable() -> (LinkPort, LinkPort) {
+    let ends = Arc::new(Mutex::new(Default::default()));
+    (
+        LinkPort {
+            ends: ends.clone(),
+            index: 0,
+        },
+        LinkPort { ends, index: 1 },
+    )
+}
+
+impl SerialDevice for LinkPort {
+    fn transfer(&mut self, byte: u8) -> u8 {
+        let mut ends = self.ends.lock().unwrap();
+        let other = &mut ends[1 - self.index];
+        match other.listening.take() {
+            Some(received) => {
+                other.incoming = Some(byte);
+                received
+            }
+            None => 0xff,
+        }
+    }
+
+    fn transfer_finished(&mut self) {
+        let mut ends = self.ends.lock().unwrap();
+        let other = &mut ends[1 - self.index];
+        other.delivered = other.incoming.take().or(other.delivered);
+    }
+
+    fn poll_external(&mut self, byte: u8) -> Option<u8> {
+        let mut ends = self.ends.lock().unwrap();
+        let end = &mut ends[self.index];
+        if end.delivered.is_some() {
+            return end.delivered.take();
+        }
+        if end.incoming.is_none() {
+            end.listening = Some(byte);
+        }
+        None
+    }
+}
+
+/// Connects the link ports of two `GameBoy`s with a new cable, and points
+/// their infrared ports at each other.
+pub fn connect(first: &mut GameBoy, second: &mut GameBoy) {
+    let (first_port, second_port) = cable();
+    first.set_serial_device(Box::new(first_port));
+    second.set_serial_device(Box::new(second_port));
+
+    let (first_port, second_port) = infrared::pair();
+    first.set_infrared_device(Box::new(first_port));
+    second.set_infrared_device(Box::new(second_port));
+}
+
+/// Steps the `GameBoy` that is furthest behind, so that linked instances stay
+/// within an instruction of each other and their transfers line up.
+pub fn step_lockstep(gameboys: &mut [GameBoy]) {
+    let behind = gameboys.iter_mut().min_by_key(|gameboy| gameboy.cpu.cycles);
+    if let Some(gameboy) = behind {
+        gameboy.step();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gameboy::interrupts::{IF_ADDR, SERIAL};
+    use crate::gameboy::memory::{Memory, SB_ADDR, SC_ADDR};
+
+    #[test]
+    fn test_transfer_over_cable() {
+        let (first_port, second_port) = cable();
+        let mut master = Memory::new();
+        let mut slave = Memory::new();
+        master.serial.set_device(Box::new(first_port));
+        slave.serial.set_device(Box::new(second_port));
+
+        slave.write_byte(SB_ADDR, 0x34);
+        slave.write_byte(SC_ADDR, 0x80);
+        slave.tick(4);
+
+        master.write_byte(SB_ADDR, 0x12);
+        master.write_byte(SC_ADDR, 0x81);
+        master.tick(8 * 512);
+        assert_eq!(master.read_byte(SB_ADDR), 0x34);
+        assert_eq!(master.read_byte(IF_ADDR) & SERIAL, SERIAL);
+
+        // The slave only receives the byte once the master has shifted it all
+        assert_eq!(slave.read_byte(IF_ADDR) & SERIAL, 0);
+        slave.tick(4);
+        assert_eq!(slave.read_byte(SB_ADDR), 0x12);
+        assert_eq!(slave.read_byte(IF_ADDR) & SERIAL, SERIAL);
+        assert_eq!(slave.read_byte(SC_ADDR), 0x7e);
+    }
+
+    #[test]
+    fn test_no_listener_reads_ones() {
+        let (mut first_port, _second_port) = cable();
+        assert_eq!(first_port.transfer(0x12), 0xff);
+    }
+}
@@ -0,0 +1,451 @@
+use std::fmt;
+use std::io::{self, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::thread;
+use std::time::Duration;
+
+#[cfg(unix)]
+use std::os::unix::net::{UnixListener, UnixStream};
+
+use crate::gameboy::infrared::InfraredDevice;
+use crate::gameboy::serial::SerialDevice;
+
+/// The prefix of the addresses of Unix domain sockets, e.g. `unix:/tmp/discogb.sock`.
+pub const UNIX_PREFIX: &str = "unix:";
+
+/// The number of cycles between two reports of the cycle count to the peer.
+const SYNC_INTERVAL: u64 = 1024;
+/// The number of cycles one end may run ahead of the last count reported by the other.
+const SYNC_WINDOW: u64 = 4 * SYNC_INTERVAL;
+/// How often a wait for the other end checks whether the emulation was stopped.
+const STOP_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// The messages of the lockstep protocol, each a tag byte and its payload.
+const SYNC: u8 = 0x01;
+const TRANSFER: u8 = 0x02;
+const REPLY: u8 = 0x03;
+const FINISHED: u8 = 0x04;
+const LED: u8 = 0x05;
+
+/// A message exchanged with the other end.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Message {
+    /// The sender has run this many cycles.
+    Sync(u64),
+    /// The sender started a transfer on its internal clock, shifting out this byte.
+    Transfer(u8),
+    /// The byte shifted out by the receiver of a `Transfer`.
+    Reply(u8),
+    /// The sender finished shifting the byte of its last `Transfer`.
+    Finished,
+    /// The sender turned its infrared LED on or off.
+    Led(bool),
+}
+
+impl Message {
+    fn write_to(self, writer: &mut impl Write) -> io::Result<()> {
+        match self {
+            Message::Sync(cycles) => {
+                writer.write_all(&[SYNC])?;
+                writer.write_all(&cycles.to_le_bytes())?;
+            }
+            Message::Transfer(byte) => writer.write_all(&[TRANSFER, byte])?,
+            Message::Reply(byte) => writer.write_all(&[REPLY, byte])?,
+            Message::Finished => writer.write_all(&[FINISHED])?,
+            Message::Led(on) => writer.write_all(&[LED, on as u8])?,
+        }
+        writer.flush()
+    }
+
+    fn read_from(reader: &mut impl Read) -> io::Result<Self> {
+        let mut tag = [0];
+        reader.read_exact(&mut tag)?;
+        let mut byte = [0];
+        match tag[0] {
+            SYNC => {
+                let mut cycles = [0; 8];
+                reader.read_exact(&mut cycles)?;
+                Ok(Message::Sync(u64::from_le_bytes(cycles)))
+            }
+            TRANSFER => {
+                reader.read_exact(&mut byte)?;
+                Ok(Message::Transfer(byte[0]))
+            }
+            REPLY => {
+                reader.read_exact(&mut byte)?;
+                Ok(Message::Reply(byte[0]))
+            }
+            FINISHED => Ok(Message::Finished),
+            LED => {
+                reader.read_exact(&mut byte)?;
+                Ok(Message::Led(byte[0] != 0))
+            }
+            tag => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("unknown link message {:#04x}", tag),
+            )),
+        }
+    }
+}
+
+/// What is known of the other end, updated by the thread reading its messages.
+#[derive(Debug, Default)]
+struct State {
+    /// The last cycle count reported by the other end.
+    peer_cycles: u64,
+    /// The byte offered while waiting for a transfer on the external clock.
+    listening: Option<u8>,
+    /// The reply to the last `Transfer` sent.
+    reply: Option<u8>,
+    /// The byte being shifted in by the other end, until its transfer finishes.
+    incoming: Option<u8>,
+    /// The byte shifted in by the other end, waiting to be picked up.
+    delivered: Option<u8>,
+    /// Whether the LED of the other end is on.
+    peer_led: bool,
+    /// Whether the connection was closed, after which the cable acts unplugged.
+    closed: bool,
+}
+
+type Writer = Arc<Mutex<Box<dyn Write + Send>>>;
+type SharedState = Arc<(Mutex<State>, Condvar)>;
+
+/// A link cable to another DiscoGB process, over a TCP or Unix domain socket.
+///
+/// Both ends report their cycle count every `SYNC_INTERVAL` cycles and wait
+/// whenever they get more than `SYNC_WINDOW` cycles ahead of the other, so
+/// transfers on the external clock arrive at about the right time. A transfer
+/// then works as with the in-process cable: the bytes are swapped when the
+/// end driving the clock starts it, and delivered to the other end when it
+/// finishes.
+pub struct SocketLink {
+    state: SharedState,
+    writer: Writer,
+    /// The cycle count at which to report to the other end next.
+    next_sync: u64,
+    /// Set when the emulation is stopped, which ends any wait for the other end.
+    stop: Arc<AtomicBool>,
+}
+
+impl fmt::Debug for SocketLink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SocketLink")
+            .field("state", &self.state.0)
+            .field("next_sync", &self.next_sync)
+            .finish()
+    }
+}
+
+impl SocketLink {
+    /// Waits for the other end to connect to the given address, either
+    /// `host:port` or a path prefixed with `unix:`.
+    pub fn listen(addr: &str) -> io::Result<Self> {
+        #[cfg(unix)]
+        if let Some(path) = addr.strip_prefix(UNIX_PREFIX) {
+            remove_stale_socket(path)?;
+            let (stream, _) = UnixListener::bind(path)?.accept()?;
+            return Ok(Self::new(stream.try_clone()?, stream));
+        }
+
+        let (stream, _) = TcpListener::bind(addr)?.accept()?;
+        stream.set_nodelay(true)?;
+        Ok(Self::new(stream.try_clone()?, stream))
+    }
+
+    /// Connects to the other end listening at the given address, either
+    /// `host:port` or a path prefixed with `unix:`.
+    pub fn connect(addr: &str) -> io::Result<Self> {
+        #[cfg(unix)]
+        if let Some(path) = addr.strip_prefix(UNIX_PREFIX) {
+            let stream = UnixStream::connect(path)?;
+            return Ok(Self::new(stream.try_clone()?, stream));
+        }
+
+        let stream = TcpStream::connect(addr)?;
+        stream.set_nodelay(true)?;
+        Ok(Self::new(stream.try_clone()?, stream))
+    }
+
+    /// Creates a link over the given halves of a connection, reading the
+    /// messages of the other end on a new thread.
+    pub fn new(reader: impl Read + Send + 'static, writer: impl Write + Send + 'static) -> Self {
+        let state = Arc::new((Mutex::new(State::default()), Condvar::new()));
+        let writer: Writer = Arc::new(Mutex::new(Box::new(writer)));
+
+        let (reader_state, reader_writer) = (state.clone(), writer.clone());
+        thread::Builder::new()
+            .name("Link Cable".to_string())
+            .spawn(move || {
+                if let Err(err) = read_messages(reader, &reader_state, &reader_writer) {
+                    eprintln!("Link cable disconnected: {}", err);
+                }
+                reader_state.0.lock().unwrap().closed = true;
+                reader_state.1.notify_all();
+            })
+            .unwrap();
+
+        Self {
+            state,
+            writer,
+            next_sync: 0,
+            stop: Arc::default(),
+        }
+    }
+
+    /// Returns the infrared port facing that of the other end, over the same connection.
+    ///
+    /// The ends only keep within `SYNC_WINDOW` cycles of each other, which is
+    /// longer than the pulses of most infrared protocols, so games timing them
+    /// may not work.
+    pub fn infrared(&self) -> SocketInfrared {
+        SocketInfrared {
+            state: self.state.clone(),
+            writer: self.writer.clone(),
+            led: false,
+        }
+    }
+
+    /// Sends a message to the other end, closing the link if that fails.
+    fn send(&self, message: Message) {
+        send_or_close(&self.state, &self.writer, message);
+    }
+
+    /// Waits while the given condition holds, the connection is open and the
+    /// emulation was not stopped, returning the state.
+    fn wait_while(&self, mut condition: impl FnMut(&mut State) -> bool) -> MutexGuard<'_, State> {
+        let (state, changed) = &*self.state;
+        let mut state = state.lock().unwrap();
+        while !self.stop.load(Ordering::Relaxed) {
+            let (guard, _) = changed
+                .wait_timeout_while(state, STOP_POLL_INTERVAL, |state| {
+                    condition(state) && !state.closed
+                })
+                .unwrap();
+            state = guard;
+            if !condition(&mut state) || state.closed {
+                break;
+            }
+        }
+        state
+    }
+}
+
+/// Removes the socket left at the given path by a previous run, which would
+/// make binding fail. Anything else found there is left alone.
+#[cfg(unix)]
+fn remove_stale_socket(path: &str) -> io::Result<()> {
+    use std::os::unix::fs::FileTypeExt;
+
+    match std::fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.file_type().is_socket() => std::fs::remove_file(path),
+        Ok(_) => Err(io::Error::new(
+            io::ErrorKind::AddrInUse,
+            format!("{} exists and is not a socket", path),
+        )),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(()),
+        Err(err) => Err(err),
+    }
+}
+
+fn send(writer: &Writer, message: Message) -> io::Result<()> {
+    message.write_to(&mut *writer.lock().unwrap())
+}
+
+/// Sends a message to the other end, marking the connection closed if that fails.
+fn send_or_close(state: &SharedState, writer: &Writer, message: Message) {
+    if let Err(err) = send(writer, message) {
+        eprintln!("Link cable disconnected: {}", err);
+        state.0.lock().unwrap().closed = true;
+        state.1.notify_all();
+    }
+}
+
+/// Handles the messages of the other end until the connection is closed.
+fn read_messages(
+    reader: impl Read,
+    state: &(Mutex<State>, Condvar),
+    writer: &Writer,
+) -> io::Result<()> {
+    let mut reader = BufReader::new(reader);
+    loop {
+        let message = Message::read_from(&mut reader)?;
+        let mut state_guard = state.0.lock().unwrap();
+        match message {
+            Message::Sync(cycles) => state_guard.peer_cycles = cycles,
+            Message::Transfer(byte) => {
+                let reply = match state_guard.listening.take() {
+                    Some(listening) => {
+                        state_guard.incoming = Some(byte);
+                        listening
+                    }
+                    None => 0xff,
+                };
+                drop(state_guard);
+                send(writer, Message::Reply(reply))?;
+                continue;
+            }
+            Message::Reply(byte) => state_guard.reply = Some(byte),
+            Message::Finished => {
+                state_guard.delivered = state_guard.incoming.take().or(state_guard.delivered);
+            }
+            Message::Led(on) => state_guard.peer_led = on,
+        }
+        state.1.notify_all();
+    }
+}
+
+impl SerialDevice for SocketLink {
+    fn transfer(&mut self, byte: u8) -> u8 {
+        self.send(Message::Transfer(byte));
+        let mut state = self.wait_while(|state| state.reply.is_none());
+        state.reply.take().unwrap_or(0xff)
+    }
+
+    fn transfer_finished(&mut self) {
+        self.send(Message::Finished);
+    }
+
+    fn poll_external(&mut self, byte: u8) -> Option<u8> {
+        let mut state = self.state.0.lock().unwrap();
+        if state.delivered.is_some() {
+            return state.delivered.take();
+        }
+        if state.incoming.is_none() {
+            state.listening = Some(byte);
+        }
+        None
+    }
+
+    fn sync(&mut self, cycles: u64) {
+        // The cycle count goes back when an earlier state is loaded
+        if cycles + SYNC_INTERVAL < self.next_sync {
+            self.next_sync = cycles;
+        }
+        if cycles < self.next_sync {
+            return;
+        }
+        self.next_sync = cycles + SYNC_INTERVAL;
+        self.send(Message::Sync(cycles));
+        let _state = self.wait_while(|state| state.peer_cycles + SYNC_WINDOW < cycles);
+    }
+
+    fn set_stop_flag(&mut self, stop: Arc<AtomicBool>) {
+        self.stop = stop;
+    }
+}
+
+/// The infrared port of a `GameBoy` facing that of another DiscoGB process,
+/// sharing the connection of a `SocketLink`.
+pub struct SocketInfrared {
+    state: SharedState,
+    writer: Writer,
+    /// Whether the LED of this end is on.
+    led: bool,
+}
+
+impl fmt::Debug for SocketInfrared {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SocketInfrared")
+            .field("led", &self.led)
+            .finish()
+    }
+}
+
+impl InfraredDevice for SocketInfrared {
+    fn set_led(&mut self, on: bool) {
+        if on != self.led {
+            self.led = on;
+            send_or_close(&self.state, &self.writer, Message::Led(on));
+        }
+    }
+
+    fn light(&mut self) -> bool {
+        self.state.0.lock().unwrap().peer_led
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_message_round_trip() {
+        let messages = [
+            Message::Sync(0x0123_4567_89ab),
+            Message::Transfer(0x42),
+            Message::Reply(0xff),
+            Message::Finished,
+            Message::Led(true),
+        ];
+        let mut bytes = Vec::new();
+        for message in messages {
+            message.write_to(&mut bytes).unwrap();
+        }
+
+        let mut reader = bytes.as_slice();
+        for message in messages {
+            assert_eq!(Message::read_from(&mut reader).unwrap(), message);
+        }
+        assert!(Message::read_from(&mut reader).is_err());
+    }
+
+    #[test]
+    fn test_transfer_over_tcp() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        let accepted = thread::spawn(move || listener.accept().unwrap().0);
+        let mut master = SocketLink::connect(&addr).unwrap();
+        let stream = accepted.join().unwrap();
+        let mut slave = SocketLink::new(stream.try_clone().unwrap(), stream);
+
+        assert_eq!(slave.poll_external(0x34), None);
+        assert_eq!(master.transfer(0x12), 0x34);
+        assert_eq!(slave.poll_external(0x34), None);
+        master.transfer_finished();
+
+        // The slave receives the byte once the master's message has arrived
+        let received = (0..1000).find_map(|_| {
+            thread::sleep(std::time::Duration::from_millis(1));
+            slave.poll_external(0x34)
+        });
+        assert_eq!(received, Some(0x12));
+    }
+
+    #[test]
+    fn test_stop_ends_wait() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        let accepted = thread::spawn(move || listener.accept().unwrap().0);
+        let mut link = SocketLink::connect(&addr).unwrap();
+        let _peer = accepted.join().unwrap();
+
+        // The other end never reports its cycles nor replies
+        let stop = Arc::new(AtomicBool::new(false));
+        link.set_stop_flag(stop.clone());
+        let setter = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(20));
+            stop.store(true, Ordering::Relaxed);
+        });
+        link.sync(2 * SYNC_WINDOW);
+        assert_eq!(link.transfer(0x12), 0xff);
+        setter.join().unwrap();
+
+        // Going back in time reports the new count straight away
+        link.sync(SYNC_INTERVAL);
+        assert_eq!(link.next_sync, 2 * SYNC_INTERVAL);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_listen_keeps_other_files() {
+        let path = std::env::temp_dir().join(format!("discogb-link-{}", std::process::id()));
+        std::fs::write(&path, b"data").unwrap();
+        let addr = format!("{}{}", UNIX_PREFIX, path.display());
+        let err = SocketLink::listen(&addr).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::AddrInUse);
+        assert_eq!(std::fs::read(&path).unwrap(), b"data");
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,99 @@
+pub const HDMA1_ADDR: u16 = 0xff51;
+pub const HDMA2_ADDR: u16 = 0xff52;
+pub const HDMA3_ADDR: u16 = 0xff53;
+pub const HDMA4_ADDR: u16 = 0xff54;
+pub const HDMA5_ADDR: u16 = 0xff55;
+
+/// The number of bytes copied per block (and per HBlank).
+pub const HDMA_BLOCK_SIZE: u16 = 0x10;
+/// The number of CPU cycles a block takes to copy, in normal and double speed mode.
+const BLOCK_CYCLES: u32 = 32;
+const BLOCK_CYCLES_DOUBLE_SPEED: u32 = 64;
+
+/// The CGB VRAM DMA controller (`HDMA1`-`HDMA5`).
+#[derive(Debug, Default, Clone)]
+pub struct Hdma {
+    /// The address the next block is copied from.
+    pub source: u16,
+    /// The address the next block is copied to, always in VRAM.
+    pub destination: u16,
+    /// The number of blocks left, minus one, as reported by `HDMA5`.
+    pub length: u8,
+    /// Whether an HBlank DMA is in progress.
+    pub hblank_active: bool,
+}
+
+/// A transfer requested by a write to `HDMA5`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum HdmaRequest {
+    /// Copy the given number of blocks at once.
+    General(u8),
+    /// Copy one block at the start of each HBlank.
+    HBlank,
+    /// Stop the current HBlank DMA.
+    Cancel,
+}
+
+impl Hdma {
+    /// Returns the value of the given `HDMA` register.
+    /// Only `HDMA5` can be read; the address registers read as 0xff.
+    pub fn read_register(&self, addr: u16) -> u8 {
+        match addr {
+            HDMA5_ADDR if self.hblank_active => self.length,
+            HDMA5_ADDR => 0b1000_0000 | self.length,
+            _ => 0xff,
+        }
+    }
+
+    /// Writes the given `HDMA` register, returning the transfer requested by
+    /// a write to `HDMA5`, if any.
+    pub fn write_register(&mut self, addr: u16, value: u8) -> Option<HdmaRequest> {
+        match addr {
+            HDMA1_ADDR => self.source = (value as u16) << 8 | (self.source & 0x00f0),
+            HDMA2_ADDR => self.source = (self.source & 0xff00) | (value & 0xf0) as u16,
+            HDMA3_ADDR => {
+                self.destination =
+                    0x8000 | ((value & 0x1f) as u16) << 8 | (self.destination & 0x00f0)
+            }
+            HDMA4_ADDR => self.destination = (self.destination & 0xff00) | (value & 0xf0) as u16,
+            HDMA5_ADDR => {
+                // Cancelling keeps the remaining length, so it can be read back
+                if self.hblank_active && value & 0b1000_0000 == 0 {
+                    self.hblank_active = false;
+                    return Some(HdmaRequest::Cancel);
+                }
+
+                self.length = value & 0b0111_1111;
+                return Some(if value & 0b1000_0000 != 0 {
+                    self.hblank_active = true;
+                    HdmaRequest::HBlank
+                } else {
+                    HdmaRequest::General(self.length + 1)
+                });
+            }
+            _ => panic!("Attempted to write to non-HDMA register {:#06x}", addr),
+        }
+        None
+    }
+
+    /// Moves on to the next block once one has been copied.
+    pub fn finish_block(&mut self) {
+        self.source = self.source.wrapping_add(HDMA_BLOCK_SIZE);
+        self.destination = 0x8000 | (self.destination.wrapping_add(HDMA_BLOCK_SIZE) & 0x1ff0);
+        self.length = self.length.wrapping_sub(1) & 0b0111_1111;
+
+        // The length wraps around to 0x7f once the last block has been copied
+        if self.length == 0b0111_1111 {
+            self.hblank_active = false;
+        }
+    }
+}
+
+/// Returns the number of CPU cycles the CPU is stalled for while a block is copied.
+pub fn block_cycles(double_speed: bool) -> u32 {
+    if double_speed {
+        BLOCK_CYCLES_DOUBLE_SPEED
+    } else {
+        BLOCK_CYCLES
+    }
+}
@@ -0,0 +1,103 @@
+use std::sync::Mutex;
+
+use crate::gameboy::infrared::{ConstantLight, InfraredDevice};
+
+pub const RP_ADDR: u16 = 0xff56;
+
+/// The bit of `RP` that turns the LED on.
+const LED: u8 = 0b0000_0001;
+/// The bit of `RP` that reads 0 while light is received.
+const NO_LIGHT: u8 = 0b0000_0010;
+/// The bits of `RP` that must both be set to read the receiver.
+const READ_ENABLE: u8 = 0b1100_0000;
+
+/// The infrared port of the CGB, read and written through `RP`.
+#[derive(Debug)]
+pub struct Infrared {
+    /// The LED and read enable bits written to `RP`.
+    rp: u8,
+    /// What the port faces, locked so that reading `RP` can poll it.
+    device: Mutex<Box<dyn InfraredDevice>>,
+}
+
+/// A clone faces nothing, so that snapshots can't drive the device of the
+/// `Infrared` they were taken from.
+impl Clone for Infrared {
+    fn clone(&self) -> Self {
+        Self {
+            rp: self.rp,
+            ..Self::default()
+        }
+    }
+}
+
+impl Default for Infrared {
+    fn default() -> Self {
+        Self {
+            rp: 0,
+            device: Mutex::new(Box::new(ConstantLight::default())),
+        }
+    }
+}
+
+impl Infrared {
+    /// Points the port at the given device, replacing the previous one.
+    pub fn set_device(&mut self, device: Box<dyn InfraredDevice>) {
+        *self.device.get_mut().unwrap() = device;
+    }
+
+    /// Points the port away from its device, returning it.
+    pub fn take_device(&mut self) -> Box<dyn InfraredDevice> {
+        std::mem::replace(
+            self.device.get_mut().unwrap(),
+            Box::new(ConstantLight::default()),
+        )
+    }
+
+    /// Returns the value of `RP`.
+    pub fn read(&self) -> u8 {
+        let receiving = self.rp & READ_ENABLE == READ_ENABLE && self.device.lock().unwrap().light();
+        let no_light = if receiving { 0 } else { NO_LIGHT };
+        0b0011_1100 | self.rp | no_light
+    }
+
+    /// Writes `RP`, turning the LED on or off.
+    pub fn write(&mut self, value: u8) {
+        let led = self.rp & LED;
+        self.rp = value & (READ_ENABLE | LED);
+        if self.rp & LED != led {
+            self.device.get_mut().unwrap().set_led(self.rp & LED != 0);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rp_reads_light() {
+        let mut infrared = Infrared::default();
+        infrared.set_device(Box::new(ConstantLight { on: true }));
+        assert_eq!(infrared.read(), 0b0011_1110);
+
+        // The receiver only reads light while enabled
+        infrared.write(0xc1);
+        assert_eq!(infrared.read(), 0b1111_1101);
+        infrared.set_device(Box::new(ConstantLight { on: false }));
+        assert_eq!(infrared.read(), 0b1111_1111);
+    }
+
+    #[test]
+    fn test_clone_faces_nothing() {
+        let mut infrared = Infrared::default();
+        infrared.write(0xc0);
+        infrared.set_device(Box::new(ConstantLight { on: true }));
+
+        let mut clone = infrared.clone();
+        assert_eq!(clone.read(), 0b1111_1110);
+        clone.set_device(Box::new(ConstantLight { on: true }));
+        assert_eq!(clone.read(), 0b1111_1100);
+        assert!(infrared.take_device().light());
+    }
+}
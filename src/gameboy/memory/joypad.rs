@@ -0,0 +1,168 @@
+use crate::gameboy::interrupts::JOYPAD;
+
+pub const JOYP_ADDR: u16 = 0xff00;
+
+/// The line of `P1` that selects the d-pad when low (P14).
+const SELECT_DIRECTIONS: u8 = 0b0001_0000;
+/// The line of `P1` that selects the buttons when low (P15).
+const SELECT_ACTIONS: u8 = 0b0010_0000;
+
+/// A button of the joypad.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Button {
+    A,
+    B,
+    Select,
+    Start,
+    Right,
+    Left,
+    Up,
+    Down,
+}
+
+impl Button {
+    pub const ALL: [Button; 8] = [
+        Button::A,
+        Button::B,
+        Button::Select,
+        Button::Start,
+        Button::Right,
+        Button::Left,
+        Button::Up,
+        Button::Down,
+    ];
+}
+
+/// The state of the joypad: `true` for each pressed button.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct Buttons {
+    pub a: bool,
+    pub b: bool,
+    pub select: bool,
+    pub start: bool,
+    pub right: bool,
+    pub left: bool,
+    pub up: bool,
+    pub down: bool,
+}
+
+impl Buttons {
+    /// Presses or releases the given button.
+    pub fn set(&mut self, button: Button, pressed: bool) {
+        let state = match button {
+            Button::A => &mut self.a,
+            Button::B => &mut self.b,
+            Button::Select => &mut self.select,
+            Button::Start => &mut self.start,
+            Button::Right => &mut self.right,
+            Button::Left => &mut self.left,
+            Button::Up => &mut self.up,
+            Button::Down => &mut self.down,
+        };
+        *state = pressed;
+    }
+
+    /// Returns the pressed buttons as the low nibble of `P1`, with 1 for each pressed one.
+    fn action_bits(&self) -> u8 {
+        (self.a as u8) | (self.b as u8) << 1 | (self.select as u8) << 2 | (self.start as u8) << 3
+    }
+
+    /// Returns the pressed directions as the low nibble of `P1`, with 1 for each pressed one.
+    fn direction_bits(&self) -> u8 {
+        (self.right as u8) | (self.left as u8) << 1 | (self.up as u8) << 2 | (self.down as u8) << 3
+    }
+}
+
+/// The joypad, read through the `P1` register.
+#[derive(Debug, Clone)]
+pub struct Joypad {
+    /// The select lines written to `P1` (bits 4-5), active low.
+    select: u8,
+    pub buttons: Buttons,
+}
+
+impl Default for Joypad {
+    fn default() -> Self {
+        Self {
+            select: SELECT_DIRECTIONS | SELECT_ACTIONS,
+            buttons: Buttons::default(),
+        }
+    }
+}
+
+impl Joypad {
+    /// Returns the value of `P1`. A button reads as 0 while pressed and selected.
+    pub fn read(&self) -> u8 {
+        0b1100_0000 | self.select | self.input_lines()
+    }
+
+    /// Writes the select lines of `P1`. Returns the interrupts to request.
+    pub fn write(&mut self, value: u8) -> u8 {
+        let lines = self.input_lines();
+        self.select = value & (SELECT_DIRECTIONS | SELECT_ACTIONS);
+        self.interrupts(lines)
+    }
+
+    /// Updates the pressed buttons. Returns the interrupts to request.
+    pub fn set_buttons(&mut self, buttons: Buttons) -> u8 {
+        let lines = self.input_lines();
+        self.buttons = buttons;
+        self.interrupts(lines)
+    }
+
+    /// Returns the input lines P10-P13, where a pressed button of a selected group pulls its line low.
+    fn input_lines(&self) -> u8 {
+        let mut pressed = 0;
+        if self.select & SELECT_DIRECTIONS == 0 {
+            pressed |= self.buttons.direction_bits();
+        }
+        if self.select & SELECT_ACTIONS == 0 {
+            pressed |= self.buttons.action_bits();
+        }
+        !pressed & 0x0f
+    }
+
+    /// Returns the joypad interrupt if an input line went from high to low since it was `previous`.
+    fn interrupts(&self, previous: u8) -> u8 {
+        if previous & !self.input_lines() != 0 {
+            JOYPAD
+        } else {
+            0
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_select_lines() {
+        let mut joypad = Joypad::default();
+        let buttons = Buttons {
+            a: true,
+            up: true,
+            ..Default::default()
+        };
+        // Nothing is selected, so pressing buttons doesn't change the lines
+        assert_eq!(joypad.set_buttons(buttons), 0);
+        assert_eq!(joypad.read(), 0xff);
+
+        assert_eq!(joypad.write(0x20), JOYPAD);
+        assert_eq!(joypad.read(), 0b1110_1011);
+        assert_eq!(joypad.write(0x10), JOYPAD);
+        assert_eq!(joypad.read(), 0b1101_1110);
+    }
+
+    #[test]
+    fn test_interrupt_on_press_only() {
+        let mut joypad = Joypad::default();
+        joypad.write(0x10);
+        let start = Buttons {
+            start: true,
+            ..Default::default()
+        };
+        assert_eq!(joypad.set_buttons(start), JOYPAD);
+        assert_eq!(joypad.set_buttons(Buttons::default()), 0);
+    }
+}
@@ -1,3 +1,28 @@
+use super::apu::{self, Apu};
+use super::interrupts::{IF_ADDR, VBLANK};
+use super::ppu::{self, Ppu};
+use super::scheduler::{self, Event, Scheduler};
+use super::sgb::Sgb;
+use super::Model;
+
+mod hdma;
+pub use hdma::*;
+
+mod infrared;
+pub use infrared::*;
+
+mod joypad;
+pub use joypad::*;
+
+mod oam_dma;
+pub use oam_dma::*;
+
+mod serial;
+pub use serial::*;
+
+mod timer;
+pub use timer::*;
+
 pub const ROM_SIZE: u16 = 0x8000;
 pub const VRAM_SIZE: u16 = 0x2000;
 pub const ERAM_SIZE: u16 = 0x2000;
@@ -23,11 +48,24 @@ pub const VRAM_ADDR_END: u16 = 0x9fff;
 pub const ERAM_ADDR_END: u16 = 0xbfff;
 pub const WRAM_ADDR_END: u16 = 0xdfff;
 pub const ECHO_ADDR_END: u16 = 0xfdff;
-pub const OAM_ADDR_END: u16 = 0xFe9f;
+pub const OAM_ADDR_END: u16 = 0xfe9f;
 pub const UNUSED_ADDR_END: u16 = 0xfeff;
 pub const IO_ADDR_END: u16 = 0xff7f;
 pub const HRAM_ADDR_END: u16 = 0xfffe;
 
+pub const KEY0_ADDR: u16 = 0xff4c;
+pub const KEY1_ADDR: u16 = 0xff4d;
+pub const VBK_ADDR: u16 = 0xff4f;
+pub const BOOT_ADDR: u16 = 0xff50;
+pub const SVBK_ADDR: u16 = 0xff70;
+
+/// The number of switchable WRAM banks of the CGB.
+pub const WRAM_BANKS: usize = 7;
+/// The size of the boot ROM overlay at the start of the address space.
+pub const BOOT_ROM_SIZE: usize = 0x100;
+/// The T-cycles the CPU is stopped for while the speed switches (2050 M-cycles).
+pub const SPEED_SWITCH_CYCLES: u32 = 8200;
+
 const BOOT_ROM: [u8; 256] = [
     0x31, 0xfe, 0xff, 0xaf, 0x21, 0xff, 0x9f, 0x32, 0xcb, 0x7c, 0x20, 0xfb, 0x21, 0x26, 0xff, 0x0e,
     0x11, 0x3e, 0x80, 0x32, 0xe2, 0x0c, 0x3e, 0xf3, 0xe2, 0x32, 0x3e, 0x77, 0x77, 0x3e, 0xfc, 0xe0,
@@ -47,32 +85,47 @@ const BOOT_ROM: [u8; 256] = [
     0xf5, 0x06, 0x19, 0x78, 0x86, 0x23, 0x05, 0x20, 0xfb, 0x86, 0x20, 0xfe, 0x3e, 0x01, 0xe0, 0x50,
 ];
 
-/// Represents a banked memory region.
+/// Represents a banked memory region: a fixed bank in the lower half, and
+/// one of several switchable banks in the upper half.
 #[derive(Debug, Clone)]
 pub struct BankedMemory {
     pub bank0: Vec<u8>,
-    pub bankn: Vec<u8>,
+    /// The switchable banks.
+    pub banks: Vec<Vec<u8>>,
+    /// The index (into `banks`) of the bank mapped in the upper half.
+    pub selected: usize,
     size: u16,
     offset: u16,
 }
 
 impl BankedMemory {
-    /// Creates a new banked memory region with the given size.
-    fn new(size: u16, offset: u16) -> Self {
+    /// Creates a new banked memory region with the given size and number of switchable banks.
+    fn new(size: u16, offset: u16, switchable_banks: usize) -> Self {
         Self {
             bank0: vec![0; size as usize / 2],
-            bankn: vec![0; size as usize / 2],
+            banks: vec![vec![0; size as usize / 2]; switchable_banks],
+            selected: 0,
             size,
             offset,
         }
     }
 
+    /// Returns the switchable bank currently mapped in the upper half.
+    pub fn bankn(&self) -> &[u8] {
+        &self.banks[self.selected]
+    }
+
+    /// Maps the switchable bank with the given index in the upper half.
+    pub fn select(&mut self, index: usize) {
+        self.selected = index % self.banks.len();
+    }
+
     /// Returns the byte at the given address, taking into account the offset.
     pub fn read(&self, addr: u16) -> u8 {
         if addr - self.offset < self.size / 2 {
             self.bank0[(addr - self.offset) as usize]
         } else {
-            self.bankn[(addr - self.offset - self.size / 2) as usize]
+            self.banks[self.selected][(addr - self.offset - self.size / 2) as usize]
         }
     }
 
@@ -81,7 +134,7 @@ impl BankedMemory {
         if addr - self.offset < self.size / 2 {
             self.bank0[(addr - self.offset) as usize] = value;
         } else {
-            self.bankn[(addr - self.offset - self.size / 2) as usize] = value;
+            self.banks[self.selected][(addr - self.offset - self.size / 2) as usize] = value;
         }
     }
 }
@@ -90,27 +143,93 @@ impl BankedMemory {
 #[derive(Debug, Clone)]
 pub struct Memory {
     pub rom: BankedMemory,
-    pub vram: [u8; VRAM_SIZE as usize],
+    /// Both VRAM banks; bank 1 (CGB only) follows bank 0.
+    pub vram: [u8; VRAM_SIZE as usize * 2],
+    /// The VRAM bank mapped at 0x8000 (`VBK`).
+    pub vram_bank: usize,
     pub eram: [u8; ERAM_SIZE as usize],
     pub wram: BankedMemory,
+    /// The bank number written to `SVBK` (CGB only), where 0 selects bank 1.
+    pub svbk: u8,
     pub oam: [u8; OAM_SIZE as usize],
     pub io: [u8; IO_SIZE as usize],
     pub hram: [u8; HRAM_SIZE as usize],
     pub ie: u8,
+    pub ppu: Ppu,
+    /// Whether the CGB features (banking, double speed, colour) are enabled.
+    pub cgb: bool,
+    /// Whether the CPU runs at double speed (CGB only).
+    pub double_speed: bool,
+    /// Whether a speed switch has been requested through `KEY1`.
+    pub speed_switch_armed: bool,
+    /// The cartridge bytes hidden behind the boot ROM until it is unmapped.
+    boot_rom_shadow: Option<Vec<u8>>,
+    /// The VRAM DMA controller (CGB only).
+    pub hdma: Hdma,
+    /// The OAM DMA transfer in progress, if any.
+    pub oam_dma: Option<OamDma>,
+    /// The number of cycles the CPU is stalled for by DMA transfers.
+    stall_cycles: u32,
+    /// Whether the DMG OAM corruption bug is emulated.
+    pub oam_corruption: bool,
+    /// The Super GameBoy, when running a game that supports it on one.
+    pub sgb: Option<Sgb>,
+    /// The audio processing unit.
+    pub apu: Apu,
+    /// The joypad, read through `P1`.
+    pub joypad: Joypad,
+    /// The timer, which also drives `DIV`.
+    pub timer: Timer,
+    /// The serial port, clocked by the timer's system counter.
+    pub serial: Serial,
+    /// The infrared port (CGB only).
+    pub infrared: Infrared,
+    /// The time, and the events of the components due later.
+    pub scheduler: Scheduler,
+    /// The time up to which the timer has been advanced.
+    timer_synced: u64,
+    /// The time up to which the PPU has been advanced.
+    ppu_synced: u64,
+}
+
+impl Default for Memory {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Memory {
     /// Creates a new `Memory` instance.
     pub fn new() -> Self {
         Self {
-            rom: BankedMemory::new(ROM_SIZE, ROM_ADDR),
-            vram: [0; VRAM_SIZE as usize],
+            rom: BankedMemory::new(ROM_SIZE, ROM_ADDR, 1),
+            vram: [0; VRAM_SIZE as usize * 2],
+            vram_bank: 0,
             eram: [0; ERAM_SIZE as usize],
-            wram: BankedMemory::new(WRAM_SIZE, WRAM_ADDR),
+            wram: BankedMemory::new(WRAM_SIZE, WRAM_ADDR, WRAM_BANKS),
+            svbk: 0,
             oam: [0; OAM_SIZE as usize],
             io: [0; IO_SIZE as usize],
             hram: [0; HRAM_SIZE as usize],
             ie: 0,
+            ppu: Ppu::new(),
+            cgb: false,
+            double_speed: false,
+            speed_switch_armed: false,
+            boot_rom_shadow: None,
+            hdma: Hdma::default(),
+            oam_dma: None,
+            stall_cycles: 0,
+            oam_corruption: true,
+            sgb: None,
+            apu: Apu::new(),
+            joypad: Joypad::default(),
+            timer: Timer::default(),
+            serial: Serial::default(),
+            infrared: Infrared::default(),
+            scheduler: Scheduler::default(),
+            timer_synced: 0,
+            ppu_synced: 0,
         }
         .init()
     }
@@ -120,14 +239,81 @@ impl Memory {
         for (addr, byte) in BOOT_ROM.iter().enumerate() {
             self.rom.write(addr as u16, *byte);
         }
+        self.schedule_frame_sequencer();
         self
     }
 
-    /// Reads a byte from the given address.
+    /// Loads a cartridge ROM. The boot ROM stays mapped over its first bytes
+    /// until it is unmapped by a write to `BOOT`.
+    pub fn load_rom(&mut self, rom: &[u8]) {
+        let len = rom.len().min(ROM_SIZE as usize);
+        let shadow_len = len.min(BOOT_ROM_SIZE);
+        self.boot_rom_shadow = Some(rom[..shadow_len].to_vec());
+
+        for (addr, byte) in rom.iter().enumerate().take(len).skip(shadow_len) {
+            self.rom.write(addr as u16, *byte);
+        }
+    }
+
+    /// Unmaps the boot ROM, revealing the start of the cartridge.
+    fn unmap_boot_rom(&mut self) {
+        if let Some(shadow) = self.boot_rom_shadow.take() {
+            for (addr, byte) in shadow.iter().enumerate() {
+                self.rom.write(addr as u16, *byte);
+            }
+        }
+    }
+
+    /// Puts the memory into the state the boot ROM of the given model leaves it in.
+    pub fn reset_post_boot(&mut self, model: Model, cgb_mode: bool) {
+        self.unmap_boot_rom();
+        self.cgb = cgb_mode;
+        self.ppu.cgb = cgb_mode;
+
+        self.write_byte(ppu::LCDC_ADDR, 0x91);
+        self.write_byte(ppu::BGP_ADDR, 0xfc);
+        self.write_byte(BOOT_ADDR, 0x01);
+        self.io[translate_addr(IF_ADDR, IO_ADDR)] = 0xe1;
+
+        if model == Model::Cgb {
+            // The CGB boot ROM selects the DMG compatibility mode for DMG games,
+            // and leaves every colour palette white.
+            self.io[translate_addr(KEY0_ADDR, IO_ADDR)] = if cgb_mode { 0x80 } else { 0x04 };
+            self.ppu.bg_palettes = [0xff; 64];
+            self.ppu.obj_palettes = [0xff; 64];
+        }
+    }
+
+    /// Performs a pending speed switch, as triggered by the `STOP` instruction,
+    /// stalling the CPU until it completes. Returns `true` if the speed was changed.
+    pub fn switch_speed(&mut self) -> bool {
+        if !(self.cgb && self.speed_switch_armed) {
+            return false;
+        }
+        self.speed_switch_armed = false;
+        self.sync_ppu();
+        self.double_speed = !self.double_speed;
+        self.schedule_ppu();
+        // The STOP instruction resets DIV as a write to it would
+        self.write_byte(DIV_ADDR, 0);
+        self.stall_cycles += SPEED_SWITCH_CYCLES;
+        true
+    }
+
+    /// Reads a byte from the given address, as seen by the CPU.
     pub fn read_byte(&self, addr: u16) -> u8 {
+        if self.oam_dma_blocks(addr) || self.ppu_blocks(addr) {
+            return 0xff;
+        }
+        self.peek_byte(addr)
+    }
+
+    /// Reads a byte from the given address without the restrictions placed on
+    /// the CPU, as seen by the DMA controllers.
+    pub fn peek_byte(&self, addr: u16) -> u8 {
         match addr {
             ROM_ADDR..=ROM_ADDR_END => self.rom.read(addr),
-            VRAM_ADDR..=VRAM_ADDR_END => self.vram[translate_addr(addr, VRAM_ADDR)],
+            VRAM_ADDR..=VRAM_ADDR_END => self.vram[self.vram_offset(addr)],
             ERAM_ADDR..=ERAM_ADDR_END => self.eram[translate_addr(addr, ERAM_ADDR)],
             WRAM_ADDR..=WRAM_ADDR_END => self.wram.read(addr),
             ECHO_ADDR..=ECHO_ADDR_END => self
@@ -138,7 +324,7 @@ impl Memory {
                 "Attempted to read from unused memory at address {:#06x}",
                 addr
             ),
-            IO_ADDR..=IO_ADDR_END => self.io[translate_addr(addr, IO_ADDR)],
+            IO_ADDR..=IO_ADDR_END => self.read_io(addr),
             HRAM_ADDR..=HRAM_ADDR_END => self.hram[translate_addr(addr, HRAM_ADDR)],
             IE_ADDR => self.ie,
         }
@@ -153,14 +339,21 @@ impl Memory {
 
     /// Writes a byte to the given address.
     pub fn write_byte(&mut self, addr: u16, value: u8) {
+        if matches!(addr, VRAM_ADDR..=VRAM_ADDR_END | OAM_ADDR..=OAM_ADDR_END) {
+            self.sync_ppu();
+        }
+        if self.oam_dma_blocks(addr) || self.ppu_blocks(addr) {
+            return;
+        }
+
         match addr {
             ROM_ADDR..=ROM_ADDR_END => panic!("Attempted to write to ROM at address {:#06x}", addr),
-            VRAM_ADDR..=VRAM_ADDR_END => self.vram[translate_addr(addr, VRAM_ADDR)] = value,
+            VRAM_ADDR..=VRAM_ADDR_END => self.vram[self.vram_offset(addr)] = value,
             ERAM_ADDR..=ERAM_ADDR_END => self.eram[translate_addr(addr, ERAM_ADDR)] = value,
             WRAM_ADDR..=WRAM_ADDR_END => self.wram.write(addr, value),
             ECHO_ADDR..=ECHO_ADDR_END => self
                 .wram
-                .write(translate_addr(addr, ECHO_ADDR) as u16, value),
+                .write(translate_addr(addr, ECHO_ADDR - WRAM_ADDR) as u16, value),
             OAM_ADDR..=OAM_ADDR_END => self.oam[translate_addr(addr, OAM_ADDR)] = value,
             UNUSED_ADDR..=UNUSED_ADDR_END => {
                 panic!(
@@ -168,7 +361,7 @@ impl Memory {
                     addr
                 );
             }
-            IO_ADDR..=IO_ADDR_END => self.io[translate_addr(addr, IO_ADDR)] = value,
+            IO_ADDR..=IO_ADDR_END => self.write_io(addr, value),
             HRAM_ADDR..=HRAM_ADDR_END => self.hram[translate_addr(addr, HRAM_ADDR)] = value,
             IE_ADDR => self.ie = value,
         }
@@ -181,6 +374,401 @@ impl Memory {
         self.write_byte(addr, low);
         self.write_byte(addr + 1, high);
     }
+
+    /// Returns whether an OAM DMA transfer keeps the CPU from accessing the
+    /// given address. Only HRAM remains accessible.
+    fn oam_dma_blocks(&self, addr: u16) -> bool {
+        let running = self.oam_dma.as_ref().is_some_and(OamDma::is_running);
+        running && !(HRAM_ADDR..=HRAM_ADDR_END).contains(&addr)
+    }
+
+    /// Returns whether the PPU keeps the CPU from accessing the given address:
+    /// VRAM is in use during mode 3, and OAM during modes 2 and 3.
+    fn ppu_blocks(&self, addr: u16) -> bool {
+        match addr {
+            VRAM_ADDR..=VRAM_ADDR_END => self.ppu.mode == ppu::Mode::Drawing,
+            OAM_ADDR..=OAM_ADDR_END => {
+                matches!(self.ppu.mode, ppu::Mode::OamScan | ppu::Mode::Drawing)
+            }
+            _ => false,
+        }
+    }
+
+    /// Emulates the DMG bug where a 16-bit increment or decrement of a register
+    /// pointing into OAM during mode 2 corrupts the row the PPU is reading.
+    /// The given address is the value of the register before the operation.
+    pub fn corrupt_oam(&mut self, addr: u16) {
+        if !self.oam_corruption || self.cgb || !(OAM_ADDR..=0xfeff).contains(&addr) {
+            return;
+        }
+
+        self.sync_ppu();
+        // The first row is never corrupted
+        let row = match self.ppu.oam_scan_row() {
+            Some(row) if row > 0 => row * 8,
+            _ => return,
+        };
+        let previous = row - 8;
+        let word = |oam: &[u8], offset: usize| u16::from_le_bytes([oam[offset], oam[offset + 1]]);
+
+        let a = word(&self.oam, row);
+        let b = word(&self.oam, previous);
+        let c = word(&self.oam, previous + 4);
+        let corrupted = ((a ^ c) & (b ^ c)) ^ c;
+
+        self.oam[row..row + 2].copy_from_slice(&corrupted.to_le_bytes());
+        self.oam.copy_within(previous + 2..previous + 8, row + 2);
+    }
+
+    /// Advances the OAM DMA transfer in progress by an M-cycle, scheduling
+    /// the next one until every byte has been copied.
+    fn step_oam_dma(&mut self) {
+        let Some(mut dma) = self.oam_dma.take() else {
+            return;
+        };
+
+        if let Some(offset) = dma.step() {
+            self.sync_ppu();
+            let byte = self.peek_byte(oam_dma::source_addr(dma.source + offset));
+            self.oam[offset as usize] = byte;
+        }
+
+        if !dma.is_done() {
+            self.oam_dma = Some(dma);
+            self.scheduler
+                .schedule(oam_dma::CYCLES_PER_BYTE, Event::OamDma);
+        }
+    }
+
+    /// Returns the offset into `vram` of the given address in the mapped bank.
+    fn vram_offset(&self, addr: u16) -> usize {
+        self.vram_bank * VRAM_SIZE as usize + translate_addr(addr, VRAM_ADDR)
+    }
+
+    /// Reads an IO register, dispatching to the component that owns it.
+    fn read_io(&self, addr: u16) -> u8 {
+        match addr {
+            ppu::LCDC_ADDR..=ppu::LYC_ADDR | ppu::BGP_ADDR..=ppu::WX_ADDR => {
+                self.ppu.read_register(addr)
+            }
+            ppu::BCPS_ADDR..=ppu::OPRI_ADDR if self.cgb => self.ppu.read_register(addr),
+            KEY1_ADDR if self.cgb => {
+                let speed = if self.double_speed { 0x80 } else { 0 };
+                let armed = if self.speed_switch_armed { 0x01 } else { 0 };
+                0b0111_1110 | speed | armed
+            }
+            VBK_ADDR if self.cgb => 0b1111_1110 | self.vram_bank as u8,
+            SVBK_ADDR if self.cgb => 0b1111_1000 | self.svbk,
+            HDMA1_ADDR..=HDMA5_ADDR if self.cgb => self.hdma.read_register(addr),
+            DIV_ADDR..=TAC_ADDR => self.timer_now().read_register(addr),
+            SB_ADDR | SC_ADDR => self.serial.read_register(addr, self.cgb),
+            RP_ADDR if self.cgb => self.infrared.read(),
+            apu::NR10_ADDR..=apu::NR52_ADDR | apu::WAVE_RAM_ADDR..=apu::WAVE_RAM_ADDR_END => {
+                self.apu.read_register(addr)
+            }
+            // In multiplayer mode, the SGB reports the selected joypad instead
+            JOYP_ADDR => match self.sgb.as_ref().and_then(Sgb::read_joyp) {
+                Some(value) => value,
+                None => self.joypad.read(),
+            },
+            // Only the five interrupt bits exist, the others read as 1
+            IF_ADDR => 0b1110_0000 | self.io[translate_addr(addr, IO_ADDR)],
+            _ => self.io[translate_addr(addr, IO_ADDR)],
+        }
+    }
+
+    /// Writes an IO register, dispatching to the component that owns it.
+    /// The raw value is also kept in `io` for inspection.
+    fn write_io(&mut self, addr: u16, value: u8) {
+        self.io[translate_addr(addr, IO_ADDR)] = value;
+        match addr {
+            ppu::LCDC_ADDR..=ppu::LYC_ADDR | ppu::BGP_ADDR..=ppu::WX_ADDR => {
+                self.write_ppu_register(addr, value)
+            }
+            ppu::BCPS_ADDR..=ppu::OPRI_ADDR if self.cgb => self.write_ppu_register(addr, value),
+            KEY1_ADDR if self.cgb => self.speed_switch_armed = value & 1 != 0,
+            VBK_ADDR if self.cgb => self.vram_bank = (value & 1) as usize,
+            // Bank 0 can't be mapped in the upper half; selecting it maps bank 1
+            SVBK_ADDR if self.cgb => {
+                self.svbk = value & 0b111;
+                self.wram.select((self.svbk.max(1) - 1) as usize);
+            }
+            BOOT_ADDR if value != 0 => self.unmap_boot_rom(),
+            DMA_ADDR => {
+                self.oam_dma = Some(OamDma::new(value));
+                self.scheduler
+                    .schedule(oam_dma::CYCLES_PER_BYTE, Event::OamDma);
+            }
+            DIV_ADDR => {
+                self.sync_timer();
+                // Resetting the counter is a falling edge if the sequencer bit was set
+                if self.timer.counter & (1 << self.frame_sequencer_bit()) != 0 {
+                    self.apu.clock_frame_sequencer();
+                }
+                self.timer.write_register(addr, value);
+                self.schedule_timer_reload();
+                self.schedule_frame_sequencer();
+                self.schedule_serial_bit();
+            }
+            TIMA_ADDR..=TAC_ADDR => {
+                self.sync_timer();
+                self.timer.write_register(addr, value);
+                self.schedule_timer_reload();
+            }
+            SB_ADDR => self.serial.write_register(addr, value, self.cgb),
+            SC_ADDR => {
+                self.serial.write_register(addr, value, self.cgb);
+                self.schedule_serial_bit();
+            }
+            RP_ADDR if self.cgb => self.infrared.write(value),
+            apu::NR10_ADDR..=apu::NR52_ADDR | apu::WAVE_RAM_ADDR..=apu::WAVE_RAM_ADDR_END => {
+                self.apu.write_register(addr, value)
+            }
+            JOYP_ADDR => {
+                let interrupts = self.joypad.write(value);
+                self.request_interrupts(interrupts);
+                if let Some(sgb) = &mut self.sgb {
+                    sgb.write_joyp(value);
+                }
+            }
+            HDMA1_ADDR..=HDMA5_ADDR if self.cgb => {
+                self.sync_ppu();
+                match self.hdma.write_register(addr, value) {
+                    Some(HdmaRequest::General(blocks)) => {
+                        for _ in 0..blocks {
+                            self.copy_hdma_block();
+                        }
+                    }
+                    // A transfer started outside of mode 3 copies its first block right away
+                    Some(HdmaRequest::HBlank)
+                        if !self.ppu.lcd_enabled() || self.ppu.mode == ppu::Mode::HBlank =>
+                    {
+                        self.copy_hdma_block();
+                    }
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Copies the next block of a VRAM DMA transfer, stalling the CPU meanwhile.
+    fn copy_hdma_block(&mut self) {
+        let bank_offset = self.vram_bank * VRAM_SIZE as usize;
+        for i in 0..HDMA_BLOCK_SIZE {
+            let byte = self.hdma_source_byte(self.hdma.source.wrapping_add(i));
+            let destination = translate_addr(self.hdma.destination + i, VRAM_ADDR);
+            self.vram[bank_offset + destination] = byte;
+        }
+
+        self.hdma.finish_block();
+        self.stall_cycles += hdma::block_cycles(self.double_speed);
+    }
+
+    /// Reads a byte as seen by the VRAM DMA controller.
+    fn hdma_source_byte(&self, addr: u16) -> u8 {
+        match addr {
+            VRAM_ADDR..=VRAM_ADDR_END => 0xff,
+            // The upper addresses read from external RAM instead
+            ECHO_ADDR..=IE_ADDR => self.peek_byte(addr - (ECHO_ADDR - ERAM_ADDR)),
+            _ => self.peek_byte(addr),
+        }
+    }
+
+    /// Returns the number of cycles the CPU has been stalled for since the last call.
+    pub fn take_stall_cycles(&mut self) -> u32 {
+        std::mem::take(&mut self.stall_cycles)
+    }
+
+    /// Advances the memory-mapped components by the given number of cycles,
+    /// handling the events due meanwhile at their exact time.
+    /// The cycles are those of the CPU, so the PPU runs at half that rate in double speed mode.
+    pub fn tick(&mut self, cycles: u32) {
+        let end = self.scheduler.now + cycles as u64;
+        while let Some((time, event)) = self.scheduler.pop_due(end) {
+            self.tick_components((time - self.scheduler.now) as u32);
+            self.scheduler.now = time;
+            self.handle_event(event);
+        }
+        self.tick_components((end - self.scheduler.now) as u32);
+        self.scheduler.now = end;
+    }
+
+    /// Advances the components that can't wait for an event by the given
+    /// number of cycles: the APU, which is sampled every few cycles, and the
+    /// serial port while the device it waits on may clock a transfer.
+    fn tick_components(&mut self, cycles: u32) {
+        if cycles == 0 {
+            return;
+        }
+
+        // Transfers on the external clock complete whenever the device has clocked them
+        let interrupts = self.serial.poll();
+        self.request_interrupts(interrupts);
+
+        let dots = if self.double_speed {
+            cycles / 2
+        } else {
+            cycles
+        };
+        self.apu.tick(dots);
+    }
+
+    /// Handles an event that is due now.
+    fn handle_event(&mut self, event: Event) {
+        match event {
+            Event::FrameSequencer => {
+                self.apu.clock_frame_sequencer();
+                self.schedule_frame_sequencer();
+            }
+            Event::SerialBit => {
+                let interrupts = self.serial.shift();
+                self.request_interrupts(interrupts);
+                self.schedule_serial_bit();
+            }
+            Event::Ppu => {
+                self.sync_ppu();
+                self.schedule_ppu();
+            }
+            Event::TimerReload => {
+                self.sync_timer();
+                self.schedule_timer_reload();
+            }
+            Event::OamDma => self.step_oam_dma(),
+        }
+    }
+
+    /// Returns the number of cycles per PPU dot.
+    fn cycles_per_dot(&self) -> u64 {
+        if self.double_speed {
+            2
+        } else {
+            1
+        }
+    }
+
+    /// Advances the PPU to the current time. It only has to be run at its
+    /// events and before anything it reads is written.
+    fn sync_ppu(&mut self) {
+        let cycles_per_dot = self.cycles_per_dot();
+        let dots = (self.scheduler.now - self.ppu_synced) / cycles_per_dot;
+        if dots == 0 {
+            return;
+        }
+        self.ppu_synced += dots * cycles_per_dot;
+
+        let interrupts = self.ppu.tick(dots as u32, &self.vram, &self.oam);
+        self.request_interrupts(interrupts);
+
+        if interrupts & VBLANK != 0 {
+            if let Some(sgb) = &mut self.sgb {
+                sgb.vblank(&self.ppu, &self.vram);
+            }
+        }
+
+        if self.ppu.take_hblank_started() && self.hdma.hblank_active {
+            self.copy_hdma_block();
+        }
+    }
+
+    /// Schedules the next change of the PPU mode or scanline, if the LCD is on.
+    fn schedule_ppu(&mut self) {
+        match self.ppu.dots_to_next_event() {
+            Some(dots) => {
+                let at = self.ppu_synced + dots as u64 * self.cycles_per_dot();
+                self.scheduler.schedule(at - self.scheduler.now, Event::Ppu);
+            }
+            None => self.scheduler.cancel(Event::Ppu),
+        }
+    }
+
+    /// Writes a PPU register once the PPU has caught up, then runs it again
+    /// on the next dot so that the change is reflected in `STAT` right away.
+    fn write_ppu_register(&mut self, addr: u16, value: u8) {
+        self.sync_ppu();
+        self.ppu.write_register(addr, value);
+        if self.ppu.lcd_enabled() {
+            let at = self.ppu_synced + self.cycles_per_dot();
+            self.scheduler.schedule(at - self.scheduler.now, Event::Ppu);
+        } else {
+            self.scheduler.cancel(Event::Ppu);
+        }
+    }
+
+    /// Returns the timer as it is now, without advancing it for good.
+    fn timer_now(&self) -> Timer {
+        let mut timer = self.timer.clone();
+        timer.tick((self.scheduler.now - self.timer_synced) as u32);
+        timer
+    }
+
+    /// Advances the timer to the current time, down to the last whole M-cycle.
+    /// It only has to be run at its events and before it is read or written.
+    fn sync_timer(&mut self) {
+        let elapsed = (self.scheduler.now - self.timer_synced) as u32;
+        let cycles = elapsed - elapsed % timer::CYCLES_PER_STEP;
+        let interrupts = self.timer.tick(cycles);
+        self.request_interrupts(interrupts);
+        self.timer_synced += cycles as u64;
+    }
+
+    /// Schedules the next reload of `TIMA`, if the timer is running.
+    fn schedule_timer_reload(&mut self) {
+        match self.timer.cycles_to_reload() {
+            Some(cycles) => {
+                let at = self.timer_synced + cycles;
+                self.scheduler
+                    .schedule(at - self.scheduler.now, Event::TimerReload);
+            }
+            None => self.scheduler.cancel(Event::TimerReload),
+        }
+    }
+
+    /// Returns the bit of the `DIV` counter whose falling edge clocks the APU frame sequencer.
+    fn frame_sequencer_bit(&self) -> u32 {
+        if self.double_speed {
+            13
+        } else {
+            12
+        }
+    }
+
+    /// Schedules the next falling edge of the bit of the system counter that
+    /// clocks the APU frame sequencer (512 Hz).
+    fn schedule_frame_sequencer(&mut self) {
+        self.sync_timer();
+        let bit = self.frame_sequencer_bit();
+        let cycles = scheduler::cycles_to_falling_edge(self.timer.counter, bit);
+        self.scheduler.schedule(cycles, Event::FrameSequencer);
+    }
+
+    /// Schedules the next falling edge of the serial clock while a transfer on
+    /// the internal clock is in progress.
+    fn schedule_serial_bit(&mut self) {
+        self.sync_timer();
+        if self.serial.transferring() && self.serial.internal_clock() {
+            let cycles =
+                scheduler::cycles_to_falling_edge(self.timer.counter, self.serial.clock_bit());
+            self.scheduler.schedule(cycles, Event::SerialBit);
+        } else {
+            self.scheduler.cancel(Event::SerialBit);
+        }
+    }
+
+    /// Sets the given bits in the interrupt flag register.
+    pub fn request_interrupts(&mut self, interrupts: u8) {
+        self.io[translate_addr(IF_ADDR, IO_ADDR)] |= interrupts;
+    }
+
+    /// Returns the interrupts both requested in `IF` and enabled in `IE`.
+    pub fn pending_interrupts(&self) -> u8 {
+        self.io[translate_addr(IF_ADDR, IO_ADDR)] & self.ie & 0b0001_1111
+    }
+
+    /// Clears the given interrupt in `IF`, once its handler is called.
+    pub fn acknowledge_interrupt(&mut self, interrupt: u8) {
+        self.io[translate_addr(IF_ADDR, IO_ADDR)] &= !interrupt;
+    }
 }
 
 /// Subtracts the offset from the given address and returns the result as a usize.
@@ -191,6 +779,7 @@ fn translate_addr(addr: u16, offset: u16) -> usize {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::gameboy::interrupts::{SERIAL, TIMER};
 
     #[test]
     fn test_read_write_byte() {
@@ -199,6 +788,244 @@ mod tests {
         assert_eq!(mem.read_byte(VRAM_ADDR), 0x12);
     }
 
+    #[test]
+    fn test_cgb_banking() {
+        let mut mem = Memory::new();
+        mem.cgb = true;
+
+        mem.write_byte(VRAM_ADDR, 0x12);
+        mem.write_byte(VBK_ADDR, 0x01);
+        assert_eq!(mem.read_byte(VBK_ADDR), 0xff);
+        assert_eq!(mem.read_byte(VRAM_ADDR), 0x00);
+        mem.write_byte(VRAM_ADDR, 0x34);
+        assert_eq!(mem.vram[VRAM_SIZE as usize], 0x34);
+
+        mem.write_byte(0xd000, 0x56);
+        mem.write_byte(SVBK_ADDR, 0x02);
+        assert_eq!(mem.read_byte(0xd000), 0x00);
+        // Selecting bank 0 maps bank 1
+        mem.write_byte(SVBK_ADDR, 0x00);
+        assert_eq!(mem.read_byte(SVBK_ADDR), 0xf8);
+        assert_eq!(mem.read_byte(0xd000), 0x56);
+    }
+
+    #[test]
+    fn test_speed_switch() {
+        let mut mem = Memory::new();
+        assert!(!mem.switch_speed());
+
+        mem.cgb = true;
+        mem.write_byte(KEY1_ADDR, 0x01);
+        assert_eq!(mem.read_byte(KEY1_ADDR), 0x7f);
+        mem.timer.counter = 0x1234;
+        assert!(mem.switch_speed());
+        assert_eq!(mem.read_byte(KEY1_ADDR), 0xfe);
+        assert_eq!(mem.read_byte(DIV_ADDR), 0);
+        assert_eq!(mem.take_stall_cycles(), SPEED_SWITCH_CYCLES);
+    }
+
+    #[test]
+    fn test_general_dma() {
+        let mut mem = Memory::new();
+        mem.cgb = true;
+        for i in 0..0x20 {
+            mem.write_byte(WRAM_ADDR + i, i as u8);
+        }
+
+        mem.write_byte(HDMA1_ADDR, 0xc0);
+        mem.write_byte(HDMA2_ADDR, 0x00);
+        mem.write_byte(HDMA3_ADDR, 0x01);
+        mem.write_byte(HDMA4_ADDR, 0x00);
+        mem.write_byte(HDMA5_ADDR, 0x01);
+
+        assert_eq!(mem.read_byte(0x811f), 0x1f);
+        assert_eq!(mem.read_byte(HDMA5_ADDR), 0xff);
+        assert_eq!(mem.take_stall_cycles(), 64);
+    }
+
+    #[test]
+    fn test_hblank_dma_cancel() {
+        let mut mem = Memory::new();
+        mem.cgb = true;
+        mem.write_byte(ppu::LCDC_ADDR, 0x91);
+        mem.write_byte(WRAM_ADDR, 0x12);
+
+        mem.write_byte(HDMA1_ADDR, 0xc0);
+        mem.write_byte(HDMA2_ADDR, 0x00);
+        mem.write_byte(HDMA3_ADDR, 0x00);
+        mem.write_byte(HDMA4_ADDR, 0x00);
+        mem.write_byte(HDMA5_ADDR, 0x82);
+        assert_eq!(mem.read_byte(HDMA5_ADDR), 0x02);
+
+        mem.tick(ppu::DOTS_PER_LINE);
+        assert_eq!(mem.read_byte(VRAM_ADDR), 0x12);
+        assert_eq!(mem.read_byte(HDMA5_ADDR), 0x01);
+
+        mem.write_byte(HDMA5_ADDR, 0x00);
+        assert_eq!(mem.read_byte(HDMA5_ADDR), 0x81);
+        mem.tick(ppu::DOTS_PER_LINE);
+        assert_eq!(mem.read_byte(VRAM_ADDR + HDMA_BLOCK_SIZE), 0x00);
+    }
+
+    #[test]
+    fn test_oam_dma() {
+        let mut mem = Memory::new();
+        for i in 0..OAM_DMA_LENGTH {
+            mem.write_byte(0xc100 + i, i as u8);
+        }
+        mem.write_byte(HRAM_ADDR, 0x42);
+
+        mem.write_byte(DMA_ADDR, 0xc1);
+        // The transfer starts once the instruction that wrote DMA has completed
+        assert_eq!(mem.read_byte(0xc101), 0x01);
+        mem.tick(4);
+        assert_eq!(mem.oam[1], 0x00);
+
+        mem.tick(4);
+        assert_eq!(mem.read_byte(WRAM_ADDR), 0xff);
+        assert_eq!(mem.read_byte(HRAM_ADDR), 0x42);
+        mem.write_byte(WRAM_ADDR, 0x12);
+        assert_eq!(mem.peek_byte(WRAM_ADDR), 0x00);
+
+        mem.tick(4 * (OAM_DMA_LENGTH as u32 - 1));
+        assert!(mem.oam_dma.is_none());
+        assert_eq!(mem.read_byte(OAM_ADDR_END), 0x9f);
+    }
+
+    #[test]
+    fn test_oam_dma_from_echo_range() {
+        let mut mem = Memory::new();
+        mem.write_byte(0xdf00, 0x34);
+        mem.write_byte(0xe100, 0x56);
+
+        mem.write_byte(DMA_ADDR, 0xff);
+        mem.tick(4);
+        mem.tick(4);
+        assert_eq!(mem.oam[0], 0x34);
+
+        mem.tick(4 * OAM_DMA_LENGTH as u32);
+        mem.write_byte(DMA_ADDR, 0xe1);
+        mem.tick(4);
+        mem.tick(4);
+        assert_eq!(mem.oam[0], 0x56);
+    }
+
+    #[test]
+    fn test_ppu_access_blocking() {
+        let mut mem = Memory::new();
+        mem.write_byte(VRAM_ADDR, 0x12);
+        mem.write_byte(OAM_ADDR, 0x34);
+        mem.write_byte(ppu::LCDC_ADDR, 0x91);
+
+        // Mode 2
+        assert_eq!(mem.read_byte(VRAM_ADDR), 0x12);
+        assert_eq!(mem.read_byte(OAM_ADDR), 0xff);
+        mem.write_byte(OAM_ADDR, 0x56);
+        assert_eq!(mem.oam[0], 0x34);
+
+        // Mode 3
+        mem.tick(ppu::OAM_SCAN_DOTS);
+        assert_eq!(mem.read_byte(VRAM_ADDR), 0xff);
+        mem.write_byte(VRAM_ADDR, 0x56);
+        assert_eq!(mem.vram[0], 0x12);
+
+        // Mode 0
+        mem.tick(ppu::DOTS_PER_LINE - ppu::OAM_SCAN_DOTS - 4);
+        assert_eq!(mem.read_byte(VRAM_ADDR), 0x12);
+        assert_eq!(mem.read_byte(OAM_ADDR), 0x34);
+    }
+
+    #[test]
+    fn test_oam_corruption() {
+        let mut mem = Memory::new();
+        for (i, byte) in mem.oam.iter_mut().enumerate() {
+            *byte = i as u8;
+        }
+        mem.write_byte(ppu::LCDC_ADDR, 0x91);
+        mem.tick(8);
+
+        mem.oam_corruption = false;
+        mem.corrupt_oam(OAM_ADDR);
+        assert_eq!(mem.oam[16], 16);
+
+        mem.oam_corruption = true;
+        mem.corrupt_oam(OAM_ADDR);
+        // ((0x1110 ^ 0x0d0c) & (0x0908 ^ 0x0d0c)) ^ 0x0d0c, then the rest of row 1
+        assert_eq!(
+            &mem.oam[16..24],
+            &[0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f]
+        );
+    }
+
+    #[test]
+    fn test_boot_rom_unmapped() {
+        let mut mem = Memory::new();
+        mem.load_rom(&[0xaa; 0x200]);
+        assert_eq!(mem.read_byte(0x0000), BOOT_ROM[0]);
+        assert_eq!(mem.read_byte(0x0100), 0xaa);
+
+        mem.write_byte(BOOT_ADDR, 0x01);
+        assert_eq!(mem.read_byte(0x0000), 0xaa);
+    }
+
+    #[test]
+    fn test_div_clocks_frame_sequencer() {
+        let mut mem = Memory::new();
+        mem.write_byte(apu::NR52_ADDR, 0x80);
+        mem.tick(0x2000);
+        assert_eq!(mem.read_byte(DIV_ADDR), 0x20);
+        assert_eq!(mem.apu.frame_step, 1);
+
+        // Resetting DIV while bit 12 is set is a falling edge too
+        mem.tick(0x1000);
+        mem.write_byte(DIV_ADDR, 0x12);
+        assert_eq!(mem.read_byte(DIV_ADDR), 0x00);
+        assert_eq!(mem.apu.frame_step, 2);
+    }
+
+    #[test]
+    fn test_components_scheduled() {
+        let mut mem = Memory::new();
+        mem.write_byte(TIMA_ADDR, 0xff);
+        mem.write_byte(TAC_ADDR, 0b101);
+        mem.write_byte(ppu::LCDC_ADDR, 0x91);
+        mem.write_byte(DMA_ADDR, 0xc0);
+        assert_eq!(mem.scheduler.time_of(Event::TimerReload), Some(20));
+        assert_eq!(mem.scheduler.time_of(Event::Ppu), Some(1));
+        assert_eq!(mem.scheduler.time_of(Event::OamDma), Some(4));
+
+        mem.tick(16);
+        assert_eq!(mem.peek_byte(IF_ADDR) & TIMER, 0);
+        mem.tick(4);
+        assert_eq!(mem.peek_byte(IF_ADDR) & TIMER, TIMER);
+        assert_eq!(
+            mem.scheduler.time_of(Event::Ppu),
+            Some(ppu::OAM_SCAN_DOTS as u64)
+        );
+
+        // Idle components have nothing scheduled
+        mem.tick(4 * OAM_DMA_LENGTH as u32);
+        mem.write_byte(TAC_ADDR, 0);
+        mem.write_byte(ppu::LCDC_ADDR, 0);
+        assert_eq!(mem.scheduler.time_of(Event::TimerReload), None);
+        assert_eq!(mem.scheduler.time_of(Event::Ppu), None);
+        assert_eq!(mem.scheduler.time_of(Event::OamDma), None);
+    }
+
+    #[test]
+    fn test_serial_transfer_interrupt() {
+        let mut mem = Memory::new();
+        mem.write_byte(SB_ADDR, 0x42);
+        mem.write_byte(SC_ADDR, 0x81);
+        // Eight bits at 8192 Hz
+        mem.tick(8 * 512 - 4);
+        assert_eq!(mem.read_byte(IF_ADDR) & SERIAL, 0);
+        mem.tick(4);
+        assert_eq!(mem.read_byte(IF_ADDR) & SERIAL, SERIAL);
+        assert_eq!(mem.read_byte(SB_ADDR), 0xff);
+        assert_eq!(mem.read_byte(SC_ADDR), 0x7f);
+    }
+
     #[test]
     #[should_panic(expected = "Attempted to write to ROM at address 0x0000")]
     fn test_write_byte_rom() {
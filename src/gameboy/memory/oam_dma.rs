@@ -0,0 +1,61 @@
+pub const DMA_ADDR: u16 = 0xff46;
+
+/// The number of bytes copied into OAM by a transfer.
+pub const OAM_DMA_LENGTH: u16 = 0xa0;
+/// The number of cycles it takes to copy a single byte (one M-cycle).
+pub const CYCLES_PER_BYTE: u64 = 4;
+
+/// An OAM DMA transfer in progress, started by a write to `DMA`.
+#[derive(Debug, Clone)]
+pub struct OamDma {
+    /// The address the transfer copies from.
+    pub source: u16,
+    /// The number of bytes copied so far.
+    pub copied: u16,
+    /// Whether the transfer is still in the M-cycle it waits for before copying.
+    starting: bool,
+}
+
+impl OamDma {
+    /// Creates a transfer copying from the given page (`XX00`).
+    pub fn new(page: u8) -> Self {
+        Self {
+            source: (page as u16) << 8,
+            copied: 0,
+            starting: true,
+        }
+    }
+
+    /// Advances the transfer by one M-cycle.
+    /// Returns the offset of the byte to copy meanwhile, if any.
+    pub fn step(&mut self) -> Option<u16> {
+        if self.starting {
+            self.starting = false;
+            return None;
+        }
+
+        let offset = self.copied;
+        self.copied += 1;
+        Some(offset)
+    }
+
+    /// Returns whether the transfer is copying data, locking the CPU out of the bus.
+    pub fn is_running(&self) -> bool {
+        !self.starting && self.copied < OAM_DMA_LENGTH
+    }
+
+    /// Returns whether every byte has been copied.
+    pub fn is_done(&self) -> bool {
+        self.copied == OAM_DMA_LENGTH
+    }
+}
+
+/// Returns the address the DMA controller actually reads for the given source address.
+/// Everything from 0xe000 upwards is wired to work RAM, like echo RAM.
+pub fn source_addr(addr: u16) -> u16 {
+    if addr >= 0xe000 {
+        addr - 0x2000
+    } else {
+        addr
+    }
+}
@@ -0,0 +1,223 @@
+use crate::gameboy::interrupts::SERIAL;
+use crate::gameboy::serial::{NullDevice, SerialDevice};
+
+pub const SB_ADDR: u16 = 0xff01;
+pub const SC_ADDR: u16 = 0xff02;
+
+/// The bit of `SC` that starts a transfer, cleared once it completes.
+const TRANSFER_ENABLE: u8 = 0b1000_0000;
+/// The bit of `SC` that selects the fast clock (CGB only).
+const FAST_CLOCK: u8 = 0b0000_0010;
+/// The bit of `SC` that selects the internal clock.
+const INTERNAL_CLOCK: u8 = 0b0000_0001;
+
+/// The bit of the system counter whose falling edge shifts a bit on the internal
+/// clock (8192 Hz), and the one used by the fast clock of the CGB (262144 Hz).
+const CLOCK_BIT: u32 = 8;
+const FAST_CLOCK_BIT: u32 = 3;
+
+/// The serial port, shifting `SB` out to the device plugged into the link port
+/// while shifting its byte in.
+///
+/// On the internal clock, the device exchanges its byte when the transfer starts,
+/// then one bit is shifted per falling edge of the serial clock. On the external
+/// clock, the whole byte arrives once the device has clocked it.
+#[derive(Debug)]
+pub struct Serial {
+    pub sb: u8,
+    pub sc: u8,
+    /// The bits still to be shifted in during the current transfer.
+    incoming: u8,
+    /// The number of bits shifted during the current transfer.
+    shifted: u8,
+    /// The device at the other end. Devices shared with other threads keep
+    /// their shared state themselves.
+    device: Box<dyn SerialDevice>,
+}
+
+/// A clone has nothing plugged into its link port: the device stays with the
+/// original, which is why restoring a save state keeps the current device.
+impl Clone for Serial {
+    fn clone(&self) -> Self {
+        Self {
+            sb: self.sb,
+            sc: self.sc,
+            incoming: self.incoming,
+            shifted: self.shifted,
+            device: Box::new(NullDevice),
+        }
+    }
+}
+
+impl Default for Serial {
+    fn default() -> Self {
+        Self {
+            sb: 0,
+            sc: 0,
+            incoming: 0,
+            shifted: 0,
+            device: Box::new(NullDevice),
+        }
+    }
+}
+
+impl Serial {
+    /// Plugs the given device into the link port, unplugging the previous one.
+    pub fn set_device(&mut self, device: Box<dyn SerialDevice>) {
+        self.device = device;
+    }
+
+    /// Unplugs the device from the link port, returning it.
+    pub fn take_device(&mut self) -> Box<dyn SerialDevice> {
+        std::mem::replace(&mut self.device, Box::new(NullDevice))
+    }
+
+    /// Returns the device plugged into the link port.
+    pub fn device_mut(&mut self) -> &mut dyn SerialDevice {
+        self.device.as_mut()
+    }
+
+    /// Returns the value of the given serial register.
+    pub fn read_register(&self, addr: u16, cgb: bool) -> u8 {
+        match addr {
+            SB_ADDR => self.sb,
+            SC_ADDR if cgb => 0b0111_1100 | self.sc,
+            SC_ADDR => 0b0111_1110 | self.sc,
+            _ => unreachable!("Not a serial register: {:#06x}", addr),
+        }
+    }
+
+    /// Writes the given serial register.
+    pub fn write_register(&mut self, addr: u16, value: u8, cgb: bool) {
+        match addr {
+            SB_ADDR => self.sb = value,
+            SC_ADDR => {
+                let mask = if cgb { 0b1000_0011 } else { 0b1000_0001 };
+                self.sc = value & mask;
+                if self.transferring() && self.sc & INTERNAL_CLOCK != 0 {
+                    self.incoming = self.device.transfer(self.sb);
+                    self.shifted = 0;
+                }
+            }
+            _ => unreachable!("Not a serial register: {:#06x}", addr),
+        }
+    }
+
+    /// Returns whether a transfer is in progress.
+    pub fn transferring(&self) -> bool {
+        self.sc & TRANSFER_ENABLE != 0
+    }
+
+    /// Returns whether the serial port drives the clock.
+    pub fn internal_clock(&self) -> bool {
+        self.sc & INTERNAL_CLOCK != 0
+    }
+
+    /// Returns the bit of the system counter whose falling edge shifts a bit.
+    pub fn clock_bit(&self) -> u32 {
+        if self.sc & FAST_CLOCK != 0 {
+            FAST_CLOCK_BIT
+        } else {
+            CLOCK_BIT
+        }
+    }
+
+    /// Polls the device while waiting for a transfer on the external clock.
+    /// Returns the interrupts to request.
+    pub fn poll(&mut self) -> u8 {
+        if !self.transferring() || self.internal_clock() {
+            return 0;
+        }
+
+        match self.device.poll_external(self.sb) {
+            Some(byte) => {
+                self.sb = byte;
+                self.sc &= !TRANSFER_ENABLE;
+                SERIAL
+            }
+            None => 0,
+        }
+    }
+
+    /// Shifts a bit on a falling edge of the internal clock.
+    /// Returns the interrupts to request.
+    pub fn shift(&mut self) -> u8 {
+        if !self.transferring() || !self.internal_clock() {
+            return 0;
+        }
+
+        self.sb = self.sb << 1 | self.incoming >> 7;
+        self.incoming <<= 1;
+        self.shifted += 1;
+        if self.shifted < 8 {
+            return 0;
+        }
+        self.sc &= !TRANSFER_ENABLE;
+        self.device.transfer_finished();
+        SERIAL
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gameboy::serial::CaptureDevice;
+
+    #[test]
+    fn test_internal_clock_transfer() {
+        let capture = CaptureDevice::new();
+        let mut serial = Serial::default();
+        serial.set_device(Box::new(capture.clone()));
+
+        serial.write_register(SB_ADDR, 0x42, false);
+        serial.write_register(SC_ADDR, 0x81, false);
+        assert_eq!(capture.bytes(), [0x42]);
+        assert_eq!(serial.read_register(SC_ADDR, false), 0xff);
+
+        // The null byte is shifted in one bit at a time
+        for _ in 0..4 {
+            assert_eq!(serial.shift(), 0);
+        }
+        assert_eq!(serial.sb, 0x2f);
+        for _ in 0..3 {
+            assert_eq!(serial.shift(), 0);
+        }
+        assert_eq!(serial.shift(), SERIAL);
+        assert_eq!(serial.sb, 0xff);
+        assert_eq!(serial.read_register(SC_ADDR, false), 0x7f);
+    }
+
+    #[test]
+    fn test_clone_leaves_device_plugged_in() {
+        let capture = CaptureDevice::new();
+        let mut serial = Serial::default();
+        serial.set_device(Box::new(capture.clone()));
+
+        let mut clone = serial.clone();
+        clone.write_register(SB_ADDR, 0x42, false);
+        clone.write_register(SC_ADDR, 0x81, false);
+        assert!(capture.bytes().is_empty());
+
+        serial.take_device().transfer(0x24);
+        assert_eq!(capture.bytes(), [0x24]);
+    }
+
+    #[test]
+    fn test_external_clock_waits() {
+        let mut serial = Serial::default();
+        serial.write_register(SC_ADDR, 0x80, false);
+        assert_eq!(serial.poll(), 0);
+        assert_eq!(serial.shift(), 0);
+        assert!(serial.transferring());
+    }
+
+    #[test]
+    fn test_fast_clock_is_cgb_only() {
+        let mut serial = Serial::default();
+        serial.write_register(SC_ADDR, 0x02, false);
+        assert_eq!(serial.clock_bit(), CLOCK_BIT);
+        serial.write_register(SC_ADDR, 0x02, true);
+        assert_eq!(serial.clock_bit(), FAST_CLOCK_BIT);
+        assert_eq!(serial.read_register(SC_ADDR, true), 0x7e);
+    }
+}
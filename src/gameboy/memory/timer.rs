@@ -0,0 +1,233 @@
+use crate::gameboy::interrupts::TIMER;
+use crate::gameboy::scheduler::cycles_to_falling_edge;
+
+pub const DIV_ADDR: u16 = 0xff04;
+pub const TIMA_ADDR: u16 = 0xff05;
+pub const TMA_ADDR: u16 = 0xff06;
+pub const TAC_ADDR: u16 = 0xff07;
+
+/// The number of cycles in an M-cycle, the step at which the timer advances.
+pub const CYCLES_PER_STEP: u32 = 4;
+/// The bit of the system counter whose falling edge increments `TIMA`, for each clock select of `TAC`.
+const TAC_BITS: [u32; 4] = [9, 3, 5, 7];
+
+/// The timer, driven by the 16-bit system counter whose upper byte is `DIV`.
+///
+/// `TIMA` is incremented on each falling edge of a counter bit selected by `TAC`
+/// and ANDed with the timer enable bit, so writes to `DIV` and `TAC` can
+/// increment it as well. When it overflows, `TIMA` reads 0 for one M-cycle before
+/// `TMA` is loaded into it and the interrupt is requested.
+#[derive(Debug, Clone, Default)]
+pub struct Timer {
+    /// The system counter, incremented every cycle.
+    pub counter: u16,
+    pub tima: u8,
+    pub tma: u8,
+    pub tac: u8,
+    /// Whether `TIMA` overflowed during the last M-cycle and must be reloaded.
+    overflowed: bool,
+    /// Whether `TMA` was loaded into `TIMA` during the last M-cycle.
+    reloading: bool,
+}
+
+impl Timer {
+    /// Returns the value of the given timer register.
+    pub fn read_register(&self, addr: u16) -> u8 {
+        match addr {
+            DIV_ADDR => (self.counter >> 8) as u8,
+            TIMA_ADDR => self.tima,
+            TMA_ADDR => self.tma,
+            TAC_ADDR => 0b1111_1000 | self.tac,
+            _ => unreachable!("Not a timer register: {:#06x}", addr),
+        }
+    }
+
+    /// Writes the given timer register.
+    pub fn write_register(&mut self, addr: u16, value: u8) {
+        match addr {
+            DIV_ADDR => {
+                let signal = self.signal();
+                self.counter = 0;
+                self.detect_falling_edge(signal);
+            }
+            // Writing TIMA cancels a pending reload, but is ignored during the reload itself
+            TIMA_ADDR if !self.reloading => {
+                self.tima = value;
+                self.overflowed = false;
+            }
+            TIMA_ADDR => {}
+            TMA_ADDR => {
+                self.tma = value;
+                if self.reloading {
+                    self.tima = value;
+                }
+            }
+            TAC_ADDR => {
+                let signal = self.signal();
+                self.tac = value & 0b111;
+                self.detect_falling_edge(signal);
+            }
+            _ => unreachable!("Not a timer register: {:#06x}", addr),
+        }
+    }
+
+    /// Returns whether `TIMA` is incremented (`TAC` bit 2).
+    fn enabled(&self) -> bool {
+        self.tac & 0b100 != 0
+    }
+
+    /// Returns the bit of the system counter selected by `TAC`.
+    fn tima_bit(&self) -> u32 {
+        TAC_BITS[(self.tac & 0b11) as usize]
+    }
+
+    /// Returns the input of the `TIMA` falling edge detector.
+    fn signal(&self) -> bool {
+        self.enabled() && self.counter & (1 << self.tima_bit()) != 0
+    }
+
+    /// Returns the number of cycles until `TMA` is next loaded into `TIMA`
+    /// and the interrupt requested, or `None` while the timer is stopped.
+    pub fn cycles_to_reload(&self) -> Option<u64> {
+        if self.overflowed {
+            return Some(CYCLES_PER_STEP as u64);
+        }
+        if !self.enabled() {
+            return None;
+        }
+
+        let bit = self.tima_bit();
+        let increments = 0xff - self.tima as u64;
+        let overflow = cycles_to_falling_edge(self.counter, bit) + (increments << (bit + 1));
+        Some(overflow + CYCLES_PER_STEP as u64)
+    }
+
+    /// Increments `TIMA` if the signal fell since it was last `previous`.
+    fn detect_falling_edge(&mut self, previous: bool) {
+        if previous && !self.signal() {
+            let (tima, overflowed) = self.tima.overflowing_add(1);
+            self.tima = tima;
+            self.overflowed |= overflowed;
+        }
+    }
+
+    /// Advances the timer by the given number of cycles, one M-cycle at a time.
+    /// Returns the interrupts to request.
+    pub fn tick(&mut self, cycles: u32) -> u8 {
+        let steps = cycles / CYCLES_PER_STEP;
+
+        // Unless TIMA overflows meanwhile, the steps can be taken all at once
+        if !self.overflowed && !self.reloading {
+            let elapsed = steps as u64 * CYCLES_PER_STEP as u64;
+            let increments = if self.enabled() {
+                let shift = self.tima_bit() + 1;
+                let counter = self.counter as u64;
+                ((counter + elapsed) >> shift) - (counter >> shift)
+            } else {
+                0
+            };
+            if self.tima as u64 + increments <= 0xff {
+                self.counter = self.counter.wrapping_add(elapsed as u16);
+                self.tima += increments as u8;
+                return 0;
+            }
+        }
+
+        let mut interrupts = 0;
+        for _ in 0..steps {
+            self.reloading = false;
+            if self.overflowed {
+                self.overflowed = false;
+                self.tima = self.tma;
+                self.reloading = true;
+                interrupts |= TIMER;
+            }
+
+            let signal = self.signal();
+            self.counter = self.counter.wrapping_add(CYCLES_PER_STEP as u16);
+            self.detect_falling_edge(signal);
+        }
+        interrupts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tima_increments() {
+        let mut timer = Timer::default();
+        timer.write_register(TAC_ADDR, 0b101);
+        timer.tick(16 * 3);
+        assert_eq!(timer.read_register(TIMA_ADDR), 3);
+        assert_eq!(timer.read_register(TAC_ADDR), 0xfd);
+    }
+
+    #[test]
+    fn test_delayed_reload() {
+        let mut timer = Timer::default();
+        timer.write_register(TMA_ADDR, 0x42);
+        timer.write_register(TIMA_ADDR, 0xff);
+        timer.write_register(TAC_ADDR, 0b101);
+
+        assert_eq!(timer.tick(16), 0);
+        // TIMA reads 0 for one M-cycle before being reloaded
+        assert_eq!(timer.tima, 0x00);
+        assert_eq!(timer.tick(4), TIMER);
+        assert_eq!(timer.tima, 0x42);
+
+        // Writes to TIMA are ignored during the reload cycle, but TMA goes through
+        timer.write_register(TIMA_ADDR, 0x10);
+        timer.write_register(TMA_ADDR, 0x20);
+        assert_eq!(timer.tima, 0x20);
+    }
+
+    #[test]
+    fn test_tima_write_cancels_reload() {
+        let mut timer = Timer::default();
+        timer.write_register(TMA_ADDR, 0x42);
+        timer.write_register(TIMA_ADDR, 0xff);
+        timer.write_register(TAC_ADDR, 0b101);
+
+        timer.tick(16);
+        timer.write_register(TIMA_ADDR, 0x10);
+        assert_eq!(timer.tick(4), 0);
+        assert_eq!(timer.tima, 0x10);
+    }
+
+    #[test]
+    fn test_div_and_tac_write_glitches() {
+        let mut timer = Timer::default();
+        timer.write_register(TAC_ADDR, 0b101);
+        timer.tick(8);
+        // Bit 3 is set, so resetting the counter is a falling edge
+        timer.write_register(DIV_ADDR, 0x12);
+        assert_eq!(timer.tima, 1);
+        assert_eq!(timer.read_register(DIV_ADDR), 0);
+
+        timer.tick(8);
+        // So is disabling the timer
+        timer.write_register(TAC_ADDR, 0b001);
+        assert_eq!(timer.tima, 2);
+    }
+
+    #[test]
+    fn test_cycles_to_reload() {
+        let mut timer = Timer::default();
+        assert_eq!(timer.cycles_to_reload(), None);
+
+        timer.write_register(TIMA_ADDR, 0xfe);
+        timer.write_register(TAC_ADDR, 0b101);
+        assert_eq!(timer.cycles_to_reload(), Some(36));
+        assert_eq!(timer.tick(32), 0);
+        assert_eq!(timer.cycles_to_reload(), Some(4));
+        assert_eq!(timer.tick(4), TIMER);
+
+        // Many increments at once once the reload is over
+        timer.tick(4);
+        timer.tick(16 * 0x80);
+        assert_eq!(timer.tima, 0x80);
+        assert_eq!(timer.counter, 40 + 16 * 0x80);
+    }
+}
@@ -1,29 +1,373 @@
-use std::sync::{Arc, RwLock};
+pub mod apu;
+pub mod audio;
+use audio::{AudioSink, NullSink};
 
 pub mod cpu;
 use cpu::Cpu;
 
 pub mod memory;
-use memory::Memory;
+use memory::{Buttons, Memory};
 
-/// A struct representing the GameBoy.
+pub mod cartridge;
+use cartridge::Header;
+
+pub mod infrared;
+use infrared::InfraredDevice;
+
+pub mod interrupts;
+pub mod link;
+pub mod ppu;
+pub mod printer;
+pub mod scheduler;
+pub mod serial;
+use serial::SerialDevice;
+
+pub mod sgb;
+use sgb::Sgb;
+
+pub mod wav;
+
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+
+/// The hardware models that can be emulated.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum Model {
+    /// The original GameBoy.
+    #[default]
+    Dmg,
+    /// The Super GameBoy.
+    Sgb,
+    /// The GameBoy Color.
+    Cgb,
+}
+
+impl Model {
+    /// Returns the model best suited to the game with the given header.
+    pub fn detect(header: &Header) -> Self {
+        if header.supports_cgb() {
+            Model::Cgb
+        } else if header.supports_sgb() {
+            Model::Sgb
+        } else {
+            Model::Dmg
+        }
+    }
+}
+
+/// A snapshot of the whole state of a `GameBoy`, to return to later.
 #[derive(Debug, Clone)]
+pub struct SaveState {
+    cpu: Cpu,
+}
+
+/// A struct representing the GameBoy.
+///
+/// The `GameBoy` owns its state outright, so that the CPU accesses the memory
+/// without any locking: frontends running it on another thread communicate
+/// with that thread rather than sharing the `GameBoy`.
+#[derive(Debug)]
 pub struct GameBoy {
-    pub cpu: Arc<RwLock<Cpu>>,
-    pub memory: Arc<RwLock<Memory>>,
+    /// The CPU, which owns the memory.
+    pub cpu: Cpu,
+    /// The emulated hardware model.
+    pub model: Model,
+    /// The header of the loaded cartridge.
+    pub header: Header,
+    /// Where the audio samples are sent.
+    audio_sink: Box<dyn AudioSink>,
+}
+
+impl Default for GameBoy {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl GameBoy {
     /// Creates a new `GameBoy`.
     pub fn new() -> Self {
-        let memory = Arc::new(RwLock::new(Memory::new()));
-        let cpu = Arc::new(RwLock::new(Cpu::new(memory.clone())));
+        Self {
+            cpu: Cpu::new(Memory::new()),
+            model: Model::Dmg,
+            header: Header::parse(&[]),
+            audio_sink: Box::new(NullSink),
+        }
+    }
 
-        Self { cpu, memory }
+    /// Creates a new `GameBoy` with the given ROM loaded. The model is detected
+    /// from the cartridge header unless one is given.
+    pub fn with_rom(rom: &[u8], model: Option<Model>) -> Self {
+        let header = Header::parse(rom);
+        let model = model.unwrap_or_else(|| Model::detect(&header));
+        if model == Model::Dmg && header.cgb_only() {
+            eprintln!("\"{}\" only runs on a GameBoy Color", header.title);
+        }
+
+        let mut gameboy = Self {
+            model,
+            header,
+            ..Self::new()
+        };
+
+        let mem = &mut gameboy.cpu.mem;
+        mem.load_rom(rom);
+
+        // The SGB only listens to games that declare support for it
+        if model == Model::Sgb && gameboy.header.supports_sgb() {
+            mem.sgb = Some(Sgb::new());
+        }
+
+        mem.apu
+            .set_sample_rate(audio::DEFAULT_SAMPLE_RATE, model == Model::Cgb);
+
+        // There is no CGB boot ROM to run, so start from the state it leaves behind
+        if model == Model::Cgb {
+            mem.reset_post_boot(model, gameboy.header.supports_cgb());
+            gameboy.cpu.reset_post_boot(model);
+        }
+
+        gameboy
     }
 
     /// Runs the GameBoy.
     pub fn run(&mut self) {
-        self.cpu.write().unwrap().run();
+        loop {
+            self.step();
+        }
+    }
+
+    /// Executes a single instruction and advances the rest of the system by its cycles,
+    /// then sends the audio produced meanwhile to the sink.
+    pub fn step(&mut self) {
+        let elapsed = self.cpu.step();
+        // Let the rest of the system catch up with the CPU, handling its scheduled events
+        self.cpu.mem.tick(elapsed);
+
+        let cycles = self.cpu.cycles;
+        self.cpu.mem.serial.device_mut().sync(cycles);
+
+        let apu = &mut self.cpu.mem.apu;
+        let samples = apu.take_samples();
+        if samples.is_empty() {
+            return;
+        }
+
+        self.audio_sink.push_samples(&samples);
+        for (channel, samples) in apu.take_channel_samples().iter().flatten().enumerate() {
+            self.audio_sink.push_channel_samples(channel, samples);
+        }
+    }
+
+    /// Returns the memory.
+    pub fn memory(&self) -> &Memory {
+        &self.cpu.mem
+    }
+
+    /// Returns the memory, to change it.
+    pub fn memory_mut(&mut self) -> &mut Memory {
+        &mut self.cpu.mem
+    }
+
+    /// Sends the audio samples to the given sink from now on.
+    /// The previous sink is finished, then dropped.
+    pub fn set_audio_sink(&mut self, sink: Box<dyn AudioSink>) {
+        self.cpu.mem.apu.set_channel_capture(sink.wants_channels());
+        self.audio_sink.finish();
+        self.audio_sink = sink;
+    }
+
+    /// Turns the `GameBoy` off for good, finishing the audio sink and turning
+    /// off the device plugged into the link port.
+    pub fn power_off(&mut self) {
+        self.audio_sink.finish();
+        self.cpu.mem.serial.device_mut().power_off();
+    }
+
+    /// Plugs the given device into the link port.
+    /// The previous device is turned off, then dropped.
+    pub fn set_serial_device(&mut self, device: Box<dyn SerialDevice>) {
+        self.cpu.mem.serial.device_mut().power_off();
+        self.cpu.mem.serial.set_device(device);
+    }
+
+    /// Hands the device plugged into the link port a flag that is set when
+    /// the emulation is stopped, so that it doesn't keep waiting for its peer.
+    pub fn set_stop_flag(&mut self, stop: Arc<AtomicBool>) {
+        self.cpu.mem.serial.device_mut().set_stop_flag(stop);
+    }
+
+    /// Points the infrared port at the given device. The previous device is dropped.
+    pub fn set_infrared_device(&mut self, device: Box<dyn InfraredDevice>) {
+        self.cpu.mem.infrared.set_device(device);
+    }
+
+    /// Sets the buttons pressed on the joypad, requesting the joypad interrupt
+    /// if a selected one was just pressed.
+    pub fn set_buttons(&mut self, buttons: Buttons) {
+        let mem = &mut self.cpu.mem;
+        let interrupts = mem.joypad.set_buttons(buttons);
+        mem.request_interrupts(interrupts);
+    }
+
+    /// Takes a snapshot of the CPU and memory.
+    pub fn save_state(&self) -> SaveState {
+        SaveState {
+            cpu: self.cpu.clone(),
+        }
+    }
+
+    /// Restores a snapshot taken by `save_state` on this `GameBoy`.
+    pub fn load_state(&mut self, state: &SaveState) {
+        // The devices facing the link and infrared ports are not part of the state
+        let device = self.cpu.mem.serial.take_device();
+        let infrared = self.cpu.mem.infrared.take_device();
+        self.cpu = state.cpu.clone();
+        self.cpu.mem.serial.set_device(device);
+        self.cpu.mem.infrared.set_device(infrared);
+    }
+
+    /// Returns the sample rate of the audio sent to the sink.
+    pub fn sample_rate(&self) -> u32 {
+        self.cpu.mem.apu.sample_rate()
+    }
+
+    /// Sets the sample rate of the audio sent to the sink.
+    pub fn set_sample_rate(&mut self, sample_rate: u32) {
+        self.cpu
+            .mem
+            .apu
+            .set_sample_rate(sample_rate, self.model == Model::Cgb);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    /// What the ROMs of the mooneye test suite leave in B, C, D, E, H and L on success.
+    const MOONEYE_PASS: [u8; 6] = [3, 5, 8, 13, 21, 34];
+    /// The T-cycles a test ROM is given to finish, about 10 seconds.
+    const TEST_ROM_CYCLES: u64 = 10 * apu::CLOCK_RATE as u64;
+
+    /// Runs a ROM of the mooneye test suite from the state the DMG boot ROM
+    /// leaves, until it executes the `LD B, B` that marks its end. Returns
+    /// whether it passed, or `None` if it never finished.
+    fn run_mooneye(rom: &[u8]) -> Option<bool> {
+        let mut gameboy = GameBoy::with_rom(rom, Some(Model::Dmg));
+        gameboy.cpu.mem.reset_post_boot(Model::Dmg, false);
+        gameboy.cpu.reset_post_boot(Model::Dmg);
+        gameboy.cpu.accurate_timing = true;
+
+        while gameboy.cpu.cycles < TEST_ROM_CYCLES {
+            if !gameboy.cpu.halt && gameboy.cpu.mem.peek_byte(gameboy.cpu.pc) == 0x40 {
+                let regs = gameboy.cpu.regs;
+                return Some([regs.b, regs.c, regs.d, regs.e, regs.h, regs.l] == MOONEYE_PASS);
+            }
+            gameboy.step();
+        }
+        None
+    }
+
+    /// Builds a ROM in the manner of the mooneye test suite: the given code
+    /// runs from 0x0150 and jumps to 0x0200 to pass or to 0x0220 to fail, and
+    /// the given timer interrupt handler is at 0x0050.
+    fn mooneye_style_rom(code: &[u8], timer_handler: &[u8]) -> Vec<u8> {
+        let mut rom = vec![0; 0x8000];
+        rom[0x50..0x50 + timer_handler.len()].copy_from_slice(timer_handler);
+        // JP 0x0150
+        rom[0x100..0x103].copy_from_slice(&[0xc3, 0x50, 0x01]);
+        rom[0x150..0x150 + code.len()].copy_from_slice(code);
+        // LD B, 3; LD C, 5; LD D, 8; LD E, 13; LD H, 21; LD L, 34; LD B, B; JR -2
+        rom[0x200..0x20f].copy_from_slice(&[
+            0x06, 0x03, 0x0e, 0x05, 0x16, 0x08, 0x1e, 0x0d, 0x26, 0x15, 0x2e, 0x22, 0x40, 0x18,
+            0xfe,
+        ]);
+        // LD A, 0x42; LD B, A; LD C, A; LD D, A; LD E, A; LD H, A; LD L, A; LD B, B; JR -2
+        rom[0x220..0x22b].copy_from_slice(&[
+            0x3e, 0x42, 0x47, 0x4f, 0x57, 0x5f, 0x67, 0x6f, 0x40, 0x18, 0xfe,
+        ]);
+        rom
+    }
+
+    #[test]
+    fn test_rom_reports_failure() {
+        // JP 0x0220
+        let rom = mooneye_style_rom(&[0xc3, 0x20, 0x02], &[]);
+        assert_eq!(run_mooneye(&rom), Some(false));
+    }
+
+    #[test]
+    fn test_rom_timer_interrupt_wakes_halt() {
+        #[rustfmt::skip]
+        let code = [
+            0xf3,             // DI
+            0x31, 0xfe, 0xff, // LD SP, 0xfffe
+            0x16, 0x00,       // LD D, 0
+            0xaf,             // XOR A
+            0xe0, 0x0f,       // LDH (IF), A
+            0x3e, 0x04,       // LD A, 0x04
+            0xe0, 0xff,       // LDH (IE), A
+            0x3e, 0xf0,       // LD A, 0xf0
+            0xe0, 0x05,       // LDH (TIMA), A
+            0x3e, 0x42,       // LD A, 0x42
+            0xe0, 0x06,       // LDH (TMA), A
+            0x3e, 0x05,       // LD A, 0x05
+            0xe0, 0x07,       // LDH (TAC), A
+            0xfb,             // EI
+            0x76,             // HALT
+            0x00,             // NOP
+            0xf0, 0x05,       // LDH A, (TIMA)
+            0xfe, 0x42,       // CP 0x42
+            0xda, 0x20, 0x02, // JP C, fail
+            0xfe, 0x48,       // CP 0x48
+            0xd2, 0x20, 0x02, // JP NC, fail
+            0x7a,             // LD A, D
+            0xfe, 0x01,       // CP 1
+            0xc2, 0x20, 0x02, // JP NZ, fail
+            0xf0, 0x0f,       // LDH A, (IF)
+            0xe6, 0x04,       // AND 0x04
+            0xc2, 0x20, 0x02, // JP NZ, fail
+            0xc3, 0x00, 0x02, // JP pass
+        ];
+        // INC D; RETI
+        let rom = mooneye_style_rom(&code, &[0x14, 0xd9]);
+        assert_eq!(run_mooneye(&rom), Some(true));
+    }
+
+    /// Runs the ROMs of the mooneye test suite in the given directory under
+    /// `MOONEYE_ROMS` (the `build` directory of the suite) whose names contain
+    /// the given filter, panicking with the names of those that fail.
+    fn run_mooneye_roms(subdir: &str, filter: &str) {
+        let dir = PathBuf::from(std::env::var("MOONEYE_ROMS").expect("MOONEYE_ROMS is not set"))
+            .join(subdir);
+        let mut paths: Vec<_> = std::fs::read_dir(&dir)
+            .unwrap_or_else(|err| panic!("Failed to read {}: {}", dir.display(), err))
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "gb"))
+            .filter(|path| path.file_name().unwrap().to_string_lossy().contains(filter))
+            .collect();
+        paths.sort();
+        assert!(!paths.is_empty(), "No ROMs in {}", dir.display());
+
+        let failed: Vec<_> = paths
+            .iter()
+            .filter(|path| run_mooneye(&std::fs::read(path).unwrap()) != Some(true))
+            .map(|path| path.file_name().unwrap().to_string_lossy().into_owned())
+            .collect();
+        assert!(failed.is_empty(), "Failed: {:?}", failed);
+    }
+
+    #[test]
+    #[ignore = "needs the mooneye test suite ROMs, found through MOONEYE_ROMS"]
+    fn test_mooneye_timer() {
+        run_mooneye_roms("acceptance/timer", "");
+    }
+
+    #[test]
+    #[ignore = "needs the mooneye test suite ROMs, found through MOONEYE_ROMS"]
+    fn test_mooneye_timing() {
+        run_mooneye_roms("acceptance", "_timing");
     }
 }
@@ -0,0 +1,246 @@
+use std::collections::VecDeque;
+
+use super::{tile_pixel, BgPixel, ObjPixel, Ppu, LCD_WIDTH};
+
+/// The number of dots a sprite fetch stalls the pixel pipeline for.
+const SPRITE_FETCH_DOTS: u8 = 6;
+
+/// The steps of the background/window fetcher. Every step but `Push` takes two dots.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+enum FetcherStep {
+    #[default]
+    Tile,
+    DataLow,
+    DataHigh,
+    Push,
+}
+
+/// The state of the pixel FIFO renderer for the current scanline.
+#[derive(Debug, Default, Clone)]
+pub struct Fifo {
+    /// The background/window FIFO.
+    bg: VecDeque<BgPixel>,
+    /// The sprite FIFO.
+    obj: VecDeque<ObjPixel>,
+    /// The current step of the background/window fetcher.
+    step: FetcherStep,
+    /// The number of dots spent in the current fetcher step.
+    step_dots: u8,
+    /// The tile column the fetcher is working on, relative to the start of the line.
+    fetch_x: u8,
+    tile: u8,
+    /// The CGB attributes of the tile being fetched.
+    attributes: u8,
+    low: u8,
+    high: u8,
+    /// Whether the fetcher is fetching window tiles.
+    window: bool,
+    /// The number of pixels shifted out to the LCD on this line.
+    x: u8,
+    /// The number of pixels still to be discarded for fine scrolling (`SCX % 8`).
+    discard: u8,
+    /// Whether the first tile fetched on the line is yet to be thrown away.
+    initial_fetch: bool,
+    /// A bitmask of the sprites (indices into `Ppu::sprites`) already fetched.
+    fetched_sprites: u16,
+    /// The sprite being fetched and the dots spent on it so far.
+    sprite_fetch: Option<(usize, u8)>,
+}
+
+impl Fifo {
+    /// Resets the renderer for a new scanline.
+    fn reset(&mut self, discard: u8) {
+        self.bg.clear();
+        self.obj.clear();
+        self.step = FetcherStep::Tile;
+        self.step_dots = 0;
+        self.fetch_x = 0;
+        self.window = false;
+        self.x = 0;
+        self.discard = discard;
+        self.initial_fetch = true;
+        self.fetched_sprites = 0;
+        self.sprite_fetch = None;
+    }
+}
+
+impl Ppu {
+    /// Prepares the pixel FIFO renderer at the start of mode 3.
+    pub(super) fn fifo_start_line(&mut self) {
+        let discard = self.scx % 8;
+        self.fifo.reset(discard);
+    }
+
+    /// Returns a lower bound of the dots left before the line is drawn:
+    /// at most one pixel is shifted out per dot.
+    pub(super) fn fifo_min_dots_left(&self) -> u32 {
+        (LCD_WIDTH - self.fifo.x as usize) as u32 + self.fifo.discard as u32
+    }
+
+    /// Advances the pixel FIFO renderer by a single dot.
+    /// Returns `true` once the last pixel of the line has been shifted out.
+    pub(super) fn fifo_step(&mut self, vram: &[u8]) -> bool {
+        // A sprite fetch stalls both the fetcher and the pixel output.
+        if let Some((sprite, dots)) = self.fifo.sprite_fetch {
+            if dots + 1 == SPRITE_FETCH_DOTS {
+                self.fifo.sprite_fetch = None;
+                self.fifo_merge_sprite(sprite, vram);
+            } else {
+                self.fifo.sprite_fetch = Some((sprite, dots + 1));
+            }
+            return false;
+        }
+
+        if let Some(sprite) = self.fifo_pending_sprite() {
+            // The background fetcher keeps going until it has pixels to mix with.
+            if self.fifo.bg.is_empty() {
+                self.fifo_advance_fetcher(vram);
+            } else {
+                self.fifo.fetched_sprites |= 1 << sprite;
+                self.fifo.sprite_fetch = Some((sprite, 0));
+            }
+            return false;
+        }
+
+        if self.fifo_window_triggered() {
+            self.fifo.bg.clear();
+            self.fifo.window = true;
+            self.fifo.fetch_x = 0;
+            self.fifo.step = FetcherStep::Tile;
+            self.fifo.step_dots = 0;
+            self.window_line += 1;
+            return false;
+        }
+
+        self.fifo_advance_fetcher(vram);
+
+        let Some(bg) = self.fifo.bg.pop_front() else {
+            return false;
+        };
+        let obj = self.fifo.obj.pop_front();
+
+        if self.fifo.discard > 0 {
+            self.fifo.discard -= 1;
+            return false;
+        }
+
+        self.mix_pixel(self.fifo.x as usize, bg, obj);
+        self.fifo.x += 1;
+
+        self.fifo.x as usize == LCD_WIDTH
+    }
+
+    /// Returns the index of the next sprite that starts at the current pixel, if any.
+    fn fifo_pending_sprite(&self) -> Option<usize> {
+        if !self.sprites_enabled() || self.fifo.discard > 0 {
+            return None;
+        }
+
+        self.sprites
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| self.fifo.fetched_sprites & (1 << index) == 0)
+            .find(|(_, sprite)| sprite.x as u16 <= self.fifo.x as u16 + 8)
+            .map(|(index, _)| index)
+    }
+
+    /// Returns whether the window starts at the current pixel.
+    fn fifo_window_triggered(&self) -> bool {
+        !self.fifo.window
+            && self.fifo.discard == 0
+            && self.window_enabled()
+            && self.window_y_triggered
+            && self.fifo.x as u16 + 7 >= self.wx as u16
+    }
+
+    /// Advances the background/window fetcher by a single dot.
+    fn fifo_advance_fetcher(&mut self, vram: &[u8]) {
+        // The window line counter has already been advanced when the window started.
+        let (map, row) = if self.fifo.window {
+            (self.window_map_offset(), self.window_line.wrapping_sub(1))
+        } else {
+            (self.bg_map_offset(), self.ly.wrapping_add(self.scy))
+        };
+
+        if self.fifo.step == FetcherStep::Push {
+            if self.fifo.bg.is_empty() {
+                if self.fifo.initial_fetch {
+                    self.fifo.initial_fetch = false;
+                } else {
+                    let attributes = self.fifo.attributes;
+                    for pixel in 0..8 {
+                        let pixel = if attributes & 0b0010_0000 != 0 {
+                            7 - pixel
+                        } else {
+                            pixel
+                        };
+                        self.fifo.bg.push_back(BgPixel {
+                            color: tile_pixel(self.fifo.low, self.fifo.high, pixel),
+                            palette: attributes & 0b0000_0111,
+                            priority: attributes & 0b1000_0000 != 0,
+                        });
+                    }
+                    self.fifo.fetch_x = self.fifo.fetch_x.wrapping_add(1);
+                }
+                self.fifo.step = FetcherStep::Tile;
+            }
+            return;
+        }
+
+        self.fifo.step_dots += 1;
+        if self.fifo.step_dots < 2 {
+            return;
+        }
+        self.fifo.step_dots = 0;
+
+        let data_offset = self.bg_row_offset(self.fifo.tile, self.fifo.attributes, row % 8);
+        self.fifo.step = match self.fifo.step {
+            FetcherStep::Tile => {
+                let column = if self.fifo.window {
+                    self.fifo.fetch_x
+                } else {
+                    (self.scx / 8).wrapping_add(self.fifo.fetch_x)
+                };
+                let map_offset = map + (row as usize / 8) * 32 + (column as usize & 31);
+                self.fifo.tile = vram[map_offset];
+                self.fifo.attributes = self.bg_attributes(vram, map_offset);
+                FetcherStep::DataLow
+            }
+            FetcherStep::DataLow => {
+                self.fifo.low = vram[data_offset];
+                FetcherStep::DataHigh
+            }
+            FetcherStep::DataHigh => {
+                self.fifo.high = vram[data_offset + 1];
+                FetcherStep::Push
+            }
+            FetcherStep::Push => unreachable!(),
+        };
+    }
+
+    /// Mixes the row of the given sprite into the sprite FIFO. Pixels already
+    /// occupied by a higher priority sprite are kept: with X priority that is
+    /// any earlier sprite, otherwise one with a lower OAM index.
+    fn fifo_merge_sprite(&mut self, index: usize, vram: &[u8]) {
+        let sprite = self.sprites[index];
+        let (low, high) = self.sprite_row(&sprite, vram);
+        let skip = (self.fifo.x as usize + 8)
+            .saturating_sub(sprite.x as usize)
+            .min(8);
+
+        while self.fifo.obj.len() < 8 {
+            self.fifo.obj.push_back(ObjPixel::default());
+        }
+
+        for (slot, pixel) in (skip..8).enumerate() {
+            let pixel = if sprite.x_flip() { 7 - pixel } else { pixel };
+            let color = tile_pixel(low, high, pixel as u8);
+            let existing = self.fifo.obj[slot];
+            let replace = existing.color == 0
+                || (!self.x_priority() && color != 0 && sprite.index < existing.index);
+            if replace {
+                self.fifo.obj[slot] = ObjPixel::new(&sprite, color);
+            }
+        }
+    }
+}
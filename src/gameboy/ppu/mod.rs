@@ -0,0 +1,854 @@
+use super::interrupts::{STAT, VBLANK};
+
+mod fifo;
+use fifo::Fifo;
+
+mod scanline;
+
+pub const LCDC_ADDR: u16 = 0xff40;
+pub const STAT_ADDR: u16 = 0xff41;
+pub const SCY_ADDR: u16 = 0xff42;
+pub const SCX_ADDR: u16 = 0xff43;
+pub const LY_ADDR: u16 = 0xff44;
+pub const LYC_ADDR: u16 = 0xff45;
+pub const BGP_ADDR: u16 = 0xff47;
+pub const OBP0_ADDR: u16 = 0xff48;
+pub const OBP1_ADDR: u16 = 0xff49;
+pub const WY_ADDR: u16 = 0xff4a;
+pub const WX_ADDR: u16 = 0xff4b;
+pub const BCPS_ADDR: u16 = 0xff68;
+pub const BCPD_ADDR: u16 = 0xff69;
+pub const OCPS_ADDR: u16 = 0xff6a;
+pub const OCPD_ADDR: u16 = 0xff6b;
+pub const OPRI_ADDR: u16 = 0xff6c;
+
+pub const LCD_WIDTH: usize = 160;
+pub const LCD_HEIGHT: usize = 144;
+
+/// The number of dots (T-cycles) in a single scanline.
+pub const DOTS_PER_LINE: u32 = 456;
+/// The number of scanlines in a frame, including the V-Blank lines.
+pub const LINES_PER_FRAME: u8 = 154;
+/// The number of dots spent in mode 2 (OAM scan).
+pub const OAM_SCAN_DOTS: u32 = 80;
+/// The fixed number of dots the scanline renderer spends in mode 3.
+pub const SCANLINE_DRAWING_DOTS: u32 = 172;
+
+/// The maximum number of sprites that can be displayed on a single scanline.
+const SPRITES_PER_LINE: usize = 10;
+
+/// The offset of VRAM bank 1 in the VRAM passed to the PPU (CGB only).
+pub const VRAM_BANK1_OFFSET: usize = 0x2000;
+/// The size of each of the CGB colour palette RAMs (eight palettes of four colours).
+const PALETTE_RAM_SIZE: usize = 64;
+
+/// The mode the PPU is in, as reported in the lower two bits of `STAT`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Mode {
+    HBlank = 0,
+    VBlank = 1,
+    OamScan = 2,
+    Drawing = 3,
+}
+
+/// The rendering strategy used by the PPU during mode 3.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum Renderer {
+    /// Renders a whole scanline at once at the start of mode 3. Fast, but
+    /// blind to register writes made while the line is being drawn.
+    #[default]
+    Scanline,
+    /// Emulates the pixel fetcher and the BG/OBJ FIFOs dot by dot, producing
+    /// variable mode 3 lengths and accurate mid-scanline effects.
+    Fifo,
+}
+
+/// The palette register a pixel of the framebuffer was drawn with.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum PaletteSource {
+    #[default]
+    Bg,
+    Obp0,
+    Obp1,
+}
+
+/// A sprite selected during OAM scan.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Sprite {
+    pub y: u8,
+    pub x: u8,
+    pub tile: u8,
+    pub flags: u8,
+    /// The index of the sprite in OAM.
+    pub index: u8,
+}
+
+impl Sprite {
+    /// Returns whether the sprite is drawn behind non-zero background colours.
+    pub fn behind_bg(&self) -> bool {
+        self.flags & 0b1000_0000 != 0
+    }
+
+    /// Returns whether the sprite is flipped vertically.
+    pub fn y_flip(&self) -> bool {
+        self.flags & 0b0100_0000 != 0
+    }
+
+    /// Returns whether the sprite is flipped horizontally.
+    pub fn x_flip(&self) -> bool {
+        self.flags & 0b0010_0000 != 0
+    }
+
+    /// Returns whether the sprite uses `OBP1` rather than `OBP0`.
+    pub fn uses_obp1(&self) -> bool {
+        self.flags & 0b0001_0000 != 0
+    }
+
+    /// Returns the CGB colour palette of the sprite.
+    pub fn cgb_palette(&self) -> u8 {
+        self.flags & 0b0000_0111
+    }
+
+    /// Returns the VRAM bank of the sprite's tile (CGB only).
+    pub fn vram_bank(&self) -> usize {
+        (self.flags as usize >> 3) & 1
+    }
+
+    /// Returns the palette register the sprite is drawn with.
+    pub fn palette_source(&self) -> PaletteSource {
+        if self.uses_obp1() {
+            PaletteSource::Obp1
+        } else {
+            PaletteSource::Obp0
+        }
+    }
+}
+
+/// A background/window pixel, before it is mixed with the sprites.
+#[derive(Debug, Default, Copy, Clone)]
+struct BgPixel {
+    color: u8,
+    /// The CGB colour palette.
+    palette: u8,
+    /// Whether the CGB tile attributes give the pixel priority over sprites.
+    priority: bool,
+}
+
+/// A sprite pixel, before it is mixed with the background.
+#[derive(Debug, Default, Copy, Clone)]
+struct ObjPixel {
+    color: u8,
+    source: PaletteSource,
+    /// The CGB colour palette.
+    palette: u8,
+    behind_bg: bool,
+    /// The OAM index of the sprite, used for the CGB priority.
+    index: u8,
+}
+
+impl ObjPixel {
+    /// Creates a new `ObjPixel` of the given colour, drawn by the given sprite.
+    fn new(sprite: &Sprite, color: u8) -> Self {
+        Self {
+            color,
+            source: sprite.palette_source(),
+            palette: sprite.cgb_palette(),
+            behind_bg: sprite.behind_bg(),
+            index: sprite.index,
+        }
+    }
+}
+
+/// Represents the picture processing unit of the GameBoy.
+#[derive(Debug, Clone)]
+pub struct Ppu {
+    pub lcdc: u8,
+    /// The writable bits (3-6) of `STAT`; the mode and coincidence bits are derived.
+    pub stat: u8,
+    pub scy: u8,
+    pub scx: u8,
+    pub ly: u8,
+    pub lyc: u8,
+    pub bgp: u8,
+    pub obp0: u8,
+    pub obp1: u8,
+    pub wy: u8,
+    pub wx: u8,
+    /// The CGB background palette specification (`BCPS`).
+    pub bcps: u8,
+    /// The CGB sprite palette specification (`OCPS`).
+    pub ocps: u8,
+    /// The CGB object priority mode (`OPRI`).
+    pub opri: u8,
+    /// The CGB background colour palette RAM.
+    pub bg_palettes: [u8; PALETTE_RAM_SIZE],
+    /// The CGB sprite colour palette RAM.
+    pub obj_palettes: [u8; PALETTE_RAM_SIZE],
+    /// Whether the CGB features (tile attributes, colour palettes) are enabled.
+    pub cgb: bool,
+    /// The current mode.
+    pub mode: Mode,
+    /// The rendering strategy used during mode 3. Changes take effect on the next line.
+    pub renderer: Renderer,
+    /// The shades (0-3) of the last rendered pixels, one byte per pixel.
+    pub framebuffer: Vec<u8>,
+    /// The palette each pixel of the framebuffer was drawn with, so that
+    /// frontends can colour the background and each sprite palette separately.
+    pub palette_sources: Vec<PaletteSource>,
+    /// The RGB555 colours of the last rendered pixels in CGB mode.
+    pub cgb_framebuffer: Vec<u16>,
+    /// The number of frames completed since power on.
+    pub frames: u64,
+    /// The number of dots spent in mode 3 on the most recently drawn line.
+    pub last_drawing_dots: u32,
+    /// The dot within the current scanline.
+    dot: u32,
+    /// The dot at which mode 3 started on the current scanline.
+    drawing_start: u32,
+    /// The renderer drawing the current scanline.
+    line_renderer: Renderer,
+    /// The internal line counter of the window.
+    window_line: u8,
+    /// Whether `WY` has matched `LY` at some point during this frame.
+    window_y_triggered: bool,
+    /// The sprites selected for the current scanline.
+    sprites: Vec<Sprite>,
+    /// The state of the pixel FIFO renderer.
+    fifo: Fifo,
+    /// The previous state of the STAT interrupt line, used for edge detection.
+    stat_line: bool,
+    /// Whether mode 0 has been entered since the last call to `take_hblank_started`.
+    hblank_started: bool,
+}
+
+impl Default for Ppu {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Ppu {
+    /// Creates a new `Ppu` instance with the LCD turned off.
+    pub fn new() -> Self {
+        Self {
+            lcdc: 0,
+            stat: 0,
+            scy: 0,
+            scx: 0,
+            ly: 0,
+            lyc: 0,
+            bgp: 0,
+            obp0: 0,
+            obp1: 0,
+            wy: 0,
+            wx: 0,
+            bcps: 0,
+            ocps: 0,
+            opri: 0,
+            bg_palettes: [0; PALETTE_RAM_SIZE],
+            obj_palettes: [0; PALETTE_RAM_SIZE],
+            cgb: false,
+            mode: Mode::HBlank,
+            renderer: Renderer::default(),
+            framebuffer: vec![0; LCD_WIDTH * LCD_HEIGHT],
+            palette_sources: vec![PaletteSource::Bg; LCD_WIDTH * LCD_HEIGHT],
+            cgb_framebuffer: vec![0x7fff; LCD_WIDTH * LCD_HEIGHT],
+            frames: 0,
+            last_drawing_dots: 0,
+            dot: 0,
+            drawing_start: 0,
+            line_renderer: Renderer::default(),
+            window_line: 0,
+            window_y_triggered: false,
+            sprites: Vec::with_capacity(SPRITES_PER_LINE),
+            fifo: Fifo::default(),
+            stat_line: false,
+            hblank_started: false,
+        }
+    }
+
+    /// Returns whether the LCD is turned on (`LCDC` bit 7).
+    pub fn lcd_enabled(&self) -> bool {
+        self.lcdc & 0b1000_0000 != 0
+    }
+
+    /// Reads one of the PPU registers.
+    pub fn read_register(&self, addr: u16) -> u8 {
+        match addr {
+            LCDC_ADDR => self.lcdc,
+            STAT_ADDR => {
+                let coincidence = if self.ly == self.lyc { 0b0100 } else { 0 };
+                0b1000_0000 | self.stat | coincidence | self.mode as u8
+            }
+            SCY_ADDR => self.scy,
+            SCX_ADDR => self.scx,
+            LY_ADDR => self.ly,
+            LYC_ADDR => self.lyc,
+            BGP_ADDR => self.bgp,
+            OBP0_ADDR => self.obp0,
+            OBP1_ADDR => self.obp1,
+            WY_ADDR => self.wy,
+            WX_ADDR => self.wx,
+            BCPS_ADDR => 0b0100_0000 | self.bcps,
+            BCPD_ADDR => self.bg_palettes[(self.bcps & 0x3f) as usize],
+            OCPS_ADDR => 0b0100_0000 | self.ocps,
+            OCPD_ADDR => self.obj_palettes[(self.ocps & 0x3f) as usize],
+            OPRI_ADDR => 0b1111_1110 | self.opri,
+            _ => panic!("Attempted to read non-PPU register {:#06x}", addr),
+        }
+    }
+
+    /// Writes one of the PPU registers.
+    pub fn write_register(&mut self, addr: u16, value: u8) {
+        match addr {
+            LCDC_ADDR => {
+                let was_enabled = self.lcd_enabled();
+                self.lcdc = value;
+                if was_enabled && !self.lcd_enabled() {
+                    self.turn_off();
+                } else if !was_enabled && self.lcd_enabled() {
+                    self.turn_on();
+                }
+            }
+            STAT_ADDR => self.stat = value & 0b0111_1000,
+            SCY_ADDR => self.scy = value,
+            SCX_ADDR => self.scx = value,
+            LY_ADDR => {} // Read-only
+            LYC_ADDR => self.lyc = value,
+            BGP_ADDR => self.bgp = value,
+            OBP0_ADDR => self.obp0 = value,
+            OBP1_ADDR => self.obp1 = value,
+            WY_ADDR => self.wy = value,
+            WX_ADDR => self.wx = value,
+            BCPS_ADDR => self.bcps = value & 0b1011_1111,
+            BCPD_ADDR => {
+                self.bg_palettes[(self.bcps & 0x3f) as usize] = value;
+                self.bcps = auto_increment(self.bcps);
+            }
+            OCPS_ADDR => self.ocps = value & 0b1011_1111,
+            OCPD_ADDR => {
+                self.obj_palettes[(self.ocps & 0x3f) as usize] = value;
+                self.ocps = auto_increment(self.ocps);
+            }
+            OPRI_ADDR => self.opri = value & 1,
+            _ => panic!("Attempted to write non-PPU register {:#06x}", addr),
+        }
+    }
+
+    /// Resets the PPU state when the LCD is switched off.
+    fn turn_off(&mut self) {
+        self.ly = 0;
+        self.dot = 0;
+        self.mode = Mode::HBlank;
+        self.window_line = 0;
+        self.window_y_triggered = false;
+        self.stat_line = false;
+    }
+
+    /// Starts the first scanline when the LCD is switched on.
+    fn turn_on(&mut self) {
+        self.ly = 0;
+        self.dot = 0;
+        self.enter_oam_scan();
+    }
+
+    /// Advances the PPU by the given number of dots.
+    /// Returns the interrupts requested in the meantime.
+    pub fn tick(&mut self, cycles: u32, vram: &[u8], oam: &[u8]) -> u8 {
+        let mut interrupts = 0;
+        if !self.lcd_enabled() {
+            return interrupts;
+        }
+
+        for _ in 0..cycles {
+            interrupts |= self.step_dot(vram, oam);
+        }
+
+        interrupts
+    }
+
+    /// Returns the number of dots until the next change of mode or line, or
+    /// `None` while the LCD is off. In mode 3 of the FIFO renderer, whose
+    /// length isn't known in advance, this is only a lower bound.
+    pub fn dots_to_next_event(&self) -> Option<u32> {
+        if !self.lcd_enabled() {
+            return None;
+        }
+
+        let dots = match self.mode {
+            Mode::OamScan => OAM_SCAN_DOTS - self.dot,
+            Mode::Drawing => match self.line_renderer {
+                Renderer::Scanline => self.drawing_start + SCANLINE_DRAWING_DOTS - self.dot,
+                Renderer::Fifo => self.fifo_min_dots_left(),
+            },
+            Mode::HBlank | Mode::VBlank => DOTS_PER_LINE - self.dot,
+        };
+        Some(dots)
+    }
+
+    /// Advances the PPU by a single dot.
+    fn step_dot(&mut self, vram: &[u8], oam: &[u8]) -> u8 {
+        let mut interrupts = 0;
+
+        match self.mode {
+            Mode::OamScan => {
+                if self.dot == 0 {
+                    self.scan_oam(oam);
+                }
+                if self.dot + 1 == OAM_SCAN_DOTS {
+                    self.enter_drawing(vram);
+                }
+            }
+            Mode::Drawing => match self.line_renderer {
+                Renderer::Scanline => {
+                    if self.dot + 1 == self.drawing_start + SCANLINE_DRAWING_DOTS {
+                        self.enter_hblank();
+                    }
+                }
+                Renderer::Fifo => {
+                    if self.fifo_step(vram) {
+                        self.enter_hblank();
+                    }
+                }
+            },
+            Mode::HBlank | Mode::VBlank => {}
+        }
+
+        self.dot += 1;
+        if self.dot == DOTS_PER_LINE {
+            self.dot = 0;
+            self.ly += 1;
+
+            if self.ly == LCD_HEIGHT as u8 {
+                self.mode = Mode::VBlank;
+                self.frames += 1;
+                interrupts |= VBLANK;
+            } else if self.ly == LINES_PER_FRAME {
+                self.ly = 0;
+                self.window_line = 0;
+                self.window_y_triggered = false;
+                self.enter_oam_scan();
+            } else if self.mode != Mode::VBlank {
+                self.enter_oam_scan();
+            }
+        }
+
+        if self.update_stat_line() {
+            interrupts |= STAT;
+        }
+
+        interrupts
+    }
+
+    /// Enters mode 2 at the start of a visible scanline.
+    fn enter_oam_scan(&mut self) {
+        self.mode = Mode::OamScan;
+        if self.ly == self.wy {
+            self.window_y_triggered = true;
+        }
+    }
+
+    /// Enters mode 3 and prepares the selected renderer.
+    fn enter_drawing(&mut self, vram: &[u8]) {
+        self.mode = Mode::Drawing;
+        self.drawing_start = self.dot + 1;
+        self.line_renderer = self.renderer;
+        match self.line_renderer {
+            Renderer::Scanline => self.render_scanline(vram),
+            Renderer::Fifo => self.fifo_start_line(),
+        }
+    }
+
+    /// Enters mode 0 once the scanline has been drawn.
+    fn enter_hblank(&mut self) {
+        self.mode = Mode::HBlank;
+        self.last_drawing_dots = self.dot + 1 - self.drawing_start;
+        self.hblank_started = true;
+    }
+
+    /// Returns the row of OAM (8 bytes, two sprites) read during the current
+    /// dot of mode 2, if in mode 2.
+    pub fn oam_scan_row(&self) -> Option<usize> {
+        if self.mode == Mode::OamScan {
+            Some(self.dot as usize / 4)
+        } else {
+            None
+        }
+    }
+
+    /// Returns whether a visible line has entered mode 0 since the last call.
+    pub fn take_hblank_started(&mut self) -> bool {
+        std::mem::take(&mut self.hblank_started)
+    }
+
+    /// Updates the STAT interrupt line, returning `true` on a rising edge.
+    fn update_stat_line(&mut self) -> bool {
+        let line = (self.stat & 0b0100_0000 != 0 && self.ly == self.lyc)
+            || (self.stat & 0b0010_0000 != 0 && self.mode == Mode::OamScan)
+            || (self.stat & 0b0001_0000 != 0 && self.mode == Mode::VBlank)
+            || (self.stat & 0b0000_1000 != 0 && self.mode == Mode::HBlank);
+
+        let rising = line && !self.stat_line;
+        self.stat_line = line;
+        rising
+    }
+
+    /// Selects up to ten sprites overlapping the current scanline, in OAM order.
+    fn scan_oam(&mut self, oam: &[u8]) {
+        let height = self.sprite_height();
+        self.sprites.clear();
+
+        for (index, entry) in oam.chunks_exact(4).enumerate() {
+            let y = entry[0];
+            let line = self.ly as u16 + 16;
+            if line >= y as u16 && line < y as u16 + height as u16 {
+                self.sprites.push(Sprite {
+                    y,
+                    x: entry[1],
+                    tile: entry[2],
+                    flags: entry[3],
+                    index: index as u8,
+                });
+                if self.sprites.len() == SPRITES_PER_LINE {
+                    break;
+                }
+            }
+        }
+    }
+
+    /// Returns the height of sprites in pixels (`LCDC` bit 2).
+    fn sprite_height(&self) -> u8 {
+        if self.lcdc & 0b0000_0100 != 0 {
+            16
+        } else {
+            8
+        }
+    }
+
+    /// Returns whether the background and window are enabled (`LCDC` bit 0).
+    fn bg_enabled(&self) -> bool {
+        self.lcdc & 0b0000_0001 != 0
+    }
+
+    /// Returns whether sprites are enabled (`LCDC` bit 1).
+    fn sprites_enabled(&self) -> bool {
+        self.lcdc & 0b0000_0010 != 0
+    }
+
+    /// Returns whether the window is enabled (`LCDC` bit 5).
+    fn window_enabled(&self) -> bool {
+        self.lcdc & 0b0010_0000 != 0
+    }
+
+    /// Returns the VRAM offset of the background tile map (`LCDC` bit 3).
+    pub(crate) fn bg_map_offset(&self) -> usize {
+        if self.lcdc & 0b0000_1000 != 0 {
+            0x1c00
+        } else {
+            0x1800
+        }
+    }
+
+    /// Returns the VRAM offset of the window tile map (`LCDC` bit 6).
+    fn window_map_offset(&self) -> usize {
+        if self.lcdc & 0b0100_0000 != 0 {
+            0x1c00
+        } else {
+            0x1800
+        }
+    }
+
+    /// Returns the VRAM offset of the given background/window tile,
+    /// honouring the addressing mode selected by `LCDC` bit 4.
+    pub(crate) fn bg_tile_offset(&self, tile: u8) -> usize {
+        if self.lcdc & 0b0001_0000 != 0 {
+            tile as usize * 16
+        } else {
+            (0x1000 + (tile as i8 as isize) * 16) as usize
+        }
+    }
+
+    /// Returns whether sprite priority is decided by X coordinate (DMG) rather
+    /// than by OAM index (CGB, unless `OPRI` selects the DMG behaviour).
+    fn x_priority(&self) -> bool {
+        !self.cgb || self.opri & 1 != 0
+    }
+
+    /// Returns the CGB attributes of the background/window map entry at the
+    /// given VRAM offset, or zero in DMG mode.
+    fn bg_attributes(&self, vram: &[u8], map_offset: usize) -> u8 {
+        if self.cgb {
+            vram[VRAM_BANK1_OFFSET + map_offset]
+        } else {
+            0
+        }
+    }
+
+    /// Returns the VRAM offset of the given row of a background/window tile,
+    /// honouring the CGB bank and vertical flip attributes.
+    fn bg_row_offset(&self, tile: u8, attributes: u8, row: u8) -> usize {
+        let row = if attributes & 0b0100_0000 != 0 {
+            7 - row
+        } else {
+            row
+        };
+        let bank = if attributes & 0b0000_1000 != 0 {
+            VRAM_BANK1_OFFSET
+        } else {
+            0
+        };
+        bank + self.bg_tile_offset(tile) + row as usize * 2
+    }
+
+    /// Returns the two bytes of the given sprite's tile row for the current scanline.
+    fn sprite_row(&self, sprite: &Sprite, vram: &[u8]) -> (u8, u8) {
+        // The sprite size may have changed since the OAM scan, so the row is wrapped into it
+        let height = self.sprite_height();
+        let mut row = self.ly.wrapping_add(16).wrapping_sub(sprite.y) & (height - 1);
+        if sprite.y_flip() {
+            row = height - 1 - row;
+        }
+
+        let tile = if height == 16 {
+            sprite.tile & 0xfe
+        } else {
+            sprite.tile
+        };
+        let bank = if self.cgb {
+            sprite.vram_bank() * VRAM_BANK1_OFFSET
+        } else {
+            0
+        };
+        let offset = bank + tile as usize * 16 + row as usize * 2;
+        (vram[offset], vram[offset + 1])
+    }
+
+    /// Mixes a background and sprite pixel and writes the result to the framebuffer.
+    fn mix_pixel(&mut self, x: usize, bg: BgPixel, obj: Option<ObjPixel>) {
+        let index = self.ly as usize * LCD_WIDTH + x;
+
+        if self.cgb {
+            // In CGB mode `LCDC` bit 0 is the master priority rather than a BG enable
+            let obj = obj.filter(|obj| {
+                obj.color != 0
+                    && self.sprites_enabled()
+                    && (!self.bg_enabled() || bg.color == 0 || !(bg.priority || obj.behind_bg))
+            });
+            self.cgb_framebuffer[index] = match obj {
+                Some(obj) => cgb_color(&self.obj_palettes, obj.palette, obj.color),
+                None => cgb_color(&self.bg_palettes, bg.palette, bg.color),
+            };
+            return;
+        }
+
+        let bg_color = if self.bg_enabled() { bg.color } else { 0 };
+        let obj = obj.filter(|obj| {
+            obj.color != 0 && self.sprites_enabled() && !(obj.behind_bg && bg_color != 0)
+        });
+        let (shade, source) = match obj {
+            Some(obj) if obj.source == PaletteSource::Obp1 => {
+                (palette_shade(self.obp1, obj.color), obj.source)
+            }
+            Some(obj) => (palette_shade(self.obp0, obj.color), obj.source),
+            None => (palette_shade(self.bgp, bg_color), PaletteSource::Bg),
+        };
+        self.framebuffer[index] = shade;
+        self.palette_sources[index] = source;
+    }
+}
+
+/// Advances the address of a CGB palette specification register if its
+/// auto-increment bit (bit 7) is set.
+fn auto_increment(spec: u8) -> u8 {
+    if spec & 0b1000_0000 != 0 {
+        0b1000_0000 | ((spec + 1) & 0x3f)
+    } else {
+        spec
+    }
+}
+
+/// Returns the RGB555 colour of the given colour index of a CGB palette.
+fn cgb_color(palette_ram: &[u8; PALETTE_RAM_SIZE], palette: u8, color: u8) -> u16 {
+    let offset = palette as usize * 8 + color as usize * 2;
+    u16::from_le_bytes([palette_ram[offset], palette_ram[offset + 1]]) & 0x7fff
+}
+
+/// Returns the colour index (0-3) of the given pixel of a tile row.
+/// Pixel 0 is the leftmost pixel.
+fn tile_pixel(low: u8, high: u8, pixel: u8) -> u8 {
+    let bit = 7 - pixel;
+    (((high >> bit) & 1) << 1) | ((low >> bit) & 1)
+}
+
+/// Maps a colour index through a DMG palette register to a shade.
+pub fn palette_shade(palette: u8, color: u8) -> u8 {
+    (palette >> (color * 2)) & 0b11
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VRAM_LEN: usize = 0x2000;
+    const OAM_LEN: usize = 0xa0;
+
+    /// Returns a VRAM image where tile 1 is solid colour 3 and the background
+    /// map shows tile 1 in every other column.
+    fn striped_vram() -> Vec<u8> {
+        let mut vram = vec![0; VRAM_LEN];
+        for byte in &mut vram[16..32] {
+            *byte = 0xff;
+        }
+        for column in (0..32).step_by(2) {
+            for row in 0..32 {
+                vram[0x1800 + row * 32 + column] = 1;
+            }
+        }
+        vram
+    }
+
+    fn run_frame(ppu: &mut Ppu, vram: &[u8], oam: &[u8]) -> u8 {
+        ppu.tick(DOTS_PER_LINE * LINES_PER_FRAME as u32, vram, oam)
+    }
+
+    #[test]
+    fn test_frame_timing() {
+        let mut ppu = Ppu::new();
+        ppu.write_register(LCDC_ADDR, 0b1001_0001);
+        let vram = vec![0; VRAM_LEN];
+        let oam = vec![0; OAM_LEN];
+
+        assert_eq!(ppu.mode, Mode::OamScan);
+        ppu.tick(OAM_SCAN_DOTS, &vram, &oam);
+        assert_eq!(ppu.mode, Mode::Drawing);
+        ppu.tick(DOTS_PER_LINE - OAM_SCAN_DOTS, &vram, &oam);
+        assert_eq!(ppu.ly, 1);
+
+        let interrupts = ppu.tick(DOTS_PER_LINE * 143, &vram, &oam);
+        assert_eq!(ppu.ly, 144);
+        assert_eq!(ppu.mode, Mode::VBlank);
+        assert_eq!(interrupts & VBLANK, VBLANK);
+    }
+
+    #[test]
+    fn test_lyc_stat_interrupt() {
+        let mut ppu = Ppu::new();
+        ppu.write_register(LCDC_ADDR, 0b1001_0001);
+        ppu.write_register(LYC_ADDR, 2);
+        ppu.write_register(STAT_ADDR, 0b0100_0000);
+        let vram = vec![0; VRAM_LEN];
+        let oam = vec![0; OAM_LEN];
+
+        assert_eq!(ppu.tick(DOTS_PER_LINE, &vram, &oam) & STAT, 0);
+        assert_eq!(ppu.tick(DOTS_PER_LINE, &vram, &oam) & STAT, STAT);
+        assert_eq!(ppu.read_register(STAT_ADDR) & 0b0100, 0b0100);
+    }
+
+    #[test]
+    fn test_renderers_agree() {
+        let vram = striped_vram();
+        let mut oam = vec![0; OAM_LEN];
+        // A sprite using tile 1 at the top-left corner of the screen.
+        oam[0] = 16;
+        oam[1] = 12;
+        oam[2] = 1;
+
+        let mut scanline = Ppu::new();
+        let mut fifo = Ppu::new();
+        fifo.renderer = Renderer::Fifo;
+        for ppu in [&mut scanline, &mut fifo] {
+            ppu.write_register(BGP_ADDR, 0b1110_0100);
+            ppu.write_register(OBP0_ADDR, 0b0101_0101);
+            ppu.write_register(SCX_ADDR, 3);
+            ppu.write_register(LCDC_ADDR, 0b1001_0011);
+            run_frame(ppu, &vram, &oam);
+        }
+
+        assert_eq!(scanline.framebuffer, fifo.framebuffer);
+        assert_eq!(scanline.framebuffer[0..8], [3, 3, 3, 3, 1, 1, 1, 1]);
+        assert_eq!(scanline.palette_sources, fifo.palette_sources);
+        assert_eq!(scanline.palette_sources[3], PaletteSource::Bg);
+        assert_eq!(scanline.palette_sources[4], PaletteSource::Obp0);
+    }
+
+    #[test]
+    fn test_sprite_size_change_mid_line() {
+        let vram = striped_vram();
+        let mut oam = vec![0; OAM_LEN];
+        // A flipped 8x16 sprite whose lower half is on the first line
+        oam[0] = 8;
+        oam[1] = 8;
+        oam[2] = 1;
+        oam[3] = 0b0100_0000;
+
+        for renderer in [Renderer::Scanline, Renderer::Fifo] {
+            let mut ppu = Ppu::new();
+            ppu.renderer = renderer;
+            ppu.write_register(OBP0_ADDR, 0b0101_0101);
+            ppu.write_register(LCDC_ADDR, 0b1001_0110);
+            ppu.tick(OAM_SCAN_DOTS, &vram, &oam);
+
+            // Switching to 8x8 sprites after the scan wraps the row into the smaller sprite
+            ppu.write_register(LCDC_ADDR, 0b1001_0010);
+            ppu.tick(DOTS_PER_LINE - OAM_SCAN_DOTS, &vram, &oam);
+            assert_eq!(ppu.ly, 1);
+        }
+    }
+
+    #[test]
+    fn test_fifo_variable_drawing_length() {
+        let vram = striped_vram();
+        let oam = vec![0; OAM_LEN];
+        let mut ppu = Ppu::new();
+        ppu.renderer = Renderer::Fifo;
+        ppu.write_register(LCDC_ADDR, 0b1001_0001);
+
+        ppu.tick(DOTS_PER_LINE, &vram, &oam);
+        let unscrolled = ppu.last_drawing_dots;
+
+        ppu.write_register(SCX_ADDR, 5);
+        ppu.tick(DOTS_PER_LINE, &vram, &oam);
+        assert_eq!(ppu.last_drawing_dots, unscrolled + 5);
+    }
+
+    #[test]
+    fn test_fifo_mid_scanline_palette_change() {
+        let vram = striped_vram();
+        let oam = vec![0; OAM_LEN];
+        let mut ppu = Ppu::new();
+        ppu.renderer = Renderer::Fifo;
+        ppu.write_register(BGP_ADDR, 0b1111_1111);
+        ppu.write_register(LCDC_ADDR, 0b1001_0001);
+
+        ppu.tick(OAM_SCAN_DOTS + 90, &vram, &oam);
+        ppu.write_register(BGP_ADDR, 0b0000_0000);
+        ppu.tick(DOTS_PER_LINE - OAM_SCAN_DOTS - 90, &vram, &oam);
+
+        assert_eq!(ppu.framebuffer[0], 3);
+        assert_eq!(ppu.framebuffer[LCD_WIDTH - 1], 0);
+    }
+
+    #[test]
+    fn test_cgb_palettes() {
+        let mut vram = striped_vram();
+        vram.resize(VRAM_BANK1_OFFSET * 2, 0);
+        let oam = vec![0; OAM_LEN];
+        let mut ppu = Ppu::new();
+        ppu.cgb = true;
+
+        // Colour 3 of palette 0 is pure red, written with auto-increment
+        ppu.write_register(BCPS_ADDR, 0b1000_0110);
+        ppu.write_register(BCPD_ADDR, 0x1f);
+        ppu.write_register(BCPD_ADDR, 0x00);
+        assert_eq!(ppu.read_register(BCPS_ADDR), 0b1100_1000);
+
+        for renderer in [Renderer::Scanline, Renderer::Fifo] {
+            ppu.renderer = renderer;
+            ppu.write_register(LCDC_ADDR, 0b1001_0001);
+            run_frame(&mut ppu, &vram, &oam);
+            assert_eq!(ppu.cgb_framebuffer[0], 0x001f);
+            assert_eq!(ppu.cgb_framebuffer[8], 0x0000);
+            ppu.write_register(LCDC_ADDR, 0);
+        }
+    }
+}
@@ -0,0 +1,73 @@
+use super::{tile_pixel, BgPixel, ObjPixel, Ppu, LCD_WIDTH};
+
+impl Ppu {
+    /// Renders the whole current scanline at once, using the register values
+    /// as they are at the start of mode 3.
+    pub(super) fn render_scanline(&mut self, vram: &[u8]) {
+        let mut bg_pixels = [BgPixel::default(); LCD_WIDTH];
+        let mut window_drawn = false;
+        let window_visible = self.window_enabled() && self.window_y_triggered;
+
+        for (x, bg_pixel) in bg_pixels.iter_mut().enumerate() {
+            let (map, px, py) = if window_visible && x + 7 >= self.wx as usize {
+                window_drawn = true;
+                (
+                    self.window_map_offset(),
+                    x + 7 - self.wx as usize,
+                    self.window_line,
+                )
+            } else {
+                (
+                    self.bg_map_offset(),
+                    (self.scx as usize + x) & 0xff,
+                    self.ly.wrapping_add(self.scy),
+                )
+            };
+
+            let map_offset = map + (py as usize / 8) * 32 + px / 8;
+            let tile = vram[map_offset];
+            let attributes = self.bg_attributes(vram, map_offset);
+            let offset = self.bg_row_offset(tile, attributes, py % 8);
+
+            let pixel = if attributes & 0b0010_0000 != 0 {
+                7 - px % 8
+            } else {
+                px % 8
+            };
+            *bg_pixel = BgPixel {
+                color: tile_pixel(vram[offset], vram[offset + 1], pixel as u8),
+                palette: attributes & 0b0000_0111,
+                priority: attributes & 0b1000_0000 != 0,
+            };
+        }
+
+        if window_drawn {
+            self.window_line += 1;
+        }
+
+        // With X priority, lower X wins and ties are broken by the OAM index
+        // (the sort is stable). Otherwise the OAM order alone decides.
+        let mut sprites = self.sprites.clone();
+        if self.x_priority() {
+            sprites.sort_by_key(|sprite| sprite.x);
+        }
+
+        for (x, bg_pixel) in bg_pixels.into_iter().enumerate() {
+            let obj = sprites
+                .iter()
+                .filter(|sprite| x + 8 >= sprite.x as usize && x < sprite.x as usize)
+                .find_map(|sprite| {
+                    let mut pixel = (x + 8 - sprite.x as usize) as u8;
+                    if sprite.x_flip() {
+                        pixel = 7 - pixel;
+                    }
+
+                    let (low, high) = self.sprite_row(sprite, vram);
+                    let color = tile_pixel(low, high, pixel);
+                    (color != 0).then(|| ObjPixel::new(sprite, color))
+                });
+
+            self.mix_pixel(x, bg_pixel, obj);
+        }
+    }
+}
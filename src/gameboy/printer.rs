@@ -0,0 +1,444 @@
+use std::fs::File;
+use std::io::{self, BufWriter};
+use std::path::{Path, PathBuf};
+
+use super::serial::SerialDevice;
+
+/// The width of the paper, in pixels.
+pub const PRINTER_WIDTH: usize = 160;
+
+/// The bytes starting every packet.
+const MAGIC: [u8; 2] = [0x88, 0x33];
+/// The byte the printer answers with after the checksum, to tell it is there.
+const ALIVE: u8 = 0x81;
+
+const INIT: u8 = 0x01;
+const PRINT: u8 = 0x02;
+const DATA: u8 = 0x04;
+const BREAK: u8 = 0x08;
+const STATUS: u8 = 0x0f;
+
+/// The bits of the status byte.
+const CHECKSUM_ERROR: u8 = 0b0000_0001;
+const PRINTING: u8 = 0b0000_0010;
+const IMAGE_FULL: u8 = 0b0000_0100;
+const UNPROCESSED: u8 = 0b0000_1000;
+
+/// The most image data the printer holds, 9 bands of 2 rows of 20 tiles.
+const BUFFER_SIZE: usize = 9 * 0x280;
+/// The number of bytes of a tile, and of tiles across the paper.
+const TILE_SIZE: usize = 16;
+const TILES_PER_ROW: usize = PRINTER_WIDTH / 8;
+/// The number of pixel rows fed for each unit of margin.
+const MARGIN_ROWS: usize = 8;
+/// The number of status requests answered as busy after a print starts.
+const PRINT_POLLS: u8 = 4;
+/// The exposure used when the game leaves it at 0, halfway between lightest and darkest.
+const DEFAULT_EXPOSURE: u8 = 0x40;
+
+/// Where the next byte received goes in a packet.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Stage {
+    Magic(usize),
+    Command,
+    Compression,
+    LengthLow,
+    LengthHigh,
+    Data,
+    ChecksumLow,
+    ChecksumHigh,
+    Alive,
+    Status,
+}
+
+/// The Game Boy Printer, rendering what it prints to PNG files.
+///
+/// Packets start with `MAGIC`, then a command, a compression flag, the length
+/// of the data and the data itself, a checksum of all of those, and two bytes
+/// during which the printer answers `ALIVE` and its status. Images are sent
+/// in bands of 2 tile rows with `DATA` packets, then printed with `PRINT`.
+/// The paper keeps feeding out until a print ends with a bottom margin, at
+/// which point the printout is saved.
+#[derive(Debug)]
+pub struct Printer {
+    /// Where the printouts are saved.
+    dir: PathBuf,
+    stage: Stage,
+    command: u8,
+    compressed: bool,
+    length: u16,
+    data: Vec<u8>,
+    /// The checksum computed over the packet received so far, and the one sent.
+    checksum: u16,
+    expected_checksum: u16,
+    status: u8,
+    /// The status requests left before the print in progress finishes.
+    print_polls: u8,
+    /// The decompressed image data waiting to be printed.
+    buffer: Vec<u8>,
+    /// The shades (0-255) of the rows printed since the paper was last cut.
+    printout: Vec<u8>,
+    /// The index of the last printout saved.
+    printed: usize,
+}
+
+impl Printer {
+    /// Creates a printer saving its printouts to the given directory, creating
+    /// it if needed.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        let dir = dir.into();
+        if let Err(err) = std::fs::create_dir_all(&dir) {
+            eprintln!("Failed to create {}: {}", dir.display(), err);
+        }
+        Self {
+            dir,
+            stage: Stage::Magic(0),
+            command: 0,
+            compressed: false,
+            length: 0,
+            data: Vec::new(),
+            checksum: 0,
+            expected_checksum: 0,
+            status: 0,
+            print_polls: 0,
+            buffer: Vec::new(),
+            printout: Vec::new(),
+            printed: 0,
+        }
+    }
+
+    /// Returns the height of the paper printed since it was last cut, in pixels.
+    pub fn printout_height(&self) -> usize {
+        self.printout.len() / PRINTER_WIDTH
+    }
+
+    /// Receives the next byte of a packet, returning the byte to answer with.
+    fn receive(&mut self, byte: u8) -> u8 {
+        if !matches!(
+            self.stage,
+            Stage::Magic(_) | Stage::ChecksumLow | Stage::ChecksumHigh
+        ) {
+            self.checksum = self.checksum.wrapping_add(byte as u16);
+        }
+
+        let mut answer = 0;
+        self.stage = match self.stage {
+            Stage::Magic(i) if byte == MAGIC[i] && i + 1 == MAGIC.len() => {
+                self.checksum = 0;
+                Stage::Command
+            }
+            Stage::Magic(i) if byte == MAGIC[i] => Stage::Magic(i + 1),
+            Stage::Magic(_) => Stage::Magic(0),
+            Stage::Command => {
+                self.command = byte;
+                Stage::Compression
+            }
+            Stage::Compression => {
+                self.compressed = byte & 1 != 0;
+                Stage::LengthLow
+            }
+            Stage::LengthLow => {
+                self.length = byte as u16;
+                Stage::LengthHigh
+            }
+            Stage::LengthHigh => {
+                self.length |= (byte as u16) << 8;
+                self.data.clear();
+                if self.length == 0 {
+                    Stage::ChecksumLow
+                } else {
+                    Stage::Data
+                }
+            }
+            Stage::Data => {
+                self.data.push(byte);
+                if self.data.len() == self.length as usize {
+                    Stage::ChecksumLow
+                } else {
+                    Stage::Data
+                }
+            }
+            Stage::ChecksumLow => {
+                self.expected_checksum = byte as u16;
+                Stage::ChecksumHigh
+            }
+            Stage::ChecksumHigh => {
+                self.expected_checksum |= (byte as u16) << 8;
+                Stage::Alive
+            }
+            Stage::Alive => {
+                answer = ALIVE;
+                if self.checksum == self.expected_checksum {
+                    self.status &= !CHECKSUM_ERROR;
+                    self.run_command();
+                } else {
+                    self.status |= CHECKSUM_ERROR;
+                }
+                Stage::Status
+            }
+            Stage::Status => {
+                answer = self.status;
+                Stage::Magic(0)
+            }
+        };
+        answer
+    }
+
+    /// Runs the command of the packet just received.
+    fn run_command(&mut self) {
+        match self.command {
+            INIT => {
+                self.buffer.clear();
+                self.status = 0;
+                self.print_polls = 0;
+            }
+            DATA if self.compressed => {
+                let data = decompress(&self.data);
+                self.store(&data);
+            }
+            DATA => {
+                let data = std::mem::take(&mut self.data);
+                self.store(&data);
+                self.data = data;
+            }
+            // The first byte is the number of copies, which is ignored
+            PRINT if self.data.len() == 4 => {
+                self.print(self.data[1], self.data[2], self.data[3]);
+            }
+            BREAK => {
+                self.buffer.clear();
+                self.print_polls = 0;
+                self.status &= !(PRINTING | IMAGE_FULL | UNPROCESSED);
+            }
+            STATUS if self.print_polls > 0 => {
+                self.print_polls -= 1;
+                if self.print_polls == 0 {
+                    self.status &= !PRINTING;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Adds image data to the buffer; an empty band marks the end of the image.
+    fn store(&mut self, data: &[u8]) {
+        let room = BUFFER_SIZE - self.buffer.len();
+        self.buffer.extend_from_slice(&data[..data.len().min(room)]);
+        self.status |= UNPROCESSED;
+        if data.is_empty() || self.buffer.len() == BUFFER_SIZE {
+            self.status |= IMAGE_FULL;
+        }
+    }
+
+    /// Prints the buffered image, fed with the margins in the high (before)
+    /// and low (after) nibbles, saving the printout once it ends with a margin.
+    fn print(&mut self, margins: u8, palette: u8, exposure: u8) {
+        self.feed((margins >> 4) as usize * MARGIN_ROWS);
+
+        // A palette of 0 is treated as the usual one, darkest last
+        let palette = if palette == 0 { 0b1110_0100 } else { palette };
+        let exposure = if exposure & 0x7f == 0 {
+            DEFAULT_EXPOSURE
+        } else {
+            exposure & 0x7f
+        };
+        let rows = render_tiles(&self.buffer);
+        self.printout.extend(
+            rows.into_iter()
+                .map(|color| shade((palette >> (color * 2)) & 0b11, exposure)),
+        );
+
+        self.buffer.clear();
+        self.status = (self.status & !(IMAGE_FULL | UNPROCESSED)) | PRINTING;
+        self.print_polls = PRINT_POLLS;
+
+        let bottom = (margins & 0x0f) as usize;
+        if bottom > 0 {
+            self.feed(bottom * MARGIN_ROWS);
+            self.cut();
+        }
+    }
+
+    /// Feeds the given number of blank pixel rows.
+    fn feed(&mut self, rows: usize) {
+        self.printout
+            .resize(self.printout.len() + rows * PRINTER_WIDTH, 0xff);
+    }
+
+    /// Saves the printout to a new PNG file and starts a new one, reporting the
+    /// outcome on stderr. The printouts of earlier sessions are kept.
+    fn cut(&mut self) {
+        let printout = std::mem::take(&mut self.printout);
+        let path = loop {
+            self.printed += 1;
+            let path = self.dir.join(format!("printout-{}.png", self.printed));
+            if !path.exists() {
+                break path;
+            }
+        };
+        match write_png(&path, &printout) {
+            Ok(()) => eprintln!("Printed to {}", path.display()),
+            Err(err) => eprintln!("Failed to save printout to {}: {}", path.display(), err),
+        }
+    }
+}
+
+impl SerialDevice for Printer {
+    fn transfer(&mut self, byte: u8) -> u8 {
+        self.receive(byte)
+    }
+
+    fn power_off(&mut self) {
+        // Whatever was printed without a bottom margin still comes out
+        if !self.printout.is_empty() {
+            self.cut();
+        }
+    }
+}
+
+/// Expands data compressed with run-length encoding: each control byte is
+/// followed either by `n + 1` literal bytes, or if its top bit is set, by a
+/// byte repeated `(n & 0x7f) + 2` times.
+fn decompress(data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::new();
+    let mut bytes = data.iter();
+    while let Some(control) = bytes.next() {
+        if control & 0x80 != 0 {
+            let Some(byte) = bytes.next() else { break };
+            out.extend(std::iter::repeat_n(*byte, (control & 0x7f) as usize + 2));
+        } else {
+            out.extend(bytes.by_ref().take(*control as usize + 1));
+        }
+    }
+    out
+}
+
+/// Decodes the given tiles, 20 per row, into the colour numbers of their pixels, row by row.
+fn render_tiles(data: &[u8]) -> Vec<u8> {
+    let tile_rows = data.len() / (TILE_SIZE * TILES_PER_ROW);
+    let mut pixels = vec![0; tile_rows * 8 * PRINTER_WIDTH];
+    for (index, tile) in data.chunks_exact(TILE_SIZE).enumerate() {
+        let (tile_x, tile_y) = (index % TILES_PER_ROW, index / TILES_PER_ROW);
+        if tile_y >= tile_rows {
+            break;
+        }
+        for (row, bytes) in tile.chunks_exact(2).enumerate() {
+            for x in 0..8 {
+                let bit = 7 - x;
+                let color = (bytes[0] >> bit) & 1 | ((bytes[1] >> bit) & 1) << 1;
+                pixels[(tile_y * 8 + row) * PRINTER_WIDTH + tile_x * 8 + x] = color;
+            }
+        }
+    }
+    pixels
+}
+
+/// Returns the grey level printed for a shade (0 white, 3 black), darker with more exposure.
+fn shade(shade: u8, exposure: u8) -> u8 {
+    let ink = shade as f32 / 3.0 * (0.75 + 0.5 * exposure as f32 / 0x7f as f32);
+    255 - (ink.min(1.0) * 255.0) as u8
+}
+
+/// Writes greyscale pixels, `PRINTER_WIDTH` per row, to a PNG file.
+fn write_png(path: &Path, pixels: &[u8]) -> io::Result<()> {
+    let mut encoder = png::Encoder::new(
+        BufWriter::new(File::create(path)?),
+        PRINTER_WIDTH as u32,
+        (pixels.len() / PRINTER_WIDTH) as u32,
+    );
+    encoder.set_color(png::ColorType::Grayscale);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder
+        .write_header()
+        .and_then(|mut writer| writer.write_image_data(pixels))
+        .map_err(io::Error::other)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Sends a packet to the printer, returning its answers to the last two bytes.
+    fn send_packet(printer: &mut Printer, command: u8, compressed: bool, data: &[u8]) -> [u8; 2] {
+        let mut packet = vec![command, compressed as u8];
+        packet.extend_from_slice(&(data.len() as u16).to_le_bytes());
+        packet.extend_from_slice(data);
+        let checksum = packet
+            .iter()
+            .fold(0u16, |sum, byte| sum.wrapping_add(*byte as u16));
+
+        for byte in MAGIC.iter().chain(&packet).chain(&checksum.to_le_bytes()) {
+            assert_eq!(printer.transfer(*byte), 0);
+        }
+        [printer.transfer(0), printer.transfer(0)]
+    }
+
+    #[test]
+    fn test_decompress() {
+        assert_eq!(decompress(&[0x01, 1, 2, 0x82, 7]), [1, 2, 7, 7, 7, 7]);
+    }
+
+    #[test]
+    fn test_print_packets() {
+        let mut printer = Printer::new(std::env::temp_dir());
+        assert_eq!(send_packet(&mut printer, INIT, false, &[]), [ALIVE, 0]);
+
+        // A band of 2 rows of black tiles, compressed as runs of 129 and 124 bytes
+        let data = [0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xfa, 0xff];
+        assert_eq!(decompress(&data), [0xff; 0x280]);
+        let [_, status] = send_packet(&mut printer, DATA, true, &data);
+        assert_eq!(status, UNPROCESSED);
+        let [_, status] = send_packet(&mut printer, DATA, false, &[]);
+        assert_eq!(status, UNPROCESSED | IMAGE_FULL);
+
+        let [_, status] = send_packet(&mut printer, PRINT, false, &[1, 0x10, 0xe4, 0x40]);
+        assert_eq!(status, PRINTING);
+        assert_eq!(printer.printout_height(), MARGIN_ROWS + 16);
+        assert_eq!(printer.printout[0], 0xff);
+        assert!(printer.printout[MARGIN_ROWS * PRINTER_WIDTH] < 0x20);
+
+        for _ in 1..PRINT_POLLS {
+            assert_eq!(
+                send_packet(&mut printer, STATUS, false, &[]),
+                [ALIVE, PRINTING]
+            );
+        }
+        assert_eq!(send_packet(&mut printer, STATUS, false, &[]), [ALIVE, 0]);
+        printer.printout.clear();
+    }
+
+    #[test]
+    fn test_power_off_saves_printout() {
+        let dir = std::env::temp_dir().join(format!("discogb-printer-{}", std::process::id()));
+        let mut printer = Printer::new(&dir);
+        assert!(dir.is_dir());
+
+        // A blank band printed without a bottom margin stays in the printer
+        send_packet(&mut printer, DATA, false, &[0; 0x280]);
+        send_packet(&mut printer, PRINT, false, &[1, 0x00, 0xe4, 0x40]);
+        assert_eq!(printer.printout_height(), 16);
+        assert!(!dir.join("printout-1.png").exists());
+
+        printer.power_off();
+        assert_eq!(printer.printout_height(), 0);
+        assert!(dir.join("printout-1.png").exists());
+
+        // A new session doesn't overwrite the printouts of the last one
+        let mut printer = Printer::new(&dir);
+        send_packet(&mut printer, DATA, false, &[0; 0x280]);
+        send_packet(&mut printer, PRINT, false, &[1, 0x00, 0xe4, 0x40]);
+        printer.power_off();
+        assert!(dir.join("printout-2.png").exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_checksum_error() {
+        let mut printer = Printer::new(std::env::temp_dir());
+        for byte in [0x88, 0x33, INIT, 0, 0, 0, 0x42, 0x00] {
+            printer.transfer(byte);
+        }
+        assert_eq!(printer.transfer(0), ALIVE);
+        assert_eq!(printer.transfer(0), CHECKSUM_ERROR);
+    }
+}
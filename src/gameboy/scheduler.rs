@@ -0,0 +1,93 @@
+/// The timed events of the memory-mapped components.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Event {
+    /// A falling edge of the bit of `DIV` that clocks the APU frame sequencer.
+    FrameSequencer,
+    /// A falling edge of the internal serial clock, shifting a bit.
+    SerialBit,
+    /// A change of the PPU mode or scanline.
+    Ppu,
+    /// The reload of `TIMA` after an overflow, requesting the timer interrupt.
+    TimerReload,
+    /// The next M-cycle of an OAM DMA transfer.
+    OamDma,
+}
+
+/// Keeps the time in cycles, and the events due at given times, so that
+/// components that are idle most of the time don't have to be polled.
+///
+/// There are only ever a few events pending, so they are kept in a `Vec`
+/// sorted by time rather than a heap, which makes them easy to cancel.
+#[derive(Debug, Clone, Default)]
+pub struct Scheduler {
+    /// The number of cycles elapsed.
+    pub now: u64,
+    /// The pending events, the latest first.
+    events: Vec<(u64, Event)>,
+}
+
+impl Scheduler {
+    /// Schedules the event in the given number of cycles, replacing any
+    /// pending occurrence of it.
+    pub fn schedule(&mut self, cycles: u64, event: Event) {
+        self.cancel(event);
+        let at = self.now + cycles;
+        let index = self.events.partition_point(|(time, _)| *time > at);
+        self.events.insert(index, (at, event));
+    }
+
+    /// Cancels the pending occurrence of the event, if any.
+    pub fn cancel(&mut self, event: Event) {
+        self.events.retain(|(_, pending)| *pending != event);
+    }
+
+    /// Returns the time of the pending occurrence of the event, if any.
+    pub fn time_of(&self, event: Event) -> Option<u64> {
+        self.events
+            .iter()
+            .find(|(_, pending)| *pending == event)
+            .map(|(time, _)| *time)
+    }
+
+    /// Removes and returns the next event due by the given time, with its time.
+    pub fn pop_due(&mut self, until: u64) -> Option<(u64, Event)> {
+        match self.events.last() {
+            Some((time, _)) if *time <= until => self.events.pop(),
+            _ => None,
+        }
+    }
+}
+
+/// Returns the number of cycles until the next falling edge of the given bit
+/// of a counter incremented every cycle.
+pub fn cycles_to_falling_edge(counter: u16, bit: u32) -> u64 {
+    let period = 1u64 << (bit + 1);
+    period - counter as u64 % period
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_events_in_order() {
+        let mut scheduler = Scheduler::default();
+        scheduler.schedule(100, Event::SerialBit);
+        scheduler.schedule(50, Event::FrameSequencer);
+        assert_eq!(scheduler.pop_due(40), None);
+        assert_eq!(scheduler.pop_due(200), Some((50, Event::FrameSequencer)));
+
+        // Rescheduling replaces the pending occurrence
+        scheduler.schedule(10, Event::SerialBit);
+        assert_eq!(scheduler.time_of(Event::SerialBit), Some(10));
+        assert_eq!(scheduler.pop_due(200), Some((10, Event::SerialBit)));
+        assert_eq!(scheduler.pop_due(200), None);
+    }
+
+    #[test]
+    fn test_cycles_to_falling_edge() {
+        assert_eq!(cycles_to_falling_edge(0, 8), 512);
+        assert_eq!(cycles_to_falling_edge(0x1f0, 8), 0x10);
+        assert_eq!(cycles_to_falling_edge(0x200, 8), 512);
+    }
+}
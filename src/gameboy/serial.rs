@@ -0,0 +1,80 @@
+use std::fmt::Debug;
+use std::sync::atomic::AtomicBool;
+use std::sync::{Arc, Mutex};
+
+/// What is plugged into the other end of the link port.
+pub trait SerialDevice: Debug + Send {
+    /// Exchanges a byte with the device when the `GameBoy` drives the clock:
+    /// `byte` is shifted out while the returned byte is shifted in.
+    fn transfer(&mut self, byte: u8) -> u8;
+
+    /// Called when the last bit of a transfer started by `transfer` was shifted.
+    fn transfer_finished(&mut self) {}
+
+    /// Polled while the `GameBoy` waits for a transfer on the external clock,
+    /// offering the byte it will shift out. Returns the byte shifted in once
+    /// the device has clocked a whole byte, or `None` until then.
+    fn poll_external(&mut self, _byte: u8) -> Option<u8> {
+        None
+    }
+
+    /// Called after each instruction with the number of cycles run so far,
+    /// for devices that must keep pace with the `GameBoy`.
+    fn sync(&mut self, _cycles: u64) {}
+
+    /// Called when the `GameBoy` is turned off for good, e.g. to save what the
+    /// device still holds.
+    fn power_off(&mut self) {}
+
+    /// Hands the device a flag that is set when the emulation is stopped, after
+    /// which it must not wait for anything that may never come.
+    fn set_stop_flag(&mut self, _stop: Arc<AtomicBool>) {}
+}
+
+/// A device standing for an unplugged cable: every bit shifted in is 1,
+/// and the external clock never ticks.
+#[derive(Debug, Default, Clone)]
+pub struct NullDevice;
+
+impl SerialDevice for NullDevice {
+    fn transfer(&mut self, _byte: u8) -> u8 {
+        0xff
+    }
+}
+
+/// A device that records every byte sent by the `GameBoy`, to be read from
+/// another thread through a clone of the device. Test ROMs print their
+/// results this way.
+#[derive(Debug, Default, Clone)]
+pub struct CaptureDevice {
+    bytes: Arc<Mutex<Vec<u8>>>,
+}
+
+impl CaptureDevice {
+    /// Creates a new, empty `CaptureDevice`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the bytes received so far.
+    pub fn bytes(&self) -> Vec<u8> {
+        self.bytes.lock().unwrap().clone()
+    }
+
+    /// Returns the bytes received so far as text.
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.bytes.lock().unwrap()).into_owned()
+    }
+
+    /// Removes and returns the bytes received so far.
+    pub fn take_bytes(&self) -> Vec<u8> {
+        std::mem::take(&mut self.bytes.lock().unwrap())
+    }
+}
+
+impl SerialDevice for CaptureDevice {
+    fn transfer(&mut self, byte: u8) -> u8 {
+        self.bytes.lock().unwrap().push(byte);
+        0xff
+    }
+}
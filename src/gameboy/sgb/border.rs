@@ -0,0 +1,131 @@
+use super::{SCREEN_HEIGHT, SCREEN_WIDTH};
+
+/// The number of border tiles, and the size of each (8x8 pixels, 4 bits per pixel).
+pub const BORDER_TILES: usize = 256;
+pub const BORDER_TILE_SIZE: usize = 32;
+/// The size of the border map, in tiles. Only the top 28 rows are visible.
+pub const BORDER_MAP_WIDTH: usize = 32;
+pub const BORDER_MAP_HEIGHT: usize = 32;
+/// The number of border palettes, and the number of colours in each.
+pub const BORDER_PALETTES: usize = 4;
+pub const BORDER_PALETTE_SIZE: usize = 16;
+
+/// The offset of the palettes in the data of `PCT_TRN`, following the map.
+const PALETTES_OFFSET: usize = 0x800;
+
+/// The picture frame drawn around the game screen, in the SNES tile format.
+#[derive(Debug, Clone)]
+pub struct Border {
+    /// The tile data, set by `CHR_TRN`.
+    pub tiles: Vec<u8>,
+    /// The tile map, set by `PCT_TRN`. Each entry holds the tile number,
+    /// the palette (4-7) in bits 10-12 and the flips in bits 14 and 15.
+    pub map: Vec<u16>,
+    /// The palettes 4-7, set by `PCT_TRN`. Colour 0 is transparent.
+    pub palettes: [[u16; BORDER_PALETTE_SIZE]; BORDER_PALETTES],
+}
+
+impl Default for Border {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Border {
+    /// Creates a new, fully transparent, `Border`.
+    pub fn new() -> Self {
+        Self {
+            tiles: vec![0; BORDER_TILES * BORDER_TILE_SIZE],
+            map: vec![0; BORDER_MAP_WIDTH * BORDER_MAP_HEIGHT],
+            palettes: [[0; BORDER_PALETTE_SIZE]; BORDER_PALETTES],
+        }
+    }
+
+    /// Loads the lower or upper 128 tiles from the data of `CHR_TRN`.
+    pub fn load_tiles(&mut self, upper: bool, data: &[u8]) {
+        let half = self.tiles.len() / 2;
+        let start = if upper { half } else { 0 };
+        self.tiles[start..start + half].copy_from_slice(&data[..half]);
+    }
+
+    /// Loads the map and palettes from the data of `PCT_TRN`.
+    pub fn load_map(&mut self, data: &[u8]) {
+        for (entry, bytes) in self.map.iter_mut().zip(data.chunks(2)) {
+            *entry = u16::from_le_bytes([bytes[0], bytes[1]]);
+        }
+
+        let colors = data[PALETTES_OFFSET..].chunks(2);
+        for (i, bytes) in colors
+            .take(BORDER_PALETTES * BORDER_PALETTE_SIZE)
+            .enumerate()
+        {
+            self.palettes[i / BORDER_PALETTE_SIZE][i % BORDER_PALETTE_SIZE] =
+                u16::from_le_bytes([bytes[0], bytes[1]]);
+        }
+    }
+
+    /// Returns the colour index (0-15) of a pixel of the given tile.
+    fn tile_pixel(&self, tile: usize, x: usize, y: usize) -> usize {
+        let tile = &self.tiles[tile * BORDER_TILE_SIZE..(tile + 1) * BORDER_TILE_SIZE];
+        let planes = [
+            tile[y * 2],
+            tile[y * 2 + 1],
+            tile[16 + y * 2],
+            tile[16 + y * 2 + 1],
+        ];
+        planes
+            .iter()
+            .enumerate()
+            .map(|(plane, byte)| (((byte >> (7 - x)) & 1) as usize) << plane)
+            .sum()
+    }
+
+    /// Renders the visible part of the border as RGB555 colours, with the
+    /// transparent pixels in the given backdrop colour.
+    pub fn render(&self, backdrop: u16) -> Vec<u16> {
+        let mut pixels = vec![backdrop; SCREEN_WIDTH * SCREEN_HEIGHT];
+        for (i, pixel) in pixels.iter_mut().enumerate() {
+            let (x, y) = (i % SCREEN_WIDTH, i / SCREEN_WIDTH);
+            let entry = self.map[(y / 8) * BORDER_MAP_WIDTH + x / 8];
+
+            let tile = (entry & 0xff) as usize;
+            let palette = ((entry >> 10) & 0b11) as usize;
+            let tile_x = if entry & 0x4000 != 0 {
+                7 - x % 8
+            } else {
+                x % 8
+            };
+            let tile_y = if entry & 0x8000 != 0 {
+                7 - y % 8
+            } else {
+                y % 8
+            };
+
+            match self.tile_pixel(tile, tile_x, tile_y) {
+                0 => {}
+                color => *pixel = self.palettes[palette][color],
+            }
+        }
+        pixels
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_flipped_tile() {
+        let mut border = Border::new();
+        // Tile 1: only the top-left pixel is set, with colour 0b0101
+        border.tiles[BORDER_TILE_SIZE] = 0x80;
+        border.tiles[BORDER_TILE_SIZE + 16] = 0x80;
+        border.palettes[1][0b0101] = 0x1234;
+        // Palette 5, flipped horizontally
+        border.map[0] = 0x4000 | 5 << 10 | 1;
+
+        let pixels = border.render(0x7fff);
+        assert_eq!(pixels[0], 0x7fff);
+        assert_eq!(pixels[7], 0x1234);
+    }
+}
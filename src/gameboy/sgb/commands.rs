@@ -0,0 +1,287 @@
+use super::{Mask, Sgb, Transfer, CELLS_X, CELLS_Y, SYSTEM_PALETTES};
+
+pub const PAL01: u8 = 0x00;
+pub const PAL23: u8 = 0x01;
+pub const PAL03: u8 = 0x02;
+pub const PAL12: u8 = 0x03;
+pub const ATTR_BLK: u8 = 0x04;
+pub const ATTR_LIN: u8 = 0x05;
+pub const ATTR_DIV: u8 = 0x06;
+pub const ATTR_CHR: u8 = 0x07;
+pub const PAL_SET: u8 = 0x0a;
+pub const PAL_TRN: u8 = 0x0b;
+pub const MLT_REQ: u8 = 0x11;
+pub const CHR_TRN: u8 = 0x13;
+pub const PCT_TRN: u8 = 0x14;
+pub const ATTR_TRN: u8 = 0x15;
+pub const ATTR_SET: u8 = 0x16;
+pub const MASK_EN: u8 = 0x17;
+
+impl Sgb {
+    /// Executes a command made of one or more packets.
+    pub(super) fn execute(&mut self, data: &[u8]) {
+        match data[0] >> 3 {
+            PAL01 => self.set_palette_pair(0, 1, data),
+            PAL23 => self.set_palette_pair(2, 3, data),
+            PAL03 => self.set_palette_pair(0, 3, data),
+            PAL12 => self.set_palette_pair(1, 2, data),
+            ATTR_BLK => self.attr_blk(data),
+            ATTR_LIN => self.attr_lin(data),
+            ATTR_DIV => self.attr_div(data),
+            ATTR_CHR => self.attr_chr(data),
+            PAL_SET => self.pal_set(data),
+            PAL_TRN => self.pending_transfer = Some(Transfer::Palettes),
+            MLT_REQ => {
+                self.players = match data[1] & 0b11 {
+                    1 => 2,
+                    3 => 4,
+                    _ => 1,
+                };
+                self.player = 0;
+            }
+            CHR_TRN => self.pending_transfer = Some(Transfer::Tiles(data[1] & 1 != 0)),
+            PCT_TRN => self.pending_transfer = Some(Transfer::Map),
+            ATTR_TRN => self.pending_transfer = Some(Transfer::Attributes),
+            ATTR_SET => self.attr_set(data[1]),
+            MASK_EN => {
+                self.mask = match data[1] & 0b11 {
+                    1 => Mask::Freeze,
+                    2 => Mask::Black,
+                    3 => Mask::Color0,
+                    _ => Mask::Cancel,
+                }
+            }
+            command => eprintln!("Unsupported SGB command: {:#04x}", command),
+        }
+    }
+
+    /// `PAL01`, `PAL23`, `PAL03` and `PAL12`: sets the shared colour 0 and the
+    /// other colours of two palettes.
+    fn set_palette_pair(&mut self, first: usize, second: usize, data: &[u8]) {
+        let color = |index: usize| u16::from_le_bytes([data[1 + index * 2], data[2 + index * 2]]);
+
+        for palette in &mut self.palettes {
+            palette[0] = color(0);
+        }
+        for i in 1..4 {
+            self.palettes[first][i] = color(i);
+            self.palettes[second][i] = color(i + 3);
+        }
+    }
+
+    /// Sets the palette of the cell at the given position, if it is on screen.
+    fn set_cell(&mut self, x: usize, y: usize, palette: u8) {
+        if x < CELLS_X && y < CELLS_Y {
+            self.attributes[y * CELLS_X + x] = palette & 0b11;
+        }
+    }
+
+    /// `ATTR_BLK`: sets the palettes inside, on the border of, and outside rectangles.
+    fn attr_blk(&mut self, data: &[u8]) {
+        let count = data[1] as usize;
+        for set in data[2..].chunks_exact(6).take(count) {
+            let control = set[0] & 0b111;
+            let (inside, mut border, outside) =
+                (set[1] & 0b11, (set[1] >> 2) & 0b11, (set[1] >> 4) & 0b11);
+            let (x1, y1, x2, y2) = (
+                set[2] as usize,
+                set[3] as usize,
+                set[4] as usize,
+                set[5] as usize,
+            );
+
+            // With only the inside or outside changed, the border follows it
+            let change_border = match control {
+                0b001 => {
+                    border = inside;
+                    true
+                }
+                0b100 => {
+                    border = outside;
+                    true
+                }
+                control => control & 0b010 != 0,
+            };
+
+            for y in 0..CELLS_Y {
+                for x in 0..CELLS_X {
+                    let within = (x1..=x2).contains(&x) && (y1..=y2).contains(&y);
+                    let on_border = within && (x == x1 || x == x2 || y == y1 || y == y2);
+                    if on_border {
+                        if change_border {
+                            self.set_cell(x, y, border);
+                        }
+                    } else if within {
+                        if control & 0b001 != 0 {
+                            self.set_cell(x, y, inside);
+                        }
+                    } else if control & 0b100 != 0 {
+                        self.set_cell(x, y, outside);
+                    }
+                }
+            }
+        }
+    }
+
+    /// `ATTR_LIN`: sets the palettes of whole rows or columns.
+    fn attr_lin(&mut self, data: &[u8]) {
+        let count = data[1] as usize;
+        for line in data[2..].iter().take(count) {
+            let (index, palette) = ((line & 0b1_1111) as usize, (line >> 5) & 0b11);
+            if line & 0b1000_0000 != 0 {
+                for x in 0..CELLS_X {
+                    self.set_cell(x, index, palette);
+                }
+            } else {
+                for y in 0..CELLS_Y {
+                    self.set_cell(index, y, palette);
+                }
+            }
+        }
+    }
+
+    /// `ATTR_DIV`: splits the screen in two along a row or column, with a
+    /// third palette for the dividing line itself.
+    fn attr_div(&mut self, data: &[u8]) {
+        let (after, before, line) = (data[1] & 0b11, (data[1] >> 2) & 0b11, (data[1] >> 4) & 0b11);
+        let horizontal = data[1] & 0b0100_0000 != 0;
+        let split = data[2] as usize;
+
+        for y in 0..CELLS_Y {
+            for x in 0..CELLS_X {
+                let position = if horizontal { y } else { x };
+                let palette = match position.cmp(&split) {
+                    std::cmp::Ordering::Less => before,
+                    std::cmp::Ordering::Equal => line,
+                    std::cmp::Ordering::Greater => after,
+                };
+                self.set_cell(x, y, palette);
+            }
+        }
+    }
+
+    /// `ATTR_CHR`: sets the palettes of consecutive cells, two bits per cell.
+    fn attr_chr(&mut self, data: &[u8]) {
+        let (mut x, mut y) = (data[1] as usize, data[2] as usize);
+        let count = u16::from_le_bytes([data[3], data[4]]) as usize;
+        let vertical = data[5] & 1 != 0;
+
+        for cell in 0..count.min((data.len() - 6) * 4) {
+            if x >= CELLS_X || y >= CELLS_Y {
+                break;
+            }
+            let palette = (data[6 + cell / 4] >> (6 - 2 * (cell % 4))) & 0b11;
+            self.set_cell(x, y, palette);
+
+            if vertical {
+                y += 1;
+                if y == CELLS_Y {
+                    y = 0;
+                    x += 1;
+                }
+            } else {
+                x += 1;
+                if x == CELLS_X {
+                    x = 0;
+                    y += 1;
+                }
+            }
+        }
+    }
+
+    /// `PAL_SET`: copies four of the system palettes, optionally applying an attribute file.
+    fn pal_set(&mut self, data: &[u8]) {
+        for (i, bytes) in data[1..9].chunks(2).enumerate() {
+            let index = u16::from_le_bytes([bytes[0], bytes[1]]) as usize % SYSTEM_PALETTES;
+            self.palettes[i] = self.system_palettes[index];
+        }
+
+        // Colour 0 is shared, and always taken from the first palette
+        let color0 = self.palettes[0][0];
+        for palette in &mut self.palettes {
+            palette[0] = color0;
+        }
+
+        if data[9] & 0b1000_0000 != 0 {
+            self.attr_set(data[9]);
+        }
+    }
+
+    /// `ATTR_SET`: applies one of the attribute files, optionally cancelling the mask.
+    fn attr_set(&mut self, value: u8) {
+        if let Some(file) = self.attribute_files.get((value & 0b11_1111) as usize) {
+            self.attributes = *file;
+        }
+        if value & 0b0100_0000 != 0 {
+            self.mask = Mask::Cancel;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::CELLS;
+    use super::*;
+
+    fn command(code: u8, args: &[u8]) -> Vec<u8> {
+        let mut data = vec![0; 16 * 2];
+        data[0] = code << 3 | 2;
+        data[1..=args.len()].copy_from_slice(args);
+        data
+    }
+
+    #[test]
+    fn test_attr_blk() {
+        let mut sgb = Sgb::new();
+        // With only the inside changed, the border follows it
+        sgb.execute(&command(ATTR_BLK, &[1, 0b001, 0b00_00_01, 2, 2, 4, 4]));
+        assert_eq!(sgb.attributes[3 * CELLS_X + 3], 1);
+        assert_eq!(sgb.attributes[2 * CELLS_X + 2], 1);
+        assert_eq!(sgb.attributes[0], 0);
+
+        // With only the outside changed, the border follows it
+        sgb.execute(&command(ATTR_BLK, &[1, 0b100, 0b10_00_00, 2, 2, 4, 4]));
+        assert_eq!(sgb.attributes[3 * CELLS_X + 3], 1);
+        assert_eq!(sgb.attributes[2 * CELLS_X + 2], 2);
+        assert_eq!(sgb.attributes[0], 2);
+    }
+
+    #[test]
+    fn test_attr_lin_and_div() {
+        let mut sgb = Sgb::new();
+        sgb.execute(&command(ATTR_DIV, &[0b0110_0111, 9]));
+        assert_eq!(sgb.attributes[0], 1);
+        assert_eq!(sgb.attributes[9 * CELLS_X], 2);
+        assert_eq!(sgb.attributes[CELLS - 1], 3);
+
+        sgb.execute(&command(ATTR_LIN, &[1, 0b0000_0011]));
+        assert_eq!(sgb.attributes[CELLS_X + 3], 0);
+    }
+
+    #[test]
+    fn test_attr_chr() {
+        let mut sgb = Sgb::new();
+        sgb.execute(&command(ATTR_CHR, &[19, 0, 3, 0, 0, 0b11_10_01_00]));
+
+        assert_eq!(sgb.attributes[19], 3);
+        assert_eq!(sgb.attributes[CELLS_X], 2);
+        assert_eq!(sgb.attributes[CELLS_X + 1], 1);
+    }
+
+    #[test]
+    fn test_pal_set_with_attribute_file() {
+        let mut sgb = Sgb::new();
+        sgb.system_palettes[300] = [1, 2, 3, 4];
+        sgb.attribute_files[5] = [3; CELLS];
+        sgb.mask = Mask::Black;
+
+        sgb.execute(&command(
+            PAL_SET,
+            &[0x2c, 0x01, 0, 0, 0, 0, 0, 0, 0b1100_0101],
+        ));
+        assert_eq!(sgb.palettes[0], [1, 2, 3, 4]);
+        assert_eq!(sgb.palettes[1][0], 1);
+        assert_eq!(sgb.attributes, [3; CELLS]);
+        assert_eq!(sgb.mask, Mask::Cancel);
+    }
+}
@@ -0,0 +1,352 @@
+use super::ppu::{Ppu, LCD_HEIGHT, LCD_WIDTH};
+
+mod border;
+pub use border::*;
+
+mod commands;
+
+/// The size of a command packet, in bytes.
+pub const PACKET_SIZE: usize = 16;
+/// The size of the data copied by a VRAM transfer command.
+pub const TRANSFER_SIZE: usize = 0x1000;
+
+/// The size of the screen, border included.
+pub const SCREEN_WIDTH: usize = 256;
+pub const SCREEN_HEIGHT: usize = 224;
+/// The position of the game screen inside the border.
+pub const GAME_X: usize = 48;
+pub const GAME_Y: usize = 40;
+
+/// The number of 8x8 cells of the game screen, each with its own palette.
+pub const CELLS_X: usize = LCD_WIDTH / 8;
+pub const CELLS_Y: usize = LCD_HEIGHT / 8;
+pub const CELLS: usize = CELLS_X * CELLS_Y;
+
+/// The number of system palettes stored by `PAL_TRN`.
+pub const SYSTEM_PALETTES: usize = 512;
+/// The number of attribute files stored by `ATTR_TRN`.
+pub const ATTRIBUTE_FILES: usize = 45;
+
+/// The palette used until the game sets its own: the shades of the DMG in grey.
+const DEFAULT_PALETTE: [u16; 4] = [0x7fff, 0x56b5, 0x294a, 0x0000];
+
+/// How the game screen is masked (`MASK_EN`), typically while VRAM transfers take place.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum Mask {
+    #[default]
+    Cancel,
+    /// The last frame stays on screen.
+    Freeze,
+    Black,
+    /// The screen is filled with colour 0.
+    Color0,
+}
+
+/// The data a pending VRAM transfer is copied into.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Transfer {
+    /// `PAL_TRN`: the system palettes.
+    Palettes,
+    /// `ATTR_TRN`: the attribute files.
+    Attributes,
+    /// `CHR_TRN`: the lower or upper half of the border tiles.
+    Tiles(bool),
+    /// `PCT_TRN`: the border map and palettes.
+    Map,
+}
+
+/// Receives the bits of command packets from the pulses written to `JOYP`.
+#[derive(Debug, Default, Clone)]
+struct PacketReceiver {
+    bytes: [u8; PACKET_SIZE],
+    /// The number of bits received so far.
+    bits: usize,
+    /// Whether a packet is being received.
+    receiving: bool,
+    /// Whether both lines have been released since the last pulse.
+    released: bool,
+}
+
+impl PacketReceiver {
+    /// Handles a write to `JOYP`, returning a packet once it is complete.
+    fn write(&mut self, value: u8) -> Option<[u8; PACKET_SIZE]> {
+        match value & 0b0011_0000 {
+            // Pulling both lines low starts a new packet
+            0b0000_0000 => {
+                *self = Self {
+                    receiving: true,
+                    ..Self::default()
+                };
+            }
+            0b0011_0000 => self.released = true,
+            lines if self.receiving && self.released => {
+                self.released = false;
+                // P14 low sends a 0, P15 low a 1
+                let bit = (lines == 0b0001_0000) as u8;
+
+                // The packet ends with a 0 stop bit
+                if self.bits == PACKET_SIZE * 8 {
+                    self.receiving = false;
+                    return (bit == 0).then_some(self.bytes);
+                }
+
+                self.bytes[self.bits / 8] |= bit << (self.bits % 8);
+                self.bits += 1;
+            }
+            _ => {}
+        }
+        None
+    }
+}
+
+/// The Super Game Boy, colourising the screen and drawing a border around it
+/// as instructed by the packets the game sends.
+#[derive(Debug, Clone)]
+pub struct Sgb {
+    /// The four palettes of the game screen. Colour 0 of palette 0 is shared by all.
+    pub palettes: [[u16; 4]; 4],
+    /// The palette of each 8x8 cell of the game screen.
+    pub attributes: [u8; CELLS],
+    /// The palettes `PAL_SET` picks from.
+    pub system_palettes: Vec<[u16; 4]>,
+    /// The attribute maps `ATTR_SET` and `PAL_SET` pick from.
+    pub attribute_files: Vec<[u8; CELLS]>,
+    pub mask: Mask,
+    pub border: Border,
+    /// The number of connected joypads (1, 2 or 4), set by `MLT_REQ`.
+    pub players: u8,
+    /// The joypad read through `JOYP` in multiplayer mode.
+    pub player: u8,
+    /// The colourised game screen, as RGB555 colours.
+    pub framebuffer: Vec<u16>,
+    receiver: PacketReceiver,
+    /// The packets of the command being received.
+    command: Vec<u8>,
+    /// The VRAM transfer to perform at the next VBlank.
+    pending_transfer: Option<Transfer>,
+    /// The last value written to `JOYP`.
+    joyp: u8,
+}
+
+impl Default for Sgb {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Sgb {
+    /// Creates a new `Sgb` with the default palettes and a blank border.
+    pub fn new() -> Self {
+        Self {
+            palettes: [DEFAULT_PALETTE; 4],
+            attributes: [0; CELLS],
+            system_palettes: vec![DEFAULT_PALETTE; SYSTEM_PALETTES],
+            attribute_files: vec![[0; CELLS]; ATTRIBUTE_FILES],
+            mask: Mask::default(),
+            border: Border::new(),
+            players: 1,
+            player: 0,
+            framebuffer: vec![DEFAULT_PALETTE[0]; LCD_WIDTH * LCD_HEIGHT],
+            receiver: PacketReceiver::default(),
+            command: Vec::new(),
+            pending_transfer: None,
+            joyp: 0b0011_0000,
+        }
+    }
+
+    /// Handles a write to `JOYP`, which carries both packets and joypad selection.
+    pub fn write_joyp(&mut self, value: u8) {
+        // Releasing P15 selects the next joypad
+        if self.players > 1 && self.joyp & 0b0010_0000 == 0 && value & 0b0011_0000 == 0b0011_0000 {
+            self.player = (self.player + 1) % self.players;
+        }
+        self.joyp = value;
+
+        if let Some(packet) = self.receiver.write(value) {
+            self.receive_packet(packet);
+        }
+    }
+
+    /// Returns the value read from `JOYP` when it reports the selected joypad:
+    /// in multiplayer mode, with neither line selected.
+    pub fn read_joyp(&self) -> Option<u8> {
+        if self.players > 1 && self.joyp & 0b0011_0000 == 0b0011_0000 {
+            Some(0b1111_0000 | (0x0f - self.player))
+        } else {
+            None
+        }
+    }
+
+    /// Queues a packet, executing the command once all of its packets have arrived.
+    fn receive_packet(&mut self, packet: [u8; PACKET_SIZE]) {
+        self.command.extend_from_slice(&packet);
+
+        let length = (self.command[0] & 0b111) as usize;
+        if self.command.len() >= length * PACKET_SIZE {
+            let command = std::mem::take(&mut self.command);
+            self.execute(&command);
+        }
+    }
+
+    /// Performs the pending VRAM transfer and colourises the finished frame.
+    /// Called at the start of each VBlank.
+    pub fn vblank(&mut self, ppu: &Ppu, vram: &[u8]) {
+        if let Some(transfer) = self.pending_transfer.take() {
+            self.transfer(transfer, &transfer_data(ppu, vram));
+        }
+
+        match self.mask {
+            Mask::Cancel => self.colorize(&ppu.framebuffer),
+            Mask::Freeze => {}
+            Mask::Black => self.framebuffer.fill(0x0000),
+            Mask::Color0 => self.framebuffer.fill(self.palettes[0][0]),
+        }
+    }
+
+    /// Colours the given shades with the palette of their cell.
+    fn colorize(&mut self, shades: &[u8]) {
+        for (i, (pixel, shade)) in self.framebuffer.iter_mut().zip(shades).enumerate() {
+            let (x, y) = (i % LCD_WIDTH, i / LCD_WIDTH);
+            let palette = self.attributes[(y / 8) * CELLS_X + x / 8] as usize;
+            *pixel = match shade {
+                0 => self.palettes[0][0],
+                shade => self.palettes[palette][*shade as usize],
+            };
+        }
+    }
+
+    /// Copies the data of a VRAM transfer to its destination.
+    fn transfer(&mut self, transfer: Transfer, data: &[u8]) {
+        match transfer {
+            Transfer::Palettes => {
+                for (palette, colors) in self.system_palettes.iter_mut().zip(data.chunks(8)) {
+                    for (color, bytes) in palette.iter_mut().zip(colors.chunks(2)) {
+                        *color = u16::from_le_bytes([bytes[0], bytes[1]]);
+                    }
+                }
+            }
+            Transfer::Attributes => {
+                let files = self.attribute_files.iter_mut();
+                for (file, bytes) in files.zip(data.chunks(CELLS / 4)) {
+                    for (cell, palette) in file.iter_mut().enumerate() {
+                        *palette = (bytes[cell / 4] >> (6 - 2 * (cell % 4))) & 0b11;
+                    }
+                }
+            }
+            Transfer::Tiles(upper) => self.border.load_tiles(upper, data),
+            Transfer::Map => self.border.load_map(data),
+        }
+    }
+
+    /// Returns the whole screen as RGB555 colours: the border with the game screen inside.
+    pub fn screen(&self) -> Vec<u16> {
+        let mut screen = self.border.render(self.palettes[0][0]);
+        for (y, row) in self.framebuffer.chunks(LCD_WIDTH).enumerate() {
+            let start = (GAME_Y + y) * SCREEN_WIDTH + GAME_X;
+            screen[start..start + LCD_WIDTH].copy_from_slice(row);
+        }
+        screen
+    }
+}
+
+/// Returns the data of a VRAM transfer: the tiles shown in the first 13 rows
+/// of the background, in screen order.
+fn transfer_data(ppu: &Ppu, vram: &[u8]) -> Vec<u8> {
+    let map = ppu.bg_map_offset();
+    let mut data = Vec::with_capacity(TRANSFER_SIZE);
+    for index in 0..TRANSFER_SIZE / 16 {
+        let tile = vram[map + (index / CELLS_X) * 32 + index % CELLS_X];
+        let offset = ppu.bg_tile_offset(tile);
+        data.extend_from_slice(&vram[offset..offset + 16]);
+    }
+    data
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Sends the given packet bit by bit, as a game would.
+    fn send_packet(sgb: &mut Sgb, packet: &[u8]) {
+        let mut bytes = [0; PACKET_SIZE];
+        bytes[..packet.len()].copy_from_slice(packet);
+
+        sgb.write_joyp(0x00);
+        sgb.write_joyp(0x30);
+        for i in 0..PACKET_SIZE * 8 + 1 {
+            let bit = bytes.get(i / 8).map_or(0, |byte| (byte >> (i % 8)) & 1);
+            sgb.write_joyp(if bit == 1 { 0x10 } else { 0x20 });
+            sgb.write_joyp(0x30);
+        }
+    }
+
+    #[test]
+    fn test_pal01() {
+        let mut sgb = Sgb::new();
+        let colors: [u16; 7] = [0x1111, 0x0001, 0x0002, 0x0003, 0x0011, 0x0012, 0x0013];
+        // PAL01, in a single packet
+        let mut packet = vec![0x01];
+        packet.extend(colors.iter().flat_map(|color| color.to_le_bytes()));
+        send_packet(&mut sgb, &packet);
+
+        assert_eq!(sgb.palettes[0], [0x1111, 0x0001, 0x0002, 0x0003]);
+        assert_eq!(sgb.palettes[1][1..], [0x0011, 0x0012, 0x0013]);
+    }
+
+    #[test]
+    fn test_colorize_by_cell() {
+        let mut sgb = Sgb::new();
+        sgb.palettes[1] = [0, 1, 2, 3];
+        sgb.attributes[1] = 1;
+
+        sgb.colorize(&vec![3; LCD_WIDTH * LCD_HEIGHT]);
+        assert_eq!(sgb.framebuffer[7], DEFAULT_PALETTE[3]);
+        assert_eq!(sgb.framebuffer[8], 3);
+    }
+
+    #[test]
+    fn test_multiplayer() {
+        let mut sgb = Sgb::new();
+        assert_eq!(sgb.read_joyp(), None);
+
+        send_packet(&mut sgb, &[0x11 << 3 | 1, 0x01]);
+        assert_eq!(sgb.players, 2);
+        let player = sgb.read_joyp().unwrap();
+
+        sgb.write_joyp(0x10);
+        sgb.write_joyp(0x30);
+        assert_ne!(sgb.read_joyp().unwrap(), player);
+        sgb.write_joyp(0x10);
+        sgb.write_joyp(0x30);
+        assert_eq!(sgb.read_joyp().unwrap(), player);
+    }
+
+    #[test]
+    fn test_pal_trn_at_vblank() {
+        let mut sgb = Sgb::new();
+        let mut ppu = Ppu::new();
+        ppu.lcdc = 0b1001_0001;
+        let mut vram = vec![0; 0x2000];
+        // The tiles are shown in order, so the data is simply the tile data
+        for index in 0..TRANSFER_SIZE / 16 {
+            vram[0x1800 + (index / CELLS_X) * 32 + index % CELLS_X] = index as u8;
+        }
+        vram[8..10].copy_from_slice(&[0x34, 0x12]);
+
+        send_packet(&mut sgb, &[0x0b << 3 | 1]);
+        assert_eq!(sgb.system_palettes[1][0], DEFAULT_PALETTE[0]);
+        sgb.vblank(&ppu, &vram);
+        assert_eq!(sgb.system_palettes[1][0], 0x1234);
+    }
+
+    #[test]
+    fn test_screen_includes_border() {
+        let mut sgb = Sgb::new();
+        sgb.framebuffer.fill(0x1234);
+
+        let screen = sgb.screen();
+        assert_eq!(screen.len(), SCREEN_WIDTH * SCREEN_HEIGHT);
+        assert_eq!(screen[0], DEFAULT_PALETTE[0]);
+        assert_eq!(screen[GAME_Y * SCREEN_WIDTH + GAME_X], 0x1234);
+    }
+}
@@ -0,0 +1,171 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use super::apu::CHANNELS;
+use super::audio::{AudioSink, Sample};
+
+/// The size of the RIFF and format headers, before the sample data.
+const HEADER_SIZE: u32 = 44;
+const BITS_PER_SAMPLE: u16 = 16;
+
+/// Writes 16-bit PCM samples to a WAV file.
+/// The sizes in the header are filled in by `finish`.
+#[derive(Debug)]
+pub struct WavWriter<W: Write + Seek> {
+    writer: W,
+    channels: u16,
+    data_size: u32,
+}
+
+impl<W: Write + Seek> WavWriter<W> {
+    /// Creates a new `WavWriter` with the given number of interleaved channels.
+    pub fn new(mut writer: W, channels: u16, sample_rate: u32) -> io::Result<Self> {
+        let block_align = channels * BITS_PER_SAMPLE / 8;
+
+        writer.write_all(b"RIFF")?;
+        writer.write_all(&0u32.to_le_bytes())?;
+        writer.write_all(b"WAVEfmt ")?;
+        writer.write_all(&16u32.to_le_bytes())?;
+        // Uncompressed PCM
+        writer.write_all(&1u16.to_le_bytes())?;
+        writer.write_all(&channels.to_le_bytes())?;
+        writer.write_all(&sample_rate.to_le_bytes())?;
+        writer.write_all(&(sample_rate * block_align as u32).to_le_bytes())?;
+        writer.write_all(&block_align.to_le_bytes())?;
+        writer.write_all(&BITS_PER_SAMPLE.to_le_bytes())?;
+        writer.write_all(b"data")?;
+        writer.write_all(&0u32.to_le_bytes())?;
+
+        Ok(Self {
+            writer,
+            channels,
+            data_size: 0,
+        })
+    }
+
+    /// Writes interleaved samples between -1.0 and 1.0, clipping the others.
+    pub fn write_samples(&mut self, samples: &[f32]) -> io::Result<()> {
+        for sample in samples {
+            let value = (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16;
+            self.writer.write_all(&value.to_le_bytes())?;
+        }
+        self.data_size += samples.len() as u32 * BITS_PER_SAMPLE as u32 / 8;
+        Ok(())
+    }
+
+    /// Returns the number of samples written per channel.
+    pub fn len(&self) -> u32 {
+        self.data_size / (self.channels as u32 * BITS_PER_SAMPLE as u32 / 8)
+    }
+
+    /// Returns whether no samples have been written.
+    pub fn is_empty(&self) -> bool {
+        self.data_size == 0
+    }
+
+    /// Fills in the sizes in the header and flushes the writer.
+    pub fn finish(&mut self) -> io::Result<()> {
+        self.writer.seek(SeekFrom::Start(4))?;
+        self.writer
+            .write_all(&(HEADER_SIZE - 8 + self.data_size).to_le_bytes())?;
+        self.writer.seek(SeekFrom::Start(HEADER_SIZE as u64 - 4))?;
+        self.writer.write_all(&self.data_size.to_le_bytes())?;
+        self.writer.seek(SeekFrom::End(0))?;
+        self.writer.flush()
+    }
+}
+
+/// A sink that records the stereo output to a WAV file, and optionally each
+/// channel to its own mono WAV file (`<name>_ch1.wav` to `<name>_ch4.wav`).
+/// The files are completed when the sink is finished.
+#[derive(Debug)]
+pub struct WavSink {
+    mix: WavWriter<BufWriter<File>>,
+    stems: Vec<WavWriter<BufWriter<File>>>,
+}
+
+impl WavSink {
+    /// Creates the WAV file at the given path, and the stem files next to it if requested.
+    pub fn create(path: &Path, sample_rate: u32, stems: bool) -> io::Result<Self> {
+        let create = |path: &Path, channels| {
+            WavWriter::new(BufWriter::new(File::create(path)?), channels, sample_rate)
+        };
+
+        let mix = create(path, 2)?;
+        let stems = if stems {
+            (1..=CHANNELS)
+                .map(|channel| create(&stem_path(path, channel), 1))
+                .collect::<io::Result<_>>()?
+        } else {
+            Vec::new()
+        };
+        Ok(Self { mix, stems })
+    }
+}
+
+/// Returns the path of the stem file of the given channel (1-4).
+pub fn stem_path(path: &Path, channel: usize) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    path.with_file_name(format!("{}_ch{}.wav", stem, channel))
+}
+
+impl AudioSink for WavSink {
+    fn push_samples(&mut self, samples: &[Sample]) {
+        let interleaved: Vec<f32> = samples
+            .iter()
+            .flat_map(|(left, right)| [*left, *right])
+            .collect();
+        if let Err(err) = self.mix.write_samples(&interleaved) {
+            eprintln!("Failed to write WAV samples: {}", err);
+        }
+    }
+
+    fn wants_channels(&self) -> bool {
+        !self.stems.is_empty()
+    }
+
+    fn push_channel_samples(&mut self, channel: usize, samples: &[f32]) {
+        if let Err(err) = self.stems[channel].write_samples(samples) {
+            eprintln!("Failed to write WAV samples: {}", err);
+        }
+    }
+
+    fn finish(&mut self) {
+        for writer in std::iter::once(&mut self.mix).chain(&mut self.stems) {
+            if let Err(err) = writer.finish() {
+                eprintln!("Failed to finish WAV file: {}", err);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    #[test]
+    fn test_wav_header() {
+        let mut wav = WavWriter::new(Cursor::new(Vec::new()), 2, 48_000).unwrap();
+        wav.write_samples(&[1.0, -1.0, 0.0, 2.0]).unwrap();
+        wav.finish().unwrap();
+        assert_eq!(wav.len(), 2);
+
+        let bytes = wav.writer.into_inner();
+        assert_eq!(bytes.len(), HEADER_SIZE as usize + 8);
+        assert_eq!(&bytes[0..4], b"RIFF");
+        assert_eq!(bytes[4..8], (HEADER_SIZE - 8 + 8).to_le_bytes());
+        assert_eq!(bytes[24..28], 48_000u32.to_le_bytes());
+        assert_eq!(bytes[40..44], 8u32.to_le_bytes());
+        assert_eq!(bytes[44..46], i16::MAX.to_le_bytes());
+        assert_eq!(bytes[50..52], i16::MAX.to_le_bytes());
+    }
+
+    #[test]
+    fn test_stem_path() {
+        let path = Path::new("out/music.wav");
+        assert_eq!(stem_path(path, 3), Path::new("out/music_ch3.wav"));
+    }
+}
@@ -0,0 +1,137 @@
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use super::palette::{Palette, DEFAULT_PALETTE};
+
+/// The name of the configuration file inside the user's config directory.
+const CONFIG_FILE: &str = "discogb/config.toml";
+
+/// The frontend configuration, persisted as TOML in the user's config directory.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub palettes: PaletteConfig,
+    pub filters: FilterConfig,
+    pub keys: KeyConfig,
+}
+
+/// The palettes selected for each layer, and the user-defined palettes.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PaletteConfig {
+    /// The name of the palette used for the background and window.
+    pub bg: String,
+    /// The name of the palette used for sprites drawn with `OBP0`.
+    pub obp0: String,
+    /// The name of the palette used for sprites drawn with `OBP1`.
+    pub obp1: String,
+    /// The user-defined palettes.
+    pub custom: Vec<Palette>,
+}
+
+impl Default for PaletteConfig {
+    fn default() -> Self {
+        Self {
+            bg: DEFAULT_PALETTE.to_string(),
+            obp0: DEFAULT_PALETTE.to_string(),
+            obp1: DEFAULT_PALETTE.to_string(),
+            custom: Vec::new(),
+        }
+    }
+}
+
+/// The post-processing filters applied to the display.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FilterConfig {
+    /// Whether CGB colours are corrected to look like on the LCD.
+    pub color_correction: bool,
+    /// Whether each frame is blended with the previous ones, emulating LCD ghosting.
+    pub frame_blending: bool,
+    /// Whether the grid between the LCD pixels is drawn.
+    pub dot_matrix: bool,
+}
+
+/// The names of the keys bound to the joypad buttons and emulator hotkeys,
+/// as given by `egui::Key::name`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct KeyConfig {
+    pub a: String,
+    pub b: String,
+    pub select: String,
+    pub start: String,
+    pub right: String,
+    pub left: String,
+    pub up: String,
+    pub down: String,
+    /// Pauses or resumes the emulation.
+    pub pause: String,
+    /// Executes a single instruction while paused.
+    pub step: String,
+    /// Runs as fast as possible while held.
+    pub fast_forward: String,
+    pub save_state: String,
+    pub load_state: String,
+    pub screenshot: String,
+}
+
+impl Default for KeyConfig {
+    fn default() -> Self {
+        Self {
+            a: "X".to_string(),
+            b: "Z".to_string(),
+            select: "Backspace".to_string(),
+            start: "Enter".to_string(),
+            right: "Right".to_string(),
+            left: "Left".to_string(),
+            up: "Up".to_string(),
+            down: "Down".to_string(),
+            pause: "P".to_string(),
+            step: "Space".to_string(),
+            fast_forward: "Tab".to_string(),
+            save_state: "F5".to_string(),
+            load_state: "F8".to_string(),
+            screenshot: "F12".to_string(),
+        }
+    }
+}
+
+impl Config {
+    /// Returns the path of the configuration file, if the platform has a config directory.
+    pub fn path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join(CONFIG_FILE))
+    }
+
+    /// Loads the configuration file, falling back to the defaults if it is
+    /// missing or cannot be parsed.
+    pub fn load() -> Self {
+        let Some(path) = Self::path() else {
+            return Self::default();
+        };
+
+        match fs::read_to_string(&path) {
+            Ok(contents) => toml::from_str(&contents).unwrap_or_else(|err| {
+                eprintln!("Ignoring invalid config file {}: {}", path.display(), err);
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        }
+    }
+
+    /// Writes the configuration file, creating its directory if needed.
+    pub fn save(&self) -> io::Result<()> {
+        let path = Self::path()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "No config directory"))?;
+        let contents = toml::to_string_pretty(self)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, contents)
+    }
+}
@@ -0,0 +1,338 @@
+use std::fs::File;
+use std::io::{self, BufWriter};
+use std::path::Path;
+use std::sync::mpsc::Sender;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::gameboy::memory::Memory;
+use crate::gameboy::ppu::{PaletteSource, Renderer};
+use crate::gameboy::sgb;
+
+use super::config::FilterConfig;
+use super::emulator::Command;
+use super::filters;
+use super::palette::Palette;
+use super::separator_ui;
+
+pub const DISPLAY_WIDTH: usize = 160;
+pub const DISPLAY_HEIGHT: usize = 144;
+
+/// The smallest and largest integer scale factors of the display.
+const MIN_SCALE: usize = 1;
+const MAX_SCALE: usize = 6;
+
+/// What the LCD shows, copied from the PPU.
+#[derive(Debug, Clone, Default)]
+pub struct Frame {
+    lcd_enabled: bool,
+    /// The number of frames completed since power on.
+    number: u64,
+    renderer: Renderer,
+    cgb: bool,
+    framebuffer: Vec<u8>,
+    palette_sources: Vec<PaletteSource>,
+    cgb_framebuffer: Vec<u16>,
+    /// The screen of the Super GameBoy, border included, if there is one.
+    sgb_screen: Option<Vec<u16>>,
+}
+
+impl Frame {
+    /// Copies the framebuffer of the PPU of the given memory.
+    pub fn capture(mem: &Memory) -> Self {
+        let ppu = &mem.ppu;
+        Self {
+            lcd_enabled: ppu.lcd_enabled(),
+            number: ppu.frames,
+            renderer: ppu.renderer,
+            cgb: ppu.cgb,
+            framebuffer: ppu.framebuffer.clone(),
+            palette_sources: ppu.palette_sources.clone(),
+            cgb_framebuffer: ppu.cgb_framebuffer.clone(),
+            sgb_screen: mem.sgb.as_ref().map(|sgb| sgb.screen()),
+        }
+    }
+}
+
+/// The LCD display, showing the framebuffer of the PPU, surrounded by the
+/// border on the Super GameBoy.
+pub struct Display {
+    /// The frame shown, from the latest snapshot of the `GameBoy`.
+    pub frame: Frame,
+    /// The index of the `GameBoy` shown, to send it commands.
+    index: usize,
+    commands: Sender<Command>,
+    texture: Option<egui::TextureHandle>,
+    /// The palettes used for the background, `OBP0` and `OBP1`, in that order.
+    pub palettes: [Palette; 3],
+    /// The integer scale factor used when displayed in a window.
+    pub scale: usize,
+    /// Whether the display fills the whole screen.
+    pub fullscreen: bool,
+    /// The post-processing filters applied to each frame.
+    pub filters: FilterConfig,
+    /// The colour correction lookup table, built on first use.
+    color_table: Option<Vec<egui::Color32>>,
+    /// The number of the last frame shown and its pixels, after frame blending.
+    last_frame: Option<(u64, Vec<egui::Color32>)>,
+    /// Whether the LCD has the keyboard focus, so keys control the joypad.
+    pub focused: bool,
+}
+
+impl Display {
+    /// Creates a new `Display` showing the frames of the `GameBoy` at the
+    /// given index, to which it sends the given commands.
+    pub fn new(
+        index: usize,
+        commands: Sender<Command>,
+        palettes: [Palette; 3],
+        filters: FilterConfig,
+    ) -> Self {
+        Self {
+            frame: Frame::default(),
+            index,
+            commands,
+            texture: None,
+            palettes,
+            scale: 2,
+            fullscreen: false,
+            filters,
+            color_table: None,
+            last_frame: None,
+            focused: false,
+        }
+    }
+
+    /// Returns the size of the displayed screen, in pixels.
+    fn screen_size(&self) -> [usize; 2] {
+        if self.frame.sgb_screen.is_some() {
+            [sgb::SCREEN_WIDTH, sgb::SCREEN_HEIGHT]
+        } else {
+            [DISPLAY_WIDTH, DISPLAY_HEIGHT]
+        }
+    }
+
+    /// Converts the current PPU framebuffer into an image, applying the filters.
+    /// The dot-matrix grid is drawn for the given number of screen pixels per LCD pixel.
+    /// Returns `None` while the LCD is turned off.
+    fn frame_image(&mut self, scale: usize) -> Option<egui::ColorImage> {
+        let size = self.screen_size();
+        let frame = &self.frame;
+        if !frame.lcd_enabled {
+            return None;
+        }
+
+        let pixels: Vec<_> = if let Some(screen) = &frame.sgb_screen {
+            screen.iter().map(|color| rgb555(*color)).collect()
+        } else if frame.cgb && self.filters.color_correction {
+            let table = self
+                .color_table
+                .get_or_insert_with(filters::color_correction_table);
+            frame
+                .cgb_framebuffer
+                .iter()
+                .map(|color| table[*color as usize & 0x7fff])
+                .collect()
+        } else if frame.cgb {
+            frame
+                .cgb_framebuffer
+                .iter()
+                .map(|color| rgb555(*color))
+                .collect()
+        } else {
+            frame
+                .framebuffer
+                .iter()
+                .zip(&frame.palette_sources)
+                .map(|(shade, source)| self.palettes[*source as usize].color(*shade))
+                .collect()
+        };
+
+        let pixels = match self.last_frame.take() {
+            // Frames are only blended once, however often they are displayed
+            Some((number, previous)) if number == frame.number && self.filters.frame_blending => {
+                previous
+            }
+            Some((_, mut previous))
+                if self.filters.frame_blending && previous.len() == pixels.len() =>
+            {
+                filters::blend_frames(&mut previous, &pixels);
+                previous
+            }
+            _ => pixels,
+        };
+        self.last_frame = Some((frame.number, pixels.clone()));
+
+        // A grid needs at least one screen pixel per LCD pixel besides the gap
+        if self.filters.dot_matrix && scale > 1 {
+            return Some(filters::dot_matrix(&pixels, size[0], size[1], scale));
+        }
+        Some(egui::ColorImage { size, pixels })
+    }
+
+    /// Uploads the current frame to the texture, creating it on first use.
+    /// Returns `None` while the LCD is turned off.
+    fn update_texture(
+        &mut self,
+        ctx: &egui::Context,
+        scale: usize,
+    ) -> Option<&egui::TextureHandle> {
+        let image = self.frame_image(scale)?;
+
+        let texture = self.texture.get_or_insert_with(|| {
+            ctx.load_texture(
+                "lcd_display",
+                egui::ColorImage::new([DISPLAY_WIDTH, DISPLAY_HEIGHT], egui::Color32::BLACK),
+                egui::TextureOptions::NEAREST,
+            )
+        });
+        texture.set(image, egui::TextureOptions::NEAREST);
+
+        Some(texture)
+    }
+
+    /// Displays the LCD at the given integer scale.
+    /// Clicking it gives it the keyboard focus, which it keeps until clicked elsewhere.
+    fn lcd_ui(&mut self, ui: &mut egui::Ui, scale: usize) {
+        let [width, height] = self.screen_size();
+        let size = egui::vec2((width * scale) as f32, (height * scale) as f32);
+
+        // The grid is drawn in physical pixels, which may not match points
+        let physical_scale = (scale as f32 * ui.ctx().pixels_per_point()).round() as usize;
+        let response = match self.update_texture(ui.ctx(), physical_scale) {
+            Some(texture) => ui.add(egui::Image::new(texture, size).sense(egui::Sense::click())),
+            None => lcd_off_ui(ui, size, &self.palettes[0]),
+        };
+
+        if response.clicked() {
+            response.request_focus();
+        }
+        if response.clicked_elsewhere() {
+            response.surrender_focus();
+        }
+        // Keep the arrow keys from moving the focus away
+        ui.memory_mut(|memory| memory.lock_focus(response.id, true));
+        self.focused = response.has_focus();
+    }
+
+    /// Saves the current frame to a PNG file named after the current time,
+    /// in the working directory, reporting the outcome on stderr.
+    pub fn save_screenshot(&mut self) {
+        let Some(image) = self.frame_image(self.scale) else {
+            eprintln!("Not taking a screenshot: the LCD is off");
+            return;
+        };
+
+        let time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |time| time.as_millis());
+        let path = format!("discogb-{}.png", time);
+        match write_png(Path::new(&path), &image) {
+            Ok(()) => eprintln!("Saved screenshot to {}", path),
+            Err(err) => eprintln!("Failed to save screenshot to {}: {}", path, err),
+        }
+    }
+
+    /// Displays the LCD together with its controls.
+    pub fn ui(&mut self, ui: &mut egui::Ui) {
+        self.lcd_ui(ui, self.scale);
+
+        separator_ui(ui);
+
+        ui.horizontal(|ui| {
+            ui.add(egui::Slider::new(&mut self.scale, MIN_SCALE..=MAX_SCALE).suffix("×"));
+            if ui.button("Fullscreen").clicked() {
+                self.fullscreen = true;
+            }
+        });
+
+        self.renderer_ui(ui);
+    }
+
+    /// Displays the LCD alone, at the largest integer scale that fits the screen.
+    pub fn fullscreen_ui(&mut self, ui: &mut egui::Ui) {
+        let available = ui.available_size();
+        let [width, height] = self.screen_size();
+        let scale =
+            ((available.x / width as f32).min(available.y / height as f32) as usize).max(MIN_SCALE);
+
+        ui.centered_and_justified(|ui| {
+            self.lcd_ui(ui, scale);
+        });
+        // The fullscreen LCD is all there is to interact with
+        self.focused = true;
+
+        ui.input(|i| {
+            if i.key_pressed(egui::Key::Escape) || i.key_pressed(egui::Key::F11) {
+                self.fullscreen = false;
+            }
+        });
+    }
+
+    /// Displays a selector for the PPU rendering strategy.
+    fn renderer_ui(&mut self, ui: &mut egui::Ui) {
+        let mut renderer = self.frame.renderer;
+
+        egui::ComboBox::from_label("Renderer")
+            .selected_text(match renderer {
+                Renderer::Scanline => "Scanline",
+                Renderer::Fifo => "Pixel FIFO",
+            })
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut renderer, Renderer::Scanline, "Scanline");
+                ui.selectable_value(&mut renderer, Renderer::Fifo, "Pixel FIFO");
+            });
+
+        if renderer != self.frame.renderer {
+            self.frame.renderer = renderer;
+            self.commands
+                .send(Command::SetRenderer(self.index, renderer))
+                .ok();
+        }
+    }
+}
+
+/// Displays a blank screen with an "LCD off" notice.
+fn lcd_off_ui(ui: &mut egui::Ui, size: egui::Vec2, palette: &Palette) -> egui::Response {
+    let (rect, response) = ui.allocate_exact_size(size, egui::Sense::click());
+    let painter = ui.painter();
+    painter.rect_filled(rect, 0.0, palette.color(0));
+    painter.text(
+        rect.center(),
+        egui::Align2::CENTER_CENTER,
+        "LCD off",
+        egui::FontId::monospace(14.0),
+        palette.color(2),
+    );
+    response
+}
+
+/// Writes the given image to a PNG file.
+fn write_png(path: &Path, image: &egui::ColorImage) -> io::Result<()> {
+    let [width, height] = image.size;
+    let mut encoder = png::Encoder::new(
+        BufWriter::new(File::create(path)?),
+        width as u32,
+        height as u32,
+    );
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+
+    let data: Vec<u8> = image
+        .pixels
+        .iter()
+        .flat_map(|pixel| pixel.to_array())
+        .collect();
+    encoder
+        .write_header()
+        .and_then(|mut writer| writer.write_image_data(&data))
+        .map_err(io::Error::other)
+}
+
+/// Converts a CGB colour (RGB555, red in the lowest bits) to a displayable colour.
+fn rgb555(color: u16) -> egui::Color32 {
+    let channel = |shift: u16| {
+        let value = ((color >> shift) & 0x1f) as u8;
+        value << 3 | value >> 2
+    };
+    egui::Color32::from_rgb(channel(0), channel(5), channel(10))
+}
@@ -0,0 +1,385 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::sync::{Arc, Mutex, RwLock};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use crate::gameboy::apu::{Apu, CHANNELS, CLOCK_RATE};
+use crate::gameboy::audio::AudioSink;
+use crate::gameboy::cpu::Registers;
+use crate::gameboy::memory::Buttons;
+use crate::gameboy::ppu::Renderer;
+use crate::gameboy::{link, GameBoy, SaveState};
+
+use super::display::Frame;
+use super::MemoryView;
+
+/// The number of instructions executed between two checks of the emulation
+/// speed and of the commands.
+const STEPS_PER_CHECK: u32 = 1000;
+
+/// The time between two snapshots taken while running, about once per frame
+/// of the GUI.
+const SNAPSHOT_INTERVAL: Duration = Duration::from_millis(16);
+
+/// A request from the GUI to the thread running the `GameBoy`s, handled
+/// between two instructions.
+#[derive(Debug)]
+pub enum Command {
+    /// Sets the buttons pressed on the joypad of the `GameBoy` at the given index.
+    SetButtons(usize, Buttons),
+    /// Executes a single instruction while stepping manually.
+    Step,
+    /// Saves the state of every `GameBoy`.
+    SaveState,
+    /// Restores the states saved last.
+    LoadState,
+    /// Sets the rendering strategy of the PPU of the `GameBoy` at the given index.
+    SetRenderer(usize, Renderer),
+    /// Sets whether the OAM corruption bug is emulated on the first `GameBoy`.
+    SetOamCorruption(bool),
+    /// Sets the muted channels of the first `GameBoy`.
+    SetMuted([bool; CHANNELS]),
+    /// Sends the audio of the first `GameBoy` to the given sink from now on.
+    SetAudioSink(Box<dyn AudioSink>),
+    /// Stops running the `GameBoy`s, ending the thread.
+    Stop,
+}
+
+/// What the GUI shows of a `GameBoy`, copied between two instructions.
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    pub pc: u16,
+    pub sp: u16,
+    pub regs: Registers,
+    pub ime: bool,
+    pub halt: bool,
+    /// What the LCD shows.
+    pub frame: Frame,
+    pub apu: Apu,
+    /// The contents of each memory view, in the order of `MemoryView::ALL`.
+    pub memory: Vec<Vec<u8>>,
+    pub oam_corruption: bool,
+}
+
+impl Snapshot {
+    /// Copies what the GUI shows of the given `GameBoy`.
+    pub fn capture(gameboy: &GameBoy) -> Self {
+        let cpu = &gameboy.cpu;
+        let mem = gameboy.memory();
+        Self {
+            pc: cpu.pc,
+            sp: cpu.sp,
+            regs: cpu.regs,
+            ime: cpu.ime,
+            halt: cpu.halt,
+            frame: Frame::capture(mem),
+            apu: mem.apu.clone(),
+            memory: MemoryView::ALL
+                .iter()
+                .map(|view| view.contents(mem).to_vec())
+                .collect(),
+            oam_corruption: mem.oam_corruption,
+        }
+    }
+
+    /// Returns the value of the flag at the given bit of the `F` register.
+    pub fn flag(&self, bit: u8) -> u8 {
+        (self.regs.f >> bit) & 1
+    }
+}
+
+/// The latest snapshots of every `GameBoy`, waiting to be taken by the GUI.
+type Snapshots = Arc<Mutex<Option<Vec<Snapshot>>>>;
+
+/// The thread running the `GameBoy`s, which owns them so that they run
+/// without any locking. The GUI sees them through the snapshots the thread
+/// takes, and changes them through commands. The thread is stopped and
+/// joined when the `Emulator` is dropped.
+#[derive(Debug)]
+pub struct Emulator {
+    commands: Sender<Command>,
+    snapshots: Snapshots,
+    /// The thread, until it is stopped.
+    thread: Option<JoinHandle<()>>,
+    /// Set when the thread is stopped, so that the link cables stop waiting.
+    stopping: Arc<AtomicBool>,
+}
+
+impl Emulator {
+    /// Starts running the given `GameBoy`s in lockstep, the first one setting
+    /// the pace, in real time unless fast-forwarding, and not at all while
+    /// stepping manually.
+    pub fn spawn(
+        mut gameboys: Vec<GameBoy>,
+        step_manually: Arc<RwLock<bool>>,
+        fast_forward: Arc<RwLock<bool>>,
+    ) -> Self {
+        let (commands, receiver) = mpsc::channel();
+        let snapshots = Snapshots::default();
+        let stopping = Arc::new(AtomicBool::new(false));
+        for gameboy in &mut gameboys {
+            gameboy.set_stop_flag(stopping.clone());
+        }
+
+        let runner = Runner {
+            gameboys,
+            commands: receiver,
+            snapshots: snapshots.clone(),
+            step_manually,
+            fast_forward,
+            saved_states: Vec::new(),
+        };
+        let thread = thread::Builder::new()
+            .name("GameBoy Run-Loop".to_string())
+            .spawn(move || runner.run())
+            .unwrap();
+
+        Self {
+            commands,
+            snapshots,
+            thread: Some(thread),
+            stopping,
+        }
+    }
+
+    /// Stops the thread and waits for it to finish the command it is handling.
+    /// Does nothing if it was already stopped.
+    pub fn stop(&mut self) {
+        let Some(thread) = self.thread.take() else {
+            return;
+        };
+        // The thread may have stopped on its own already
+        self.stopping.store(true, Ordering::Relaxed);
+        let _ = self.commands.send(Command::Stop);
+        if thread.join().is_err() {
+            eprintln!("The GameBoy stopped running with an error");
+        }
+    }
+
+    /// Sends a command to the thread.
+    pub fn send(&self, command: Command) {
+        if self.commands.send(command).is_err() {
+            eprintln!("The GameBoy has stopped running");
+        }
+    }
+
+    /// Returns a sender of commands to the thread.
+    pub fn commands(&self) -> Sender<Command> {
+        self.commands.clone()
+    }
+
+    /// Returns the snapshots of every `GameBoy` taken since the last call, if any.
+    pub fn take_snapshots(&self) -> Option<Vec<Snapshot>> {
+        self.snapshots.lock().unwrap().take()
+    }
+}
+
+impl Drop for Emulator {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// The state of the thread running the `GameBoy`s.
+struct Runner {
+    gameboys: Vec<GameBoy>,
+    commands: Receiver<Command>,
+    snapshots: Snapshots,
+    step_manually: Arc<RwLock<bool>>,
+    fast_forward: Arc<RwLock<bool>>,
+    /// The states saved by `Command::SaveState`, one per `GameBoy`.
+    saved_states: Vec<SaveState>,
+}
+
+impl Runner {
+    /// Runs the `GameBoy`s until told to stop, or until the GUI is gone.
+    fn run(mut self) {
+        let mut start = Instant::now();
+        let mut emulated = Duration::ZERO;
+        let mut last_snapshot = Instant::now();
+        let mut was_paused = false;
+
+        loop {
+            let mut changed = false;
+            loop {
+                match self.commands.try_recv() {
+                    Ok(Command::Stop) | Err(TryRecvError::Disconnected) => {
+                        self.gameboys.iter_mut().for_each(GameBoy::power_off);
+                        return;
+                    }
+                    Ok(command) => {
+                        self.handle(command);
+                        changed = true;
+                    }
+                    Err(TryRecvError::Empty) => break,
+                }
+            }
+
+            let paused = *self.step_manually.read().unwrap();
+            if paused || *self.fast_forward.read().unwrap() {
+                start = Instant::now();
+                emulated = Duration::ZERO;
+            }
+            if paused {
+                // The GUI shows the exact state while paused
+                if changed || !was_paused {
+                    self.publish();
+                }
+                was_paused = true;
+                thread::sleep(Duration::from_millis(1));
+                continue;
+            }
+            was_paused = false;
+
+            // The others are kept in step with the first one, which sets the pace
+            let cycles_before = self.gameboys[0].cpu.cycles;
+            for _ in 0..STEPS_PER_CHECK {
+                link::step_lockstep(&mut self.gameboys);
+            }
+            let cycles = self.gameboys[0].cpu.cycles - cycles_before;
+
+            if last_snapshot.elapsed() >= SNAPSHOT_INTERVAL {
+                self.publish();
+                last_snapshot = Instant::now();
+            }
+
+            // The CPU runs twice as many cycles per second in double speed mode
+            let rate = if self.gameboys[0].memory().double_speed {
+                CLOCK_RATE * 2
+            } else {
+                CLOCK_RATE
+            };
+            emulated += Duration::from_secs_f64(cycles as f64 / rate as f64);
+            if let Some(ahead) = emulated.checked_sub(start.elapsed()) {
+                thread::sleep(ahead);
+            }
+        }
+    }
+
+    /// Executes a single instruction on the first `GameBoy`, then lets the
+    /// others catch up with it.
+    fn handle(&mut self, command: Command) {
+        match command {
+            Command::SetButtons(index, buttons) => {
+                if let Some(gameboy) = self.gameboys.get_mut(index) {
+                    gameboy.set_buttons(buttons);
+                }
+            }
+            Command::Step => link::step_lockstep(&mut self.gameboys),
+            Command::SaveState => {
+                self.saved_states = self.gameboys.iter().map(GameBoy::save_state).collect();
+            }
+            Command::LoadState => {
+                for (gameboy, state) in self.gameboys.iter_mut().zip(&self.saved_states) {
+                    gameboy.load_state(state);
+                }
+            }
+            Command::SetRenderer(index, renderer) => {
+                if let Some(gameboy) = self.gameboys.get_mut(index) {
+                    gameboy.memory_mut().ppu.renderer = renderer;
+                }
+            }
+            Command::SetOamCorruption(enabled) => {
+                self.gameboys[0].memory_mut().oam_corruption = enabled;
+            }
+            Command::SetMuted(muted) => self.gameboys[0].memory_mut().apu.muted = muted,
+            Command::SetAudioSink(sink) => self.gameboys[0].set_audio_sink(sink),
+            Command::Stop => unreachable!("Stop is handled by the run loop"),
+        }
+    }
+
+    /// Replaces the snapshots waiting for the GUI with new ones.
+    fn publish(&self) {
+        let snapshots = self.gameboys.iter().map(Snapshot::capture).collect();
+        *self.snapshots.lock().unwrap() = Some(snapshots);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gameboy::audio::RingBufferSink;
+    use crate::gameboy::wav::WavSink;
+
+    /// Waits for a snapshot of the first `GameBoy` satisfying the given condition.
+    fn wait_for(emulator: &Emulator, condition: impl Fn(&Snapshot) -> bool) -> bool {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while Instant::now() < deadline {
+            if let Some(snapshots) = emulator.take_snapshots() {
+                if condition(&snapshots[0]) {
+                    return true;
+                }
+            }
+            thread::sleep(Duration::from_millis(1));
+        }
+        false
+    }
+
+    #[test]
+    fn test_commands_change_snapshots() {
+        let step_manually = Arc::new(RwLock::new(true));
+        let emulator = Emulator::spawn(vec![GameBoy::new()], step_manually, Arc::default());
+
+        // The boot ROM starts with LD SP, u16
+        emulator.send(Command::Step);
+        assert!(wait_for(&emulator, |snapshot| snapshot.pc == 3));
+
+        emulator.send(Command::SetOamCorruption(true));
+        assert!(wait_for(&emulator, |snapshot| snapshot.oam_corruption));
+    }
+
+    #[test]
+    fn test_stop_joins_thread() {
+        let mut emulator = Emulator::spawn(vec![GameBoy::new()], Arc::default(), Arc::default());
+        let commands = emulator.commands();
+
+        emulator.stop();
+        assert!(emulator.thread.is_none());
+        // The thread dropped its end of the channel when it returned
+        assert!(commands.send(Command::Step).is_err());
+
+        // Stopping again, as dropping does, is harmless
+        emulator.stop();
+    }
+
+    #[test]
+    fn test_stop_completes_recording() {
+        let path = std::env::temp_dir().join(format!("discogb-test-{}.wav", std::process::id()));
+        let mut emulator = Emulator::spawn(vec![GameBoy::new()], Arc::default(), Arc::default());
+        let sink = WavSink::create(&path, 48_000, false).unwrap();
+        emulator.send(Command::SetAudioSink(Box::new(sink)));
+        thread::sleep(Duration::from_millis(50));
+        emulator.stop();
+
+        let bytes = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let size =
+            |offset: usize| u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap());
+        assert!(bytes.len() > 44);
+        assert_eq!(size(4) as usize, bytes.len() - 8);
+        assert_eq!(size(40) as usize, bytes.len() - 44);
+    }
+
+    #[test]
+    fn test_running_drains_audio_into_sink() {
+        let emulator = Emulator::spawn(vec![GameBoy::new()], Arc::default(), Arc::default());
+
+        // The sink is swapped on the running thread, which takes the APU's samples every step
+        let reader = RingBufferSink::new(4096);
+        emulator.send(Command::SetAudioSink(Box::new(reader.clone())));
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while reader.is_empty() && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(1));
+        }
+        assert!(!reader.is_empty());
+
+        // Nothing is left to pile up in the APU between two steps
+        assert!(wait_for(&emulator, |snapshot| snapshot
+            .apu
+            .clone()
+            .take_samples()
+            .is_empty()));
+    }
+}
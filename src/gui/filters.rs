@@ -0,0 +1,122 @@
+use egui::Color32;
+
+use super::config::FilterConfig;
+
+/// The gamma of the CGB LCD, used to mix colours in linear light.
+const LCD_GAMMA: f32 = 2.2;
+/// The share of each linear CGB channel in the corrected red, green and blue
+/// channels. The LCD's channels bleed into each other, washing colours out.
+const COLOR_MATRIX: [[f32; 3]; 3] = [
+    [26.0 / 32.0, 4.0 / 32.0, 2.0 / 32.0],
+    [0.0, 24.0 / 32.0, 8.0 / 32.0],
+    [6.0 / 32.0, 4.0 / 32.0, 22.0 / 32.0],
+];
+
+/// How much the gaps between LCD pixels darken the image, out of 255.
+const DOT_MATRIX_GAP: u16 = 200;
+
+/// Returns the colour correction lookup table, indexed by RGB555 colour.
+pub fn color_correction_table() -> Vec<Color32> {
+    (0..=0x7fff).map(correct_color).collect()
+}
+
+/// Converts a CGB colour (RGB555) to the colour the LCD displays it as.
+pub fn correct_color(color: u16) -> Color32 {
+    let linear = [0, 5, 10].map(|shift| (((color >> shift) & 0x1f) as f32 / 31.0).powf(LCD_GAMMA));
+    let [r, g, b] = COLOR_MATRIX.map(|weights| {
+        let mixed: f32 = weights.iter().zip(linear).map(|(w, c)| w * c).sum();
+        (mixed.powf(1.0 / LCD_GAMMA) * 255.0).round() as u8
+    });
+    Color32::from_rgb(r, g, b)
+}
+
+/// Blends the current frame into the previous one, which then holds the
+/// displayed frame. Pixels fade out over several frames like on the LCD.
+pub fn blend_frames(previous: &mut [Color32], current: &[Color32]) {
+    for (previous, current) in previous.iter_mut().zip(current) {
+        let [r, g, b] = [0, 1, 2].map(|i| ((previous[i] as u16 + current[i] as u16) / 2) as u8);
+        *previous = Color32::from_rgb(r, g, b);
+    }
+}
+
+/// Scales the frame up by the given number of screen pixels per LCD pixel,
+/// darkening the bottom and right edges of each pixel to show the grid
+/// between the LCD pixels. Generating it at the displayed scale keeps the
+/// grid from being resampled into a moiré pattern.
+pub fn dot_matrix(
+    pixels: &[Color32],
+    width: usize,
+    height: usize,
+    scale: usize,
+) -> egui::ColorImage {
+    let size = [width * scale, height * scale];
+    let gap = |color: Color32| {
+        let [r, g, b] = [0, 1, 2].map(|i| (color[i] as u16 * DOT_MATRIX_GAP / 255) as u8);
+        Color32::from_rgb(r, g, b)
+    };
+
+    let mut image = egui::ColorImage::new(size, Color32::BLACK);
+    for (y, row) in image.pixels.chunks_mut(size[0]).enumerate() {
+        let edge_row = y % scale == scale - 1;
+        for (x, pixel) in row.iter_mut().enumerate() {
+            let edge = edge_row || x % scale == scale - 1;
+            let color = pixels[(y / scale) * width + x / scale];
+            *pixel = if edge { gap(color) } else { color };
+        }
+    }
+    image
+}
+
+/// Displays the toggles of the display filters.
+/// Returns `true` if any of them was changed.
+pub fn filters_ui(ui: &mut egui::Ui, filters: &mut FilterConfig) -> bool {
+    let mut changed = false;
+    changed |= ui
+        .checkbox(&mut filters.color_correction, "CGB colour correction")
+        .changed();
+    changed |= ui
+        .checkbox(&mut filters.frame_blending, "LCD ghosting")
+        .changed();
+    changed |= ui
+        .checkbox(&mut filters.dot_matrix, "Dot-matrix grid")
+        .changed();
+    changed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_color_correction_keeps_extremes() {
+        assert_eq!(correct_color(0x7fff), Color32::WHITE);
+        assert_eq!(correct_color(0x0000), Color32::BLACK);
+
+        // Pure red loses saturation
+        let red = correct_color(0x001f);
+        assert!(red.r() < 255 && red.b() > 0);
+    }
+
+    #[test]
+    fn test_blend_frames() {
+        let mut previous = vec![Color32::WHITE];
+        blend_frames(&mut previous, &[Color32::BLACK]);
+        assert_eq!(previous[0], Color32::from_rgb(127, 127, 127));
+    }
+
+    #[test]
+    fn test_dot_matrix() {
+        let image = dot_matrix(&[Color32::WHITE, Color32::BLACK], 2, 1, 3);
+        assert_eq!(image.size, [6, 3]);
+        assert_eq!(image.pixels[0], Color32::WHITE);
+        assert_eq!(image.pixels[2], Color32::from_rgb(200, 200, 200));
+        assert_eq!(image.pixels[3], Color32::BLACK);
+        assert_eq!(image.pixels[12], Color32::from_rgb(200, 200, 200));
+
+        // The grid follows the scale it is displayed at
+        let image = dot_matrix(&[Color32::WHITE], 1, 1, 4);
+        assert_eq!(image.size, [4, 4]);
+        assert_eq!(image.pixels[2], Color32::WHITE);
+        assert_eq!(image.pixels[3], Color32::from_rgb(200, 200, 200));
+    }
+}
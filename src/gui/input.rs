@@ -0,0 +1,220 @@
+use crate::gameboy::memory::{Button, Buttons};
+
+use super::config::KeyConfig;
+
+/// The emulator functions that can be bound to a key.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Hotkey {
+    Pause,
+    Step,
+    FastForward,
+    SaveState,
+    LoadState,
+    Screenshot,
+}
+
+impl Hotkey {
+    pub const ALL: [Hotkey; 6] = [
+        Hotkey::Pause,
+        Hotkey::Step,
+        Hotkey::FastForward,
+        Hotkey::SaveState,
+        Hotkey::LoadState,
+        Hotkey::Screenshot,
+    ];
+}
+
+/// Anything a key can be bound to.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Binding {
+    Button(Button),
+    Hotkey(Hotkey),
+}
+
+impl Binding {
+    /// Returns every binding, in the order they are listed in the settings.
+    pub fn all() -> impl Iterator<Item = Binding> {
+        Button::ALL
+            .into_iter()
+            .map(Binding::Button)
+            .chain(Hotkey::ALL.into_iter().map(Binding::Hotkey))
+    }
+
+    /// Returns the name shown in the settings.
+    pub fn label(self) -> &'static str {
+        match self {
+            Binding::Button(Button::A) => "A",
+            Binding::Button(Button::B) => "B",
+            Binding::Button(Button::Select) => "Select",
+            Binding::Button(Button::Start) => "Start",
+            Binding::Button(Button::Right) => "Right",
+            Binding::Button(Button::Left) => "Left",
+            Binding::Button(Button::Up) => "Up",
+            Binding::Button(Button::Down) => "Down",
+            Binding::Hotkey(Hotkey::Pause) => "Pause",
+            Binding::Hotkey(Hotkey::Step) => "Step",
+            Binding::Hotkey(Hotkey::FastForward) => "Fast-forward",
+            Binding::Hotkey(Hotkey::SaveState) => "Save state",
+            Binding::Hotkey(Hotkey::LoadState) => "Load state",
+            Binding::Hotkey(Hotkey::Screenshot) => "Screenshot",
+        }
+    }
+
+    /// Returns the name of the key bound in the given configuration, for editing.
+    pub fn key_name(self, keys: &mut KeyConfig) -> &mut String {
+        match self {
+            Binding::Button(Button::A) => &mut keys.a,
+            Binding::Button(Button::B) => &mut keys.b,
+            Binding::Button(Button::Select) => &mut keys.select,
+            Binding::Button(Button::Start) => &mut keys.start,
+            Binding::Button(Button::Right) => &mut keys.right,
+            Binding::Button(Button::Left) => &mut keys.left,
+            Binding::Button(Button::Up) => &mut keys.up,
+            Binding::Button(Button::Down) => &mut keys.down,
+            Binding::Hotkey(Hotkey::Pause) => &mut keys.pause,
+            Binding::Hotkey(Hotkey::Step) => &mut keys.step,
+            Binding::Hotkey(Hotkey::FastForward) => &mut keys.fast_forward,
+            Binding::Hotkey(Hotkey::SaveState) => &mut keys.save_state,
+            Binding::Hotkey(Hotkey::LoadState) => &mut keys.load_state,
+            Binding::Hotkey(Hotkey::Screenshot) => &mut keys.screenshot,
+        }
+    }
+}
+
+/// Every key that can be bound.
+const KEYS: [egui::Key; 73] = {
+    use egui::Key::*;
+    [
+        ArrowDown, ArrowLeft, ArrowRight, ArrowUp, Escape, Tab, Backspace, Enter, Space, Insert,
+        Delete, Home, End, PageUp, PageDown, Minus, PlusEquals, Num0, Num1, Num2, Num3, Num4, Num5,
+        Num6, Num7, Num8, Num9, A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S, T, U, V,
+        W, X, Y, Z, F1, F2, F3, F4, F5, F6, F7, F8, F9, F10, F11, F12, F13, F14, F15, F16, F17,
+        F18, F19, F20,
+    ]
+};
+
+/// Returns the key with the given name, as given by `egui::Key::name`.
+pub fn key_from_name(name: &str) -> Option<egui::Key> {
+    KEYS.into_iter().find(|key| key.name() == name)
+}
+
+/// The keys of a `KeyConfig`, resolved once rather than on every frame.
+#[derive(Debug, Clone, Default)]
+pub struct KeyMap {
+    buttons: Vec<(Button, egui::Key)>,
+    hotkeys: Vec<(Hotkey, egui::Key)>,
+}
+
+impl KeyMap {
+    /// Resolves the given key names, ignoring (and reporting) unknown ones.
+    pub fn new(keys: &KeyConfig) -> Self {
+        let mut keys = keys.clone();
+        let mut map = Self::default();
+        for binding in Binding::all() {
+            let name = binding.key_name(&mut keys);
+            let Some(key) = key_from_name(name) else {
+                eprintln!("Unknown key \"{}\" bound to {}", name, binding.label());
+                continue;
+            };
+            match binding {
+                Binding::Button(button) => map.buttons.push((button, key)),
+                Binding::Hotkey(hotkey) => map.hotkeys.push((hotkey, key)),
+            }
+        }
+        map
+    }
+
+    /// Returns the joypad buttons whose keys are held down.
+    pub fn pressed_buttons(&self, input: &egui::InputState) -> Buttons {
+        let mut buttons = Buttons::default();
+        for (button, key) in &self.buttons {
+            buttons.set(*button, input.key_down(*key));
+        }
+        buttons
+    }
+
+    /// Returns whether the key of the given hotkey was pressed this frame.
+    pub fn hotkey_pressed(&self, input: &egui::InputState, hotkey: Hotkey) -> bool {
+        self.hotkey_key(hotkey)
+            .is_some_and(|key| input.key_pressed(key))
+    }
+
+    /// Returns whether the key of the given hotkey is held down.
+    pub fn hotkey_down(&self, input: &egui::InputState, hotkey: Hotkey) -> bool {
+        self.hotkey_key(hotkey)
+            .is_some_and(|key| input.key_down(key))
+    }
+
+    fn hotkey_key(&self, hotkey: Hotkey) -> Option<egui::Key> {
+        self.hotkeys
+            .iter()
+            .find(|(bound, _)| *bound == hotkey)
+            .map(|(_, key)| *key)
+    }
+}
+
+/// Shows the key bound to each button and hotkey; clicking one waits for the
+/// next key press to rebind it. Returns `true` if a binding changed.
+pub fn key_bindings_ui(
+    ui: &mut egui::Ui,
+    keys: &mut KeyConfig,
+    rebinding: &mut Option<Binding>,
+) -> bool {
+    let mut changed = false;
+
+    if let Some(binding) = *rebinding {
+        let pressed = ui.input(|i| {
+            i.events.iter().find_map(|event| match event {
+                egui::Event::Key {
+                    key, pressed: true, ..
+                } => Some(*key),
+                _ => None,
+            })
+        });
+        match pressed {
+            Some(egui::Key::Escape) => *rebinding = None,
+            Some(key) => {
+                *binding.key_name(keys) = key.name().to_string();
+                *rebinding = None;
+                changed = true;
+            }
+            None => {}
+        }
+    }
+
+    egui::Grid::new("key_bindings")
+        .num_columns(2)
+        .show(ui, |ui| {
+            for binding in Binding::all() {
+                ui.label(binding.label());
+                let text = if *rebinding == Some(binding) {
+                    "Press a key…".to_string()
+                } else {
+                    binding.key_name(keys).clone()
+                };
+                if ui.button(text).clicked() {
+                    *rebinding = Some(binding);
+                }
+                ui.end_row();
+            }
+        });
+
+    if ui.button("Reset to defaults").clicked() {
+        *keys = KeyConfig::default();
+        changed = true;
+    }
+    changed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_keys_resolve() {
+        let map = KeyMap::new(&KeyConfig::default());
+        assert_eq!(map.buttons.len(), Button::ALL.len());
+        assert_eq!(map.hotkey_key(Hotkey::Step), Some(egui::Key::Space));
+        assert_eq!(key_from_name("Up"), Some(egui::Key::ArrowUp));
+    }
+}
@@ -1,20 +1,41 @@
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, RwLock};
 use std::time::Duration;
 
 use egui_extras::Size;
 use egui_grid::{Grid, GridBuilder};
 
-use crate::gameboy::memory::Memory;
+use crate::gameboy::audio::NullSink;
+use crate::gameboy::memory::{Buttons, Memory, VRAM_SIZE};
+use crate::gameboy::wav::WavSink;
 use crate::gameboy::GameBoy;
 
-const DISPLAY_WIDTH: usize = 160;
-const DISPLAY_HEIGHT: usize = 144;
+pub mod config;
+use config::Config;
 
+mod display;
+use display::Display;
+
+mod emulator;
+use emulator::{Command, Emulator, Snapshot};
+
+mod filters;
+
+mod input;
+use input::{Binding, Hotkey, KeyMap};
+
+pub mod palette;
+use palette::PaletteEditor;
+
+mod sound;
+
+#[allow(clippy::upper_case_acronyms)]
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 enum MemoryView {
     ROM0,
     ROM1,
-    VRAM,
+    VRAM0,
+    VRAM1,
     ERAM,
     WRAM0,
     WRAM1,
@@ -23,74 +44,251 @@ enum MemoryView {
     HRAM,
 }
 
+impl MemoryView {
+    /// Every view, in the order of their declaration.
+    const ALL: [MemoryView; 10] = [
+        MemoryView::ROM0,
+        MemoryView::ROM1,
+        MemoryView::VRAM0,
+        MemoryView::VRAM1,
+        MemoryView::ERAM,
+        MemoryView::WRAM0,
+        MemoryView::WRAM1,
+        MemoryView::OAM,
+        MemoryView::IO,
+        MemoryView::HRAM,
+    ];
+
+    /// Returns the contents of the view in the given memory.
+    fn contents(self, mem: &Memory) -> &[u8] {
+        let (vram0, vram1) = mem.vram.split_at(VRAM_SIZE as usize);
+        match self {
+            MemoryView::ROM0 => mem.rom.bank0.as_slice(),
+            MemoryView::ROM1 => mem.rom.bankn(),
+            MemoryView::VRAM0 => vram0,
+            MemoryView::VRAM1 => vram1,
+            MemoryView::ERAM => mem.eram.as_slice(),
+            MemoryView::WRAM0 => mem.wram.bank0.as_slice(),
+            MemoryView::WRAM1 => mem.wram.bankn(),
+            MemoryView::OAM => mem.oam.as_slice(),
+            MemoryView::IO => mem.io.as_slice(),
+            MemoryView::HRAM => mem.hram.as_slice(),
+        }
+    }
+}
+
 pub struct GuiState {
-    pub gameboy: GameBoy,
+    /// The thread running the `GameBoy`s.
+    emulator: Emulator,
+    /// The latest snapshots of the `GameBoy`s, the first one first.
+    snapshots: Vec<Snapshot>,
+    /// The buttons last sent to the joypad of each `GameBoy`.
+    buttons: Vec<Buttons>,
+    /// The sample rate of the audio of the first `GameBoy`.
+    sample_rate: u32,
+    pub config: Config,
     display: Display,
+    /// The displays of the `GameBoy`s linked to the first one, shown next to it.
+    linked_displays: Vec<Display>,
+    palette_editor: PaletteEditor,
     pub step_manually: Arc<RwLock<bool>>,
     selected_memory_view: MemoryView,
+    /// The path entered in the audio menu to record to.
+    wav_path: String,
+    /// Whether each channel is also recorded to its own file.
+    record_stems: bool,
+    /// The WAV file being recorded, if any.
+    recording: Option<PathBuf>,
+    /// The channel played alone, if any.
+    solo_channel: Option<usize>,
+    /// The keys of `config.keys`.
+    key_map: KeyMap,
+    /// Whether the key bindings window is open.
+    show_key_bindings: bool,
+    /// The binding waiting for a key press, if any.
+    rebinding: Option<Binding>,
+    /// Whether the emulation runs as fast as possible rather than in real time.
+    pub fast_forward: Arc<RwLock<bool>>,
+}
+
+impl Default for GuiState {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl GuiState {
     pub fn new() -> Self {
-        let gameboy = GameBoy::new();
-        Self {
-            display: Display {
-                texture: None,
-                memory: gameboy.memory.clone(),
-            },
-            gameboy,
-            step_manually: Arc::new(RwLock::new(true)),
+        Self::with_gameboys(vec![GameBoy::new()], Config::load())
+    }
+
+    /// Creates the GUI for the given `GameBoy`s, the first one first, with the
+    /// given configuration, and starts running them on their own thread.
+    /// The others are linked to the first one, and shown next to it.
+    pub fn with_gameboys(gameboys: Vec<GameBoy>, config: Config) -> Self {
+        let step_manually = Arc::new(RwLock::new(true));
+        let fast_forward = Arc::new(RwLock::new(false));
+        let snapshots: Vec<_> = gameboys.iter().map(Snapshot::capture).collect();
+        let sample_rate = gameboys[0].sample_rate();
+        let emulator = Emulator::spawn(gameboys, step_manually.clone(), fast_forward.clone());
+
+        let mut displays: Vec<_> = (0..snapshots.len())
+            .map(|index| {
+                Display::new(
+                    index,
+                    emulator.commands(),
+                    palette::selected(&config.palettes),
+                    config.filters.clone(),
+                )
+            })
+            .collect();
+        let display = displays.remove(0);
+
+        let mut state = Self {
+            emulator,
+            buttons: vec![Buttons::default(); snapshots.len()],
+            snapshots: Vec::new(),
+            sample_rate,
+            display,
+            linked_displays: displays,
+            palette_editor: PaletteEditor::default(),
+            step_manually,
             selected_memory_view: MemoryView::ROM0,
+            wav_path: "discogb.wav".to_string(),
+            record_stems: false,
+            recording: None,
+            solo_channel: None,
+            key_map: KeyMap::new(&config.keys),
+            show_key_bindings: false,
+            rebinding: None,
+            fast_forward,
+            config,
+        };
+        state.show_snapshots(snapshots);
+        state
+    }
+
+    /// Starts recording the audio output to the given WAV file, reporting failures on stderr.
+    pub fn start_recording(&mut self, path: &Path, stems: bool) {
+        match WavSink::create(path, self.sample_rate, stems) {
+            Ok(sink) => {
+                self.emulator.send(Command::SetAudioSink(Box::new(sink)));
+                self.recording = Some(path.to_path_buf());
+            }
+            Err(err) => eprintln!("Failed to create {}: {}", path.display(), err),
         }
     }
 
-    pub fn run(&mut self) {
-        // Step the CPU if the user has enabled manual stepping
-        if !(self.step_manually.read().unwrap().to_owned()) {
-            self.gameboy.cpu.write().unwrap().step();
+    /// Stops recording the audio output, completing the WAV files.
+    pub fn stop_recording(&mut self) {
+        self.emulator
+            .send(Command::SetAudioSink(Box::new(NullSink)));
+        self.recording = None;
+    }
+
+    /// Returns the latest snapshot of the first `GameBoy`.
+    fn snapshot(&self) -> &Snapshot {
+        &self.snapshots[0]
+    }
+
+    /// Shows the given snapshots of the `GameBoy`s, the first one first.
+    fn show_snapshots(&mut self, mut snapshots: Vec<Snapshot>) {
+        let displays = std::iter::once(&mut self.display).chain(&mut self.linked_displays);
+        for (display, snapshot) in displays.zip(&mut snapshots) {
+            display.frame = std::mem::take(&mut snapshot.frame);
         }
+        self.snapshots = snapshots;
     }
-}
 
-struct Display {
-    memory: Arc<RwLock<Memory>>,
-    texture: Option<egui::TextureHandle>,
+    /// Returns the displays of every `GameBoy`, the first one first.
+    fn displays(&mut self) -> impl Iterator<Item = &mut Display> {
+        std::iter::once(&mut self.display).chain(&mut self.linked_displays)
+    }
+
+    /// Writes the configuration file, reporting failures on stderr.
+    fn save_config(&self) {
+        if let Err(err) = self.config.save() {
+            eprintln!("Failed to save config file: {}", err);
+        }
+    }
+
+    /// Handles joypad input and hotkeys, and schedules the next repaint.
+    fn step_ui(&mut self, ctx: &egui::Context) {
+        // The keys control the joypad of the LCD that has the focus
+        let pressed = ctx.input(|i| self.key_map.pressed_buttons(i));
+        let focused: Vec<_> = self.displays().map(|display| display.focused).collect();
+        for (index, focused) in focused.into_iter().enumerate() {
+            let buttons = if focused { pressed } else { Buttons::default() };
+            if self.buttons[index] != buttons {
+                self.buttons[index] = buttons;
+                self.emulator.send(Command::SetButtons(index, buttons));
+            }
+        }
+
+        // Hotkeys are ignored while typing in a text field or choosing a key to bind
+        let lcd_focused = self.displays().any(|display| display.focused);
+        if self.rebinding.is_none() && (lcd_focused || !ctx.wants_keyboard_input()) {
+            self.hotkeys_ui(ctx);
+        }
+
+        // request call to this update function (60 FPS)
+        ctx.request_repaint_after(Duration::from_secs_f64(1.0 / 60.0));
+    }
+
+    /// Performs the functions of the hotkeys pressed this frame.
+    fn hotkeys_ui(&mut self, ctx: &egui::Context) {
+        let key_map = self.key_map.clone();
+        let pressed = |hotkey| ctx.input(|i| key_map.hotkey_pressed(i, hotkey));
+
+        if pressed(Hotkey::Pause) {
+            let mut step_manually = self.step_manually.write().unwrap();
+            *step_manually = !*step_manually;
+        }
+        if pressed(Hotkey::Step) && *self.step_manually.read().unwrap() {
+            self.emulator.send(Command::Step);
+        }
+        if pressed(Hotkey::SaveState) {
+            self.emulator.send(Command::SaveState);
+        }
+        if pressed(Hotkey::LoadState) {
+            self.emulator.send(Command::LoadState);
+        }
+        if pressed(Hotkey::Screenshot) {
+            match self
+                .linked_displays
+                .iter_mut()
+                .find(|display| display.focused)
+            {
+                Some(display) => display.save_screenshot(),
+                None => self.display.save_screenshot(),
+            }
+        }
+
+        *self.fast_forward.write().unwrap() =
+            ctx.input(|i| self.key_map.hotkey_down(i, Hotkey::FastForward));
+    }
 }
 
-impl Display {
-    fn ui(&mut self, ui: &mut egui::Ui) {
-        let texture: &egui::TextureHandle = self.texture.get_or_insert_with(|| {
-            let mut addr = 0x8000;
-            let first_sprite = std::iter::repeat_with(|| {
-                let tmp = addr;
-                addr += 1;
-                tmp
-            })
-            .take(16)
-            .map(|addr| self.memory.read().unwrap().read_byte(addr))
-            .collect::<Vec<_>>();
-
-            // Create pixel buffer
-            let pixels = {
-                let mut pixels = [0; 160 * 144 * 3];
-                for i in 0..160 * 144 {
-                    pixels[i * 3] = 255;
-                    pixels[i * 3 + 1] = 82;
-                    pixels[i * 3 + 2] = 82;
-                }
-                pixels[0] = first_sprite[0];
-                pixels
-            };
-
-            // Load the texture only once
-            ui.ctx().load_texture(
-                "lcd_display",
-                egui::ColorImage::from_rgb([DISPLAY_WIDTH, DISPLAY_HEIGHT], &pixels),
-                egui::TextureOptions::default(),
-            )
-        });
+/// Shows the controls to record the audio output to WAV files.
+fn audio_menu_ui(state: &mut GuiState, ui: &mut egui::Ui) {
+    if let Some(path) = &state.recording {
+        ui.label(format!("Recording to {}", path.display()));
+        if ui.button("Stop recording").clicked() {
+            state.stop_recording();
+            ui.close_menu();
+        }
+        return;
+    }
 
-        ui.image(texture, texture.size_vec2());
+    ui.horizontal(|ui| {
+        ui.label("File");
+        ui.text_edit_singleline(&mut state.wav_path);
+    });
+    ui.checkbox(&mut state.record_stems, "Record each channel separately");
+    if ui.button("Start recording").clicked() {
+        let path = PathBuf::from(&state.wav_path);
+        state.start_recording(&path, state.record_stems);
+        ui.close_menu();
     }
 }
 
@@ -126,34 +324,22 @@ fn flags_ui(state: &mut GuiState, ui: &mut egui::Ui) {
         .show(ui, |mut grid| {
             grid.cell(|ui| {
                 ui.vertical_centered(|ui| {
-                    ui.monospace(format!(
-                        "Z: {}",
-                        state.gameboy.cpu.read().unwrap().get_flag_z()
-                    ));
+                    ui.monospace(format!("Z: {}", state.snapshot().flag(7)));
                 });
             });
             grid.cell(|ui| {
                 ui.vertical_centered(|ui| {
-                    ui.monospace(format!(
-                        "N: {}",
-                        state.gameboy.cpu.read().unwrap().get_flag_n()
-                    ));
+                    ui.monospace(format!("N: {}", state.snapshot().flag(6)));
                 });
             });
             grid.cell(|ui| {
                 ui.vertical_centered(|ui| {
-                    ui.monospace(format!(
-                        "H: {}",
-                        state.gameboy.cpu.read().unwrap().get_flag_h()
-                    ));
+                    ui.monospace(format!("H: {}", state.snapshot().flag(5)));
                 });
             });
             grid.cell(|ui| {
                 ui.vertical_centered(|ui| {
-                    ui.monospace(format!(
-                        "C: {}",
-                        state.gameboy.cpu.read().unwrap().get_flag_c()
-                    ));
+                    ui.monospace(format!("C: {}", state.snapshot().flag(4)));
                 });
             });
         });
@@ -179,9 +365,9 @@ fn register8_grid_cell_ui(grid: &mut Grid, label: &str, value: u8) {
 
 /// Displays the 16-bit (special) registers.
 fn special_registers_ui(state: &mut GuiState, ui: &mut egui::Ui) {
-    register16_ui(ui, "PC", state.gameboy.cpu.read().unwrap().pc);
+    register16_ui(ui, "PC", state.snapshot().pc);
     ui.add_space(5.0);
-    register16_ui(ui, "SP", state.gameboy.cpu.read().unwrap().sp);
+    register16_ui(ui, "SP", state.snapshot().sp);
 }
 
 /// Displays the 8-bit registers in a grid.
@@ -196,29 +382,21 @@ fn registers_ui(state: &mut GuiState, ui: &mut egui::Ui) {
         .new_row(Size::exact(32.5))
         .cells(Size::remainder(), 2)
         .show(ui, |mut grid| {
-            register8_grid_cell_ui(&mut grid, "A", state.gameboy.cpu.read().unwrap().regs.a);
-            register8_grid_cell_ui(&mut grid, "F", state.gameboy.cpu.read().unwrap().regs.f);
-            register8_grid_cell_ui(&mut grid, "B", state.gameboy.cpu.read().unwrap().regs.b);
-            register8_grid_cell_ui(&mut grid, "C", state.gameboy.cpu.read().unwrap().regs.c);
-            register8_grid_cell_ui(&mut grid, "D", state.gameboy.cpu.read().unwrap().regs.d);
-            register8_grid_cell_ui(&mut grid, "E", state.gameboy.cpu.read().unwrap().regs.e);
-            register8_grid_cell_ui(&mut grid, "H", state.gameboy.cpu.read().unwrap().regs.h);
-            register8_grid_cell_ui(&mut grid, "L", state.gameboy.cpu.read().unwrap().regs.l);
+            register8_grid_cell_ui(&mut grid, "A", state.snapshot().regs.a);
+            register8_grid_cell_ui(&mut grid, "F", state.snapshot().regs.f);
+            register8_grid_cell_ui(&mut grid, "B", state.snapshot().regs.b);
+            register8_grid_cell_ui(&mut grid, "C", state.snapshot().regs.c);
+            register8_grid_cell_ui(&mut grid, "D", state.snapshot().regs.d);
+            register8_grid_cell_ui(&mut grid, "E", state.snapshot().regs.e);
+            register8_grid_cell_ui(&mut grid, "H", state.snapshot().regs.h);
+            register8_grid_cell_ui(&mut grid, "L", state.snapshot().regs.l);
         });
 }
 
 /// Displays the special flags IME and HALT.
 fn special_flags_ui(state: &mut GuiState, ui: &mut egui::Ui) {
-    let ime = if state.gameboy.cpu.read().unwrap().ime {
-        1
-    } else {
-        0
-    };
-    let halt = if state.gameboy.cpu.read().unwrap().halt {
-        1
-    } else {
-        0
-    };
+    let ime = if state.snapshot().ime { 1 } else { 0 };
+    let halt = if state.snapshot().halt { 1 } else { 0 };
 
     GridBuilder::new()
         .new_row(Size::exact(15.0))
@@ -250,15 +428,23 @@ fn special_flags_ui(state: &mut GuiState, ui: &mut egui::Ui) {
 fn checkbox_step_manually_ui(state: &mut GuiState, ui: &mut egui::Ui) {
     ui.add_space(5.0);
     ui.vertical(|ui| {
-        ui.checkbox(&mut *state.step_manually.write().unwrap(), "Step manually");
+        ui.checkbox(&mut state.step_manually.write().unwrap(), "Step manually");
     });
 }
 
+fn checkbox_oam_corruption_ui(state: &mut GuiState, ui: &mut egui::Ui) {
+    let enabled = &mut state.snapshots[0].oam_corruption;
+    if ui.checkbox(enabled, "Emulate OAM corruption bug").changed() {
+        state.emulator.send(Command::SetOamCorruption(*enabled));
+    }
+}
+
 fn memory_view_selectable_ui(state: &mut GuiState, ui: &mut egui::Ui) {
     ui.horizontal(|ui| {
         ui.selectable_value(&mut state.selected_memory_view, MemoryView::ROM0, "ROM 0");
         ui.selectable_value(&mut state.selected_memory_view, MemoryView::ROM1, "ROM 1");
-        ui.selectable_value(&mut state.selected_memory_view, MemoryView::VRAM, "VRAM");
+        ui.selectable_value(&mut state.selected_memory_view, MemoryView::VRAM0, "VRAM 0");
+        ui.selectable_value(&mut state.selected_memory_view, MemoryView::VRAM1, "VRAM 1");
         ui.selectable_value(&mut state.selected_memory_view, MemoryView::ERAM, "ERAM");
         ui.selectable_value(&mut state.selected_memory_view, MemoryView::WRAM0, "WRAM 0");
         ui.selectable_value(&mut state.selected_memory_view, MemoryView::WRAM1, "WRAM 1");
@@ -269,7 +455,67 @@ fn memory_view_selectable_ui(state: &mut GuiState, ui: &mut egui::Ui) {
 }
 
 impl eframe::App for GuiState {
-    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+    /// Stops the `GameBoy`s before the process exits, which happens without
+    /// dropping the `GuiState`.
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        self.emulator.stop();
+    }
+
+    fn update(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
+        if let Some(snapshots) = self.emulator.take_snapshots() {
+            self.show_snapshots(snapshots);
+        }
+
+        if frame.info().window_info.fullscreen != self.display.fullscreen {
+            frame.set_fullscreen(self.display.fullscreen);
+        }
+
+        if self.display.fullscreen {
+            egui::CentralPanel::default()
+                .frame(egui::Frame::none().fill(egui::Color32::BLACK))
+                .show(ctx, |ui| {
+                    self.display.fullscreen_ui(ui);
+                });
+            self.step_ui(ctx);
+            return;
+        }
+
+        ctx.input(|i| {
+            if i.key_pressed(egui::Key::F11) {
+                self.display.fullscreen = true;
+            }
+        });
+
+        egui::TopBottomPanel::top("menu_bar").show(ctx, |ui| {
+            egui::menu::bar(ui, |ui| {
+                ui.menu_button("Settings", |ui| {
+                    if filters::filters_ui(ui, &mut self.config.filters) {
+                        let filters = self.config.filters.clone();
+                        self.displays()
+                            .for_each(|display| display.filters = filters.clone());
+                        self.save_config();
+                    }
+                    if ui.button("Key bindings…").clicked() {
+                        self.show_key_bindings = true;
+                        ui.close_menu();
+                    }
+                });
+                ui.menu_button("Audio", |ui| audio_menu_ui(self, ui));
+            });
+        });
+
+        egui::Window::new("Key Bindings")
+            .open(&mut self.show_key_bindings)
+            .resizable(false)
+            .show(ctx, |ui| {
+                if input::key_bindings_ui(ui, &mut self.config.keys, &mut self.rebinding) {
+                    self.key_map = KeyMap::new(&self.config.keys);
+                    if let Err(err) = self.config.save() {
+                        eprintln!("Failed to save config file: {}", err);
+                    }
+                }
+            });
+
         egui::Window::new("Processor")
             .fixed_size(egui::vec2(175.0, 175.0))
             .show(ctx, |ui| {
@@ -293,9 +539,25 @@ impl eframe::App for GuiState {
             });
 
         egui::Window::new("Display")
-            .fixed_size(egui::vec2(175.0, 175.0))
+            .resizable(false)
             .show(ctx, |ui| {
-                self.display.ui(ui);
+                ui.horizontal_top(|ui| {
+                    for display in self.displays() {
+                        ui.vertical(|ui| display.ui(ui));
+                    }
+                });
+            });
+
+        egui::Window::new("Palettes")
+            .resizable(false)
+            .default_open(false)
+            .show(ctx, |ui| {
+                if palette::palettes_ui(ui, &mut self.config, &mut self.palette_editor) {
+                    let palettes = palette::selected(&self.config.palettes);
+                    self.displays()
+                        .for_each(|display| display.palettes = palettes.clone());
+                    self.save_config();
+                }
             });
 
         egui::Window::new("IO Map")
@@ -310,19 +572,10 @@ impl eframe::App for GuiState {
                     memory_view_selectable_ui(self, ui);
                 });
 
-                let mem = self.gameboy.memory.read().unwrap();
+                checkbox_oam_corruption_ui(self, ui);
 
-                let (memory_view, total_rows) = match self.selected_memory_view {
-                    MemoryView::ROM0 => (mem.rom.bank0.as_slice(), mem.rom.bank0.len() / 8),
-                    MemoryView::ROM1 => (mem.rom.bankn.as_slice(), mem.rom.bankn.len() / 8),
-                    MemoryView::VRAM => (mem.vram.as_slice(), mem.vram.len() / 8),
-                    MemoryView::ERAM => (mem.eram.as_slice(), mem.eram.len() / 8),
-                    MemoryView::WRAM0 => (mem.wram.bank0.as_slice(), mem.wram.bank0.len() / 8),
-                    MemoryView::WRAM1 => (mem.wram.bankn.as_slice(), mem.wram.bankn.len() / 8),
-                    MemoryView::OAM => (mem.oam.as_slice(), mem.oam.len() / 8),
-                    MemoryView::IO => (mem.io.as_slice(), mem.io.len() / 8),
-                    MemoryView::HRAM => (mem.hram.as_slice(), mem.hram.len() / 8),
-                };
+                let memory_view = &self.snapshot().memory[self.selected_memory_view as usize];
+                let total_rows = memory_view.len() / 8;
 
                 let text_style = egui::TextStyle::Body;
                 let row_height = ui.text_style_height(&text_style);
@@ -338,7 +591,7 @@ impl eframe::App for GuiState {
                                     let offset = match self.selected_memory_view {
                                         MemoryView::ROM0 => 0x0000,
                                         MemoryView::ROM1 => 0x4000,
-                                        MemoryView::VRAM => 0x8000,
+                                        MemoryView::VRAM0 | MemoryView::VRAM1 => 0x8000,
                                         MemoryView::ERAM => 0xa000,
                                         MemoryView::WRAM0 => 0xc000,
                                         MemoryView::WRAM1 => 0xd000,
@@ -421,19 +674,17 @@ impl eframe::App for GuiState {
             .show(ctx, |_ui| {});
 
         egui::Window::new("Sound Registers")
-            .fixed_size(egui::vec2(175.0, 175.0))
-            .show(ctx, |_ui| {});
-
-        // Step the CPU if the user has enabled manual stepping
-        if self.step_manually.read().unwrap().to_owned() {
-            ctx.input(|i| {
-                if i.key_down(egui::Key::Space) || i.key_pressed(egui::Key::Enter) {
-                    self.gameboy.cpu.write().unwrap().step();
+            .default_width(360.0)
+            .vscroll(true)
+            .show(ctx, |ui| {
+                let apu = &mut self.snapshots[0].apu;
+                let muted = apu.muted;
+                sound::sound_ui(apu, &mut self.solo_channel, ui);
+                if apu.muted != muted {
+                    self.emulator.send(Command::SetMuted(apu.muted));
                 }
             });
-        }
 
-        // request call to this update function (60 FPS)
-        ctx.request_repaint_after(Duration::from_secs_f64(1.0 / 60.0));
+        self.step_ui(ctx);
     }
 }
@@ -0,0 +1,180 @@
+use serde::{Deserialize, Serialize};
+
+use super::config::{Config, PaletteConfig};
+
+/// The name of the palette used when none (or an unknown one) is selected.
+pub const DEFAULT_PALETTE: &str = "Classic";
+
+/// A four-colour palette the DMG shades are displayed with, lightest first.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Palette {
+    pub name: String,
+    pub colors: [[u8; 3]; 4],
+}
+
+impl Palette {
+    /// Creates a new `Palette`.
+    pub fn new(name: &str, colors: [[u8; 3]; 4]) -> Self {
+        Self {
+            name: name.to_string(),
+            colors,
+        }
+    }
+
+    /// Returns the colour of the given shade (0-3).
+    pub fn color(&self, shade: u8) -> egui::Color32 {
+        let [r, g, b] = self.colors[shade as usize];
+        egui::Color32::from_rgb(r, g, b)
+    }
+}
+
+/// Returns the built-in palettes.
+pub fn presets() -> Vec<Palette> {
+    vec![
+        Palette::new(
+            DEFAULT_PALETTE,
+            [
+                [0x9b, 0xbc, 0x0f],
+                [0x8b, 0xac, 0x0f],
+                [0x30, 0x62, 0x30],
+                [0x0f, 0x38, 0x0f],
+            ],
+        ),
+        Palette::new(
+            "Pocket",
+            [
+                [0xe0, 0xdb, 0xcd],
+                [0xa8, 0x9f, 0x94],
+                [0x70, 0x6b, 0x66],
+                [0x2b, 0x2b, 0x26],
+            ],
+        ),
+        Palette::new(
+            "Light",
+            [
+                [0x00, 0xb5, 0x81],
+                [0x00, 0x9a, 0x6e],
+                [0x00, 0x69, 0x4a],
+                [0x00, 0x51, 0x3a],
+            ],
+        ),
+    ]
+}
+
+/// Returns all palettes that can be selected: the presets followed by the custom ones.
+pub fn available(config: &PaletteConfig) -> Vec<Palette> {
+    let mut palettes = presets();
+    palettes.extend(config.custom.iter().cloned());
+    palettes
+}
+
+/// Returns the palette with the given name, or the default palette if there is none.
+pub fn find(config: &PaletteConfig, name: &str) -> Palette {
+    let mut palettes = available(config);
+    match palettes.iter().position(|palette| palette.name == name) {
+        Some(index) => palettes.swap_remove(index),
+        None => palettes.swap_remove(0),
+    }
+}
+
+/// Returns the palettes selected for the background, `OBP0` and `OBP1`, in that order.
+pub fn selected(config: &PaletteConfig) -> [Palette; 3] {
+    [
+        find(config, &config.bg),
+        find(config, &config.obp0),
+        find(config, &config.obp1),
+    ]
+}
+
+/// The state of the custom palette editor.
+pub struct PaletteEditor {
+    name: String,
+    colors: [[u8; 3]; 4],
+}
+
+impl Default for PaletteEditor {
+    fn default() -> Self {
+        let palette = &presets()[0];
+        Self {
+            name: String::from("Custom"),
+            colors: palette.colors,
+        }
+    }
+}
+
+/// Displays the palette selection and the custom palette editor.
+/// Returns `true` if the configuration was changed.
+pub fn palettes_ui(ui: &mut egui::Ui, config: &mut Config, editor: &mut PaletteEditor) -> bool {
+    let palettes = available(&config.palettes);
+    let mut changed = false;
+
+    let selections = [
+        ("Background", &mut config.palettes.bg),
+        ("Sprites (OBP0)", &mut config.palettes.obp0),
+        ("Sprites (OBP1)", &mut config.palettes.obp1),
+    ];
+    for (label, selection) in selections {
+        egui::ComboBox::from_label(label)
+            .selected_text(selection.as_str())
+            .show_ui(ui, |ui| {
+                for palette in &palettes {
+                    changed |= ui
+                        .selectable_value(selection, palette.name.clone(), &palette.name)
+                        .changed();
+                }
+            });
+    }
+
+    ui.collapsing("Custom palette", |ui| {
+        ui.horizontal(|ui| {
+            ui.label("Name");
+            ui.text_edit_singleline(&mut editor.name);
+        });
+        ui.horizontal(|ui| {
+            for color in &mut editor.colors {
+                ui.color_edit_button_srgb(color);
+            }
+        });
+
+        let is_preset = presets().iter().any(|palette| palette.name == editor.name);
+        ui.add_enabled_ui(!editor.name.is_empty() && !is_preset, |ui| {
+            if ui.button("Save palette").clicked() {
+                let palette = Palette::new(&editor.name, editor.colors);
+                let custom = &mut config.palettes.custom;
+                match custom.iter_mut().find(|custom| custom.name == palette.name) {
+                    Some(existing) => *existing = palette,
+                    None => custom.push(palette),
+                }
+                changed = true;
+            }
+        });
+    });
+
+    changed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_falls_back_to_default() {
+        let config = PaletteConfig::default();
+        assert_eq!(find(&config, "Pocket").name, "Pocket");
+        assert_eq!(find(&config, "Missing").name, DEFAULT_PALETTE);
+    }
+
+    #[test]
+    fn test_custom_palettes_round_trip() {
+        let mut config = Config::default();
+        config.palettes.obp1 = String::from("Mine");
+        config
+            .palettes
+            .custom
+            .push(Palette::new("Mine", [[1, 2, 3]; 4]));
+
+        let parsed: Config = toml::from_str(&toml::to_string(&config).unwrap()).unwrap();
+        assert_eq!(parsed, config);
+        assert_eq!(selected(&parsed.palettes)[2].colors, [[1, 2, 3]; 4]);
+    }
+}
@@ -0,0 +1,213 @@
+use egui::plot::{Line, Plot, PlotPoints};
+
+use crate::gameboy::apu::{self, Apu, CHANNELS, NR10_ADDR, NR20_ADDR, NR40_ADDR, WAVE_RAM_SIZE};
+
+const CHANNEL_NAMES: [&str; CHANNELS] = ["Pulse 1", "Pulse 2", "Wave", "Noise"];
+const DUTY_CYCLES: [&str; 4] = ["12.5%", "25%", "50%", "75%"];
+const WAVE_VOLUMES: [&str; 4] = ["Mute", "100%", "50%", "25%"];
+const NOTE_NAMES: [&str; 12] = [
+    "C", "C#", "D", "D#", "E", "F", "F#", "G", "G#", "A", "A#", "B",
+];
+
+/// Returns the name of the note closest to the given frequency, e.g. "A4",
+/// if it is within the range of a piano.
+pub fn note_name(hz: f64) -> Option<String> {
+    let midi = (69.0 + 12.0 * (hz / 440.0).log2()).round();
+    if !(21.0..=108.0).contains(&midi) {
+        return None;
+    }
+
+    let midi = midi as usize;
+    Some(format!("{}{}", NOTE_NAMES[midi % 12], midi / 12 - 1))
+}
+
+/// Shows the decoded sound registers, wave RAM and channel outputs, with
+/// toggles to mute a channel or play it alone.
+pub fn sound_ui(apu: &mut Apu, solo: &mut Option<usize>, ui: &mut egui::Ui) {
+    ui.label(format!(
+        "Power: {}   Volume: L {} R {}",
+        on_off(apu.powered),
+        (apu.nr50 >> 4) & 0b111,
+        apu.nr50 & 0b111
+    ));
+
+    for channel in 0..CHANNELS {
+        ui.group(|ui| {
+            channel_header_ui(apu, channel, solo, ui);
+            egui::Grid::new(("sound_channel", channel))
+                .num_columns(2)
+                .show(ui, |ui| channel_details_ui(apu, channel, ui));
+            scope_ui(apu, channel, ui);
+        });
+    }
+
+    ui.label("Wave RAM");
+    let points: PlotPoints = (0..WAVE_RAM_SIZE * 2)
+        .map(|i| {
+            let byte = apu.ch3.ram[i / 2];
+            let sample = if i % 2 == 0 { byte >> 4 } else { byte & 0x0f };
+            [i as f64, sample as f64]
+        })
+        .collect();
+    Plot::new("wave_ram")
+        .height(60.0)
+        .include_y(0.0)
+        .include_y(15.0)
+        .allow_drag(false)
+        .allow_zoom(false)
+        .allow_scroll(false)
+        .show_axes([false, true])
+        .show(ui, |plot| plot.line(Line::new(points)));
+}
+
+/// Shows the state of a channel, with its mute and solo toggles.
+fn channel_header_ui(apu: &mut Apu, channel: usize, solo: &mut Option<usize>, ui: &mut egui::Ui) {
+    let (enabled, dac) = match channel {
+        0 => (apu.ch1.enabled, apu.ch1.envelope.dac_enabled()),
+        1 => (apu.ch2.enabled, apu.ch2.envelope.dac_enabled()),
+        2 => (apu.ch3.enabled, apu.ch3.dac_enabled),
+        _ => (apu.ch4.enabled, apu.ch4.envelope.dac_enabled()),
+    };
+    let left = apu.nr51 & (1 << (channel + 4)) != 0;
+    let right = apu.nr51 & (1 << channel) != 0;
+
+    ui.horizontal(|ui| {
+        ui.strong(CHANNEL_NAMES[channel]);
+        ui.label(format!(
+            "{} | DAC {} | {}{}",
+            if enabled { "Playing" } else { "Stopped" },
+            on_off(dac),
+            if left { "L" } else { "-" },
+            if right { "R" } else { "-" },
+        ));
+
+        if ui.checkbox(&mut apu.muted[channel], "Mute").changed() {
+            *solo = None;
+        }
+        let mut soloed = *solo == Some(channel);
+        if ui.checkbox(&mut soloed, "Solo").changed() {
+            *solo = soloed.then_some(channel);
+            for (other, muted) in apu.muted.iter_mut().enumerate() {
+                *muted = soloed && other != channel;
+            }
+        }
+    });
+}
+
+/// Shows the decoded registers of a channel.
+fn channel_details_ui(apu: &Apu, channel: usize, ui: &mut egui::Ui) {
+    let mut row = |name: &str, value: String| {
+        ui.label(name);
+        ui.label(value);
+        ui.end_row();
+    };
+
+    let (hz, length) = match channel {
+        0 | 1 => {
+            let (pulse, base) = if channel == 0 {
+                (&apu.ch1, NR10_ADDR)
+            } else {
+                (&apu.ch2, NR20_ADDR)
+            };
+            if channel == 0 {
+                let nr10 = apu.read_register(NR10_ADDR);
+                row(
+                    "Sweep",
+                    format!(
+                        "period {} | {} | shift {}",
+                        (nr10 >> 4) & 0b111,
+                        if nr10 & 0b1000 != 0 { "down" } else { "up" },
+                        nr10 & 0b111
+                    ),
+                );
+            }
+            row("Duty", DUTY_CYCLES[pulse.duty as usize].to_string());
+            row(
+                "Envelope",
+                envelope(apu.read_register(base + 2), pulse.envelope.volume),
+            );
+            (pulse.frequency_hz(), &pulse.length)
+        }
+        2 => {
+            row("Volume", WAVE_VOLUMES[apu.ch3.volume as usize].to_string());
+            (apu.ch3.frequency_hz(), &apu.ch3.length)
+        }
+        _ => {
+            let noise = &apu.ch4;
+            row(
+                "LFSR",
+                format!(
+                    "{} bits | divisor {} | shift {}",
+                    if noise.short { 7 } else { 15 },
+                    noise.divisor,
+                    noise.shift
+                ),
+            );
+            row(
+                "Envelope",
+                envelope(apu.read_register(NR40_ADDR + 2), noise.envelope.volume),
+            );
+            (noise.frequency_hz(), &noise.length)
+        }
+    };
+
+    let note = note_name(hz).unwrap_or_default();
+    row("Frequency", format!("{:.1} Hz {}", hz, note));
+    row(
+        "Length",
+        format!("{} ({})", length.counter, on_off(length.enabled)),
+    );
+}
+
+/// Returns the description of a volume envelope register and its current volume.
+fn envelope(register: u8, volume: u8) -> String {
+    format!(
+        "volume {} -> {} | {} | period {}",
+        register >> 4,
+        volume,
+        if register & 0b1000 != 0 { "up" } else { "down" },
+        register & 0b111
+    )
+}
+
+/// Plots the recent output of a channel.
+fn scope_ui(apu: &Apu, channel: usize, ui: &mut egui::Ui) {
+    let points: PlotPoints = apu
+        .scope
+        .channel(channel)
+        .enumerate()
+        .map(|(i, output)| [i as f64, output as f64])
+        .collect();
+
+    Plot::new(("sound_scope", channel))
+        .height(40.0)
+        .include_x(0.0)
+        .include_x(apu::SCOPE_LENGTH as f64)
+        .include_y(-1.0)
+        .include_y(1.0)
+        .allow_drag(false)
+        .allow_zoom(false)
+        .allow_scroll(false)
+        .show_axes([false, false])
+        .show(ui, |plot| plot.line(Line::new(points)));
+}
+
+fn on_off(value: bool) -> &'static str {
+    if value {
+        "on"
+    } else {
+        "off"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_note_name() {
+        assert_eq!(note_name(440.0).as_deref(), Some("A4"));
+        assert_eq!(note_name(261.63).as_deref(), Some("C4"));
+        assert_eq!(note_name(5.0), None);
+    }
+}
@@ -1,25 +1,170 @@
-use std::thread;
+use std::path::PathBuf;
+use std::{env, fs, process};
 
+use discogb::gameboy::infrared::ConstantLight;
+use discogb::gameboy::link::{self, SocketLink, PLAYERS};
+use discogb::gameboy::printer::Printer;
+use discogb::gameboy::{GameBoy, Model};
+use discogb::gui::config::Config;
 use discogb::gui::GuiState;
 
+const USAGE: &str =
+    "Usage: discogb [--model dmg|sgb|cgb] [--record-wav FILE [--stems]] [--link ROM | --link-listen ADDR | --link-connect ADDR | --printer DIR | --four-player] [--ir-light] [--accurate-timing] [ROM]";
+
+/// The options given on the command line.
+#[derive(Debug, Default)]
+struct Args {
+    rom: Option<String>,
+    /// The model to emulate instead of the one detected from the ROM.
+    model: Option<Model>,
+    /// The WAV file to record the audio to.
+    wav: Option<PathBuf>,
+    /// Whether each channel is also recorded to its own WAV file.
+    stems: bool,
+    /// The ROM of a second `GameBoy` connected to the first by a link cable.
+    link: Option<String>,
+    /// The other DiscoGB process to connect to with a link cable.
+    remote: Option<Remote>,
+    /// The directory to save the printouts of a Game Boy Printer plugged into the link port.
+    printer: Option<PathBuf>,
+    /// Whether a constant light shines on the infrared port, rather than none.
+    ir_light: bool,
+    /// Whether 3 more `GameBoy`s running the same ROM are plugged into a four-player adapter.
+    four_player: bool,
+    /// Whether the CPU advances the rest of the system on every memory access.
+    accurate_timing: bool,
+}
+
+/// How to reach the other end of a link cable to another process: at
+/// `host:port`, or at a Unix domain socket path prefixed with `unix:`.
+#[derive(Debug)]
+enum Remote {
+    Listen(String),
+    Connect(String),
+}
+
+/// Prints the usage and exits.
+fn usage() -> ! {
+    eprintln!("{}", USAGE);
+    process::exit(1);
+}
+
+/// Parses the command line.
+fn parse_args() -> Args {
+    let mut parsed = Args::default();
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--model" => {
+                parsed.model = match args.next().as_deref() {
+                    Some("dmg") => Some(Model::Dmg),
+                    Some("sgb") => Some(Model::Sgb),
+                    Some("cgb") => Some(Model::Cgb),
+                    _ => usage(),
+                }
+            }
+            "--record-wav" => parsed.wav = Some(args.next().unwrap_or_else(|| usage()).into()),
+            "--stems" => parsed.stems = true,
+            "--link" => parsed.link = Some(args.next().unwrap_or_else(|| usage())),
+            "--link-listen" => {
+                parsed.remote = Some(Remote::Listen(args.next().unwrap_or_else(|| usage())))
+            }
+            "--link-connect" => {
+                parsed.remote = Some(Remote::Connect(args.next().unwrap_or_else(|| usage())))
+            }
+            "--ir-light" => parsed.ir_light = true,
+            "--four-player" => parsed.four_player = true,
+            "--accurate-timing" => parsed.accurate_timing = true,
+            "--printer" => parsed.printer = Some(args.next().unwrap_or_else(|| usage()).into()),
+            _ if parsed.rom.is_none() && !arg.starts_with('-') => parsed.rom = Some(arg),
+            _ => usage(),
+        }
+    }
+
+    // Only one device can be plugged into the link port
+    let devices = [
+        parsed.link.is_some(),
+        parsed.remote.is_some(),
+        parsed.printer.is_some(),
+        parsed.four_player,
+    ];
+    if parsed.stems && parsed.wav.is_none()
+        || parsed.four_player && parsed.rom.is_none()
+        || devices.iter().filter(|plugged| **plugged).count() > 1
+    {
+        usage();
+    }
+    parsed
+}
+
+/// Loads the ROM at the given path into a new `GameBoy`, exiting if it can't be read.
+fn load_gameboy(path: &str, model: Option<Model>) -> GameBoy {
+    let rom = fs::read(path).unwrap_or_else(|err| {
+        eprintln!("Failed to read {}: {}", path, err);
+        process::exit(1);
+    });
+    GameBoy::with_rom(&rom, model)
+}
+
 fn main() {
+    let args = parse_args();
+    let mut gameboy = match &args.rom {
+        Some(path) => load_gameboy(path, args.model),
+        None => GameBoy::new(),
+    };
+
     let options = eframe::NativeOptions {
         initial_window_size: Some(egui::vec2(1080.0, 720.0)),
         ..Default::default()
     };
 
-    let state = GuiState::new();
-    let cpu = state.gameboy.cpu.clone();
-    let should_step_manually = state.step_manually.clone();
-
-    thread::Builder::new()
-        .name("GameBoy Run-Loop".to_string())
-        .spawn(move || loop {
-            if !should_step_manually.read().unwrap().to_owned() {
-                cpu.write().unwrap().step();
+    if args.ir_light {
+        let light = ConstantLight { on: true };
+        gameboy.set_infrared_device(Box::new(light));
+    }
+    if let Some(dir) = args.printer {
+        gameboy.set_serial_device(Box::new(Printer::new(dir)));
+    }
+    if let Some(remote) = args.remote {
+        let (link, addr) = match &remote {
+            Remote::Listen(addr) => {
+                eprintln!("Waiting for the other DiscoGB on {}", addr);
+                (SocketLink::listen(addr), addr)
+            }
+            Remote::Connect(addr) => (SocketLink::connect(addr), addr),
+        };
+        match link {
+            Ok(link) => {
+                gameboy.set_infrared_device(Box::new(link.infrared()));
+                gameboy.set_serial_device(Box::new(link));
+            }
+            Err(err) => {
+                eprintln!("Failed to link with {}: {}", addr, err);
+                process::exit(1);
             }
-        })
-        .unwrap();
+        }
+    }
+
+    // The linked GameBoys are shown next to the first one
+    let mut gameboys = vec![gameboy];
+    if let Some(path) = args.link {
+        let mut linked = load_gameboy(&path, args.model);
+        link::connect(&mut gameboys[0], &mut linked);
+        gameboys.push(linked);
+    }
+    if let (true, Some(path)) = (args.four_player, &args.rom) {
+        gameboys.extend((1..PLAYERS).map(|_| load_gameboy(path, args.model)));
+        link::connect_adapter(&mut gameboys);
+    }
+    for gameboy in &mut gameboys {
+        gameboy.cpu.accurate_timing = args.accurate_timing;
+    }
+
+    let mut state = GuiState::with_gameboys(gameboys, Config::load());
+    if let Some(path) = args.wav {
+        state.start_recording(&path, args.stems);
+    }
 
     eframe::run_native("DiscoGB", options, Box::new(|_cc| Box::new(state))).unwrap();
 }